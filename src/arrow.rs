@@ -0,0 +1,498 @@
+//! Apache Arrow and Parquet support (requires the `arrow` feature).
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, Write},
+    path::Path,
+    str::FromStr,
+    sync::Arc,
+};
+
+use ::arrow::{
+    array::{
+        Array, ArrayRef, BooleanArray, Float64Array, Int64Array, RecordBatch, StringArray,
+        UInt16Array, UInt8Array,
+    },
+    datatypes::{DataType, Field, Schema},
+};
+
+use parquet::{
+    arrow::{arrow_reader::ParquetRecordBatchReaderBuilder, ArrowWriter},
+    file::reader::ChunkReader,
+};
+
+use crate::{
+    prelude::{
+        ClockOffset, FormattingError, Header, Key, Observable, Observation, ParsingError, Record,
+        TimeScale, DORIS, SNR,
+    },
+    record::columns::{
+        duration_from_seconds, epoch_flag, epoch_flag_value, epoch_from_tai_nanoseconds,
+        phase_flag, phase_flag_value, snr_value, tai_nanoseconds, StationResolver,
+        CLOCK_EXTRAPOLATED_COLUMN, CLOCK_OFFSET_COLUMN, EPOCH_COLUMN, FLAG_COLUMN,
+        OBSERVABLE_COLUMN, PHASE_FLAG_COLUMN, SNR_COLUMN, STATION_CODE_COLUMN,
+        STATION_DOMES_COLUMN, STATION_LABEL_COLUMN, VALUE_COLUMN,
+    },
+};
+
+/// Schema metadata: [Header], as DORIS RINEX header section
+pub const HEADER_METADATA: &str = "doris.header";
+
+/// Schema metadata: RINEX revision
+pub const VERSION_METADATA: &str = "doris.version";
+
+/// Schema metadata: satellite name
+pub const SATELLITE_METADATA: &str = "doris.satellite";
+
+/// Schema metadata: satellite COSPAR number
+pub const COSPAR_METADATA: &str = "doris.cospar";
+
+/// Schema metadata: [TimeScale] of the record
+pub const TIME_SCALE_METADATA: &str = "doris.time_scale";
+
+/// Schema metadata: record comments, one per line
+pub const COMMENTS_METADATA: &str = "doris.comments";
+
+/// Returns the [Schema] of the DORIS [RecordBatch]
+fn schema(metadata: HashMap<String, String>) -> Schema {
+    Schema::new(vec![
+        Field::new(EPOCH_COLUMN, DataType::Int64, false),
+        Field::new(FLAG_COLUMN, DataType::UInt8, false),
+        Field::new(STATION_CODE_COLUMN, DataType::UInt16, true),
+        Field::new(STATION_LABEL_COLUMN, DataType::Utf8, true),
+        Field::new(STATION_DOMES_COLUMN, DataType::Utf8, true),
+        Field::new(OBSERVABLE_COLUMN, DataType::Utf8, true),
+        Field::new(VALUE_COLUMN, DataType::Float64, true),
+        Field::new(SNR_COLUMN, DataType::UInt8, true),
+        Field::new(PHASE_FLAG_COLUMN, DataType::UInt8, true),
+        Field::new(CLOCK_OFFSET_COLUMN, DataType::Float64, true),
+        Field::new(CLOCK_EXTRAPOLATED_COLUMN, DataType::Boolean, true),
+    ])
+    .with_metadata(metadata)
+}
+
+/// Returns the column of this [RecordBatch], with expected type
+fn column<'a, T: 'static>(batch: &'a RecordBatch, name: &str) -> Result<&'a T, ParsingError> {
+    batch
+        .column_by_name(name)
+        .and_then(|column| column.as_any().downcast_ref::<T>())
+        .ok_or(ParsingError::ArrowSchema)
+}
+
+impl DORIS {
+    /// Returns the schema metadata describing this [DORIS] [Header]
+    fn arrow_metadata(&self) -> Result<HashMap<String, String>, FormattingError> {
+        let mut metadata = HashMap::new();
+
+        metadata.insert(HEADER_METADATA.to_string(), self.header.format_string()?);
+
+        metadata.insert(
+            VERSION_METADATA.to_string(),
+            format!(
+                "{}.{:02}",
+                self.header.version.major, self.header.version.minor
+            ),
+        );
+
+        metadata.insert(
+            SATELLITE_METADATA.to_string(),
+            self.header.satellite.clone(),
+        );
+
+        if let Some(cospar) = &self.header.cospar {
+            metadata.insert(COSPAR_METADATA.to_string(), cospar.to_string());
+        }
+
+        if let Some(key) = self.record.measurements.keys().next() {
+            metadata.insert(
+                TIME_SCALE_METADATA.to_string(),
+                key.epoch.time_scale.to_string(),
+            );
+        }
+
+        if !self.record.comments.is_empty() {
+            metadata.insert(
+                COMMENTS_METADATA.to_string(),
+                self.record.comments.join("\n"),
+            );
+        }
+
+        Ok(metadata)
+    }
+
+    /// Converts this [DORIS] record to Arrow [RecordBatch], one row per observation.
+    /// Epochs that do not contain any observation (like events) are described by a row
+    /// without station and observable. The satellite [ClockOffset] is repeated on each
+    /// row of the epoch. [Header] information is stored in the schema metadata,
+    /// so the conversion is reversible, see [Self::from_record_batch].
+    /// Requires the `arrow` feature.
+    ///
+    /// | Column               | Type    | Content                                  |
+    /// |----------------------|---------|------------------------------------------|
+    /// | `epoch`              | Int64   | TAI nanoseconds (since J1900)            |
+    /// | `flag`               | UInt8   | [EpochFlag] (RINEX value)                |
+    /// | `station_code`       | UInt16  | [GroundStation] unique code              |
+    /// | `station_label`      | Utf8    | [GroundStation] label                    |
+    /// | `station_domes`      | Utf8    | [GroundStation] DOMES site number        |
+    /// | `observable`         | Utf8    | [Observable] (RINEX code)                |
+    /// | `value`              | Float64 | Observed value, [Observable] dependent   |
+    /// | `snr`                | UInt8   | [SNR] (RINEX value)                      |
+    /// | `phase_flag`         | UInt8   | [PhaseFlag] (RINEX value)                |
+    /// | `clock_offset`       | Float64 | Satellite [ClockOffset], in seconds      |
+    /// | `clock_extrapolated` | Boolean | True for extrapolated [ClockOffset]      |
+    ///
+    /// ```
+    /// use doris_rs::prelude::*;
+    ///
+    /// let doris = DORIS::from_gzip_file("data/DOR/V3/cs2rx18164.gz")
+    ///     .unwrap();
+    ///
+    /// let batch = doris.to_record_batch()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(batch.schema().metadata()["doris.satellite"], "CRYOSAT-2");
+    /// ```
+    pub fn to_record_batch(&self) -> Result<RecordBatch, FormattingError> {
+        let columnar = self.record.to_columnar();
+
+        let len = columnar.len()
+            + (0..columnar.keys().len())
+                .filter(|nth| columnar.epoch_rows(*nth).is_empty())
+                .count();
+
+        let mut epochs = Vec::with_capacity(len);
+        let mut flags = Vec::with_capacity(len);
+        let mut codes = Vec::with_capacity(len);
+        let mut labels = Vec::with_capacity(len);
+        let mut domes = Vec::with_capacity(len);
+        let mut observables = Vec::with_capacity(len);
+        let mut values = Vec::with_capacity(len);
+        let mut snr = Vec::with_capacity(len);
+        let mut phase_flags = Vec::with_capacity(len);
+        let mut clock_offsets = Vec::with_capacity(len);
+        let mut extrapolated = Vec::with_capacity(len);
+
+        let station_domes = columnar
+            .stations()
+            .iter()
+            .map(|station| station.domes.to_string())
+            .collect::<Vec<_>>();
+
+        let station_observables = columnar
+            .observables()
+            .iter()
+            .map(|observable| format!("{:x}", observable))
+            .collect::<Vec<_>>();
+
+        for (nth, key) in columnar.keys().iter().enumerate() {
+            let epoch_ns = tai_nanoseconds(key.epoch);
+            let flag = epoch_flag_value(key.flag);
+            let clock_offset = columnar.clock_offsets()[nth];
+
+            let rows = columnar.epoch_rows(nth);
+
+            if rows.is_empty() {
+                epochs.push(epoch_ns);
+                flags.push(flag);
+                codes.push(None);
+                labels.push(None);
+                domes.push(None);
+                observables.push(None);
+                values.push(None);
+                snr.push(None);
+                phase_flags.push(None);
+                clock_offsets.push(clock_offset.map(|clock| clock.offset.to_seconds()));
+                extrapolated.push(clock_offset.map(|clock| clock.extrapolated));
+            }
+
+            for row in rows {
+                let index = columnar.station_indices()[row] as usize;
+                let station = &columnar.stations()[index];
+
+                epochs.push(epoch_ns);
+                flags.push(flag);
+                codes.push(Some(station.code));
+                labels.push(Some(station.label.as_str()));
+                domes.push(Some(station_domes[index].as_str()));
+                observables.push(Some(station_observables[row].as_str()));
+                values.push(Some(columnar.values()[row]));
+                snr.push(columnar.snr()[row].map(snr_value));
+                phase_flags.push(columnar.phase_flags()[row].map(phase_flag_value));
+                clock_offsets.push(clock_offset.map(|clock| clock.offset.to_seconds()));
+                extrapolated.push(clock_offset.map(|clock| clock.extrapolated));
+            }
+        }
+
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(Int64Array::from(epochs)),
+            Arc::new(UInt8Array::from(flags)),
+            Arc::new(UInt16Array::from(codes)),
+            Arc::new(StringArray::from(labels)),
+            Arc::new(StringArray::from(domes)),
+            Arc::new(StringArray::from(observables)),
+            Arc::new(Float64Array::from(values)),
+            Arc::new(UInt8Array::from(snr)),
+            Arc::new(UInt8Array::from(phase_flags)),
+            Arc::new(Float64Array::from(clock_offsets)),
+            Arc::new(BooleanArray::from(extrapolated)),
+        ];
+
+        let schema = schema(self.arrow_metadata()?);
+
+        Ok(RecordBatch::try_new(Arc::new(schema), columns)?)
+    }
+
+    /// Converts Arrow [RecordBatch] to [DORIS]. This is the mirror operation
+    /// of [Self::to_record_batch]. Requires the `arrow` feature.
+    pub fn from_record_batch(batch: &RecordBatch) -> Result<Self, ParsingError> {
+        Self::from_record_batches(std::slice::from_ref(batch))
+    }
+
+    /// Converts Arrow [RecordBatch]es, sharing the same schema, to [DORIS].
+    /// The [Header] is described by the schema metadata of the first [RecordBatch].
+    /// Requires the `arrow` feature.
+    pub fn from_record_batches(batches: &[RecordBatch]) -> Result<Self, ParsingError> {
+        let metadata = match batches.first() {
+            Some(batch) => batch.schema().metadata().clone(),
+            None => HashMap::new(),
+        };
+
+        Self::from_arrow(&metadata, batches)
+    }
+
+    /// Converts Arrow [RecordBatch]es to [DORIS], with [Header] described by this schema metadata
+    fn from_arrow(
+        metadata: &HashMap<String, String>,
+        batches: &[RecordBatch],
+    ) -> Result<Self, ParsingError> {
+        let header = match metadata.get(HEADER_METADATA) {
+            Some(header) => Header::parse(&mut BufReader::new(header.as_bytes()))?,
+            None => Header::default(),
+        };
+
+        let time_scale = match metadata.get(TIME_SCALE_METADATA) {
+            Some(time_scale) => {
+                TimeScale::from_str(time_scale).map_err(|_| ParsingError::ArrowSchema)?
+            },
+            None => TimeScale::TAI,
+        };
+
+        let mut record = Record::default();
+
+        if let Some(comments) = metadata.get(COMMENTS_METADATA) {
+            record.comments = comments.lines().map(|line| line.to_string()).collect();
+        }
+
+        let mut stations = StationResolver::new(&header.ground_stations);
+
+        for batch in batches.iter() {
+            let epochs = column::<Int64Array>(batch, EPOCH_COLUMN)?;
+            let flags = column::<UInt8Array>(batch, FLAG_COLUMN)?;
+            let codes = column::<UInt16Array>(batch, STATION_CODE_COLUMN)?;
+            let labels = column::<StringArray>(batch, STATION_LABEL_COLUMN)?;
+            let domes = column::<StringArray>(batch, STATION_DOMES_COLUMN)?;
+            let observables = column::<StringArray>(batch, OBSERVABLE_COLUMN)?;
+            let values = column::<Float64Array>(batch, VALUE_COLUMN)?;
+            let snr = column::<UInt8Array>(batch, SNR_COLUMN)?;
+            let phase_flags = column::<UInt8Array>(batch, PHASE_FLAG_COLUMN)?;
+            let clock_offsets = column::<Float64Array>(batch, CLOCK_OFFSET_COLUMN)?;
+            let extrapolated = column::<BooleanArray>(batch, CLOCK_EXTRAPOLATED_COLUMN)?;
+
+            for row in 0..batch.num_rows() {
+                let key = Key {
+                    epoch: epoch_from_tai_nanoseconds(epochs.value(row), time_scale),
+                    flag: epoch_flag(flags.value(row))?,
+                };
+
+                let measurements = record.measurements.entry(key).or_default();
+
+                if clock_offsets.is_valid(row) {
+                    measurements.satellite_clock_offset = Some(ClockOffset {
+                        offset: duration_from_seconds(clock_offsets.value(row)),
+                        extrapolated: extrapolated.is_valid(row) && extrapolated.value(row),
+                    });
+                }
+
+                if observables.is_null(row) {
+                    continue;
+                }
+
+                if codes.is_null(row) || values.is_null(row) {
+                    return Err(ParsingError::ArrowSchema);
+                }
+
+                let label = labels.is_valid(row).then(|| labels.value(row));
+                let site_domes = domes.is_valid(row).then(|| domes.value(row));
+
+                let station = stations.resolve(Some(codes.value(row)), label, site_domes)?;
+
+                let mut observation = Observation::default().with_value(values.value(row));
+
+                if snr.is_valid(row) {
+                    observation = observation.with_snr(SNR::from(snr.value(row)));
+                }
+
+                if phase_flags.is_valid(row) {
+                    observation = observation.with_phase_flag(phase_flag(phase_flags.value(row))?);
+                }
+
+                measurements.add_observation(
+                    station,
+                    Observable::from_str(observables.value(row))?,
+                    observation,
+                );
+            }
+        }
+
+        Ok(DORIS::new(header, record))
+    }
+
+    /// Formats this [DORIS] record as Parquet, into [Write]able interface.
+    /// See [Self::to_record_batch] for the table description.
+    /// Requires the `arrow` feature.
+    pub fn format_parquet<W: Write + Send>(&self, writer: W) -> Result<(), FormattingError> {
+        let batch = self.to_record_batch()?;
+
+        let mut writer = ArrowWriter::try_new(writer, batch.schema(), None)?;
+
+        writer.write(&batch)?;
+        writer.close()?;
+
+        Ok(())
+    }
+
+    /// Dumps this [DORIS] record as Parquet file.
+    /// See [Self::to_record_batch] for the table description.
+    /// Requires the `arrow` feature.
+    ///
+    /// ```
+    /// use doris_rs::prelude::*;
+    ///
+    /// let doris = DORIS::from_gzip_file("data/DOR/V3/cs2rx18164.gz")
+    ///     .unwrap();
+    ///
+    /// let path = std::env::temp_dir().join("cs2rx18164.parquet");
+    ///
+    /// doris.to_parquet_file(&path)
+    ///     .unwrap();
+    ///
+    /// let parsed = DORIS::from_parquet_file(&path)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(parsed.record, doris.record);
+    /// ```
+    pub fn to_parquet_file<P: AsRef<Path>>(&self, path: P) -> Result<(), FormattingError> {
+        let fd = File::create(path)?;
+        self.format_parquet(fd)
+    }
+
+    /// Parses [DORIS] from Parquet content, see [Self::from_record_batches].
+    /// Requires the `arrow` feature.
+    pub fn parse_parquet<R: ChunkReader + 'static>(reader: R) -> Result<Self, ParsingError> {
+        let builder = ParquetRecordBatchReaderBuilder::try_new(reader)?;
+
+        // batches do not carry the schema metadata
+        let metadata = builder.schema().metadata().clone();
+
+        let batches = builder.build()?.collect::<Result<Vec<_>, _>>()?;
+
+        Self::from_arrow(&metadata, &batches)
+    }
+
+    /// Parses [DORIS] from local Parquet file, see [Self::from_record_batches].
+    /// Requires the `arrow` feature.
+    pub fn from_parquet_file<P: AsRef<Path>>(path: P) -> Result<Self, ParsingError> {
+        let fd = File::open(path)?;
+        Self::parse_parquet(fd)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prelude::{
+        ClockOffset, Duration, EpochFlag, Frequency, GroundStation, Key, Observable, PhaseFlag,
+        DORIS, SNR,
+    };
+    use crate::tests::toolkit::{add_synthetic_samples, synthetic_record, synthetic_t0};
+
+    #[test]
+    fn arrow_parquet_round_trip() {
+        let l1 = Observable::UnambiguousPhaseRange(Frequency::DORIS1);
+        let l2 = Observable::UnambiguousPhaseRange(Frequency::DORIS2);
+
+        let toulouse = GroundStation::default()
+            .with_site_label("TLSB")
+            .with_site_name("TOULOUSE")
+            .with_frequency_shift(-5)
+            .with_unique_id(1);
+
+        // not described in the header
+        let grasse = GroundStation::default()
+            .with_site_label("GR4B")
+            .with_unique_id(2);
+
+        let mut record = synthetic_record(
+            &toulouse,
+            &[
+                (0.0, vec![(l1, 1.0), (l2, 2.0)]),
+                (10.0, vec![(l1, 3.0), (Observable::Temperature, 20.5)]),
+            ],
+        );
+
+        add_synthetic_samples(&mut record, &grasse, &[(10.0, vec![(l2, 4.0)])]);
+
+        let t0 = synthetic_t0();
+
+        let measurements = record.measurements.values_mut().next().unwrap();
+
+        measurements.satellite_clock_offset = Some(ClockOffset::from_extrapolated_offset(
+            Duration::from_seconds(-4.25),
+        ));
+
+        for observation in measurements.observations.values_mut() {
+            *observation = observation
+                .with_snr(SNR::DbHz42_47)
+                .with_phase_flag(PhaseFlag::Discontinuity);
+        }
+
+        record.measurements.insert(
+            Key {
+                epoch: t0 + Duration::from_seconds(5.0),
+                flag: EpochFlag::ExternalEvent,
+            },
+            Default::default(),
+        );
+
+        record.comments.push("record comment".to_string());
+
+        let mut doris = DORIS::default();
+        doris.header.satellite = "CRYOSAT-2".to_string();
+        doris.header.observables = vec![l1, l2, Observable::Temperature];
+        doris.header.ground_stations = vec![toulouse.clone()];
+        doris.record = record;
+
+        let batch = doris.to_record_batch().unwrap();
+
+        assert_eq!(batch.num_rows(), 6);
+        assert_eq!(batch.num_columns(), 11);
+        assert_eq!(batch.schema().metadata()["doris.satellite"], "CRYOSAT-2");
+        assert_eq!(batch.schema().metadata()["doris.time_scale"], "TAI");
+
+        let parsed = DORIS::from_record_batch(&batch).unwrap();
+
+        assert_eq!(parsed.record, doris.record);
+        assert_eq!(parsed.header.satellite, "CRYOSAT-2");
+        assert_eq!(parsed.header.ground_stations, vec![toulouse]);
+
+        // parquet
+        let path = std::env::temp_dir().join("doris-arrow-round-trip.parquet");
+
+        doris.to_parquet_file(&path).unwrap();
+
+        let parsed = DORIS::from_parquet_file(&path).unwrap();
+        assert_eq!(parsed.record, doris.record);
+        assert_eq!(parsed.header.ground_stations, doris.header.ground_stations);
+
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,286 @@
+//! Per pass beacon frequency offset estimation
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    constants::SPEED_OF_LIGHT_M_S,
+    prelude::{Pass, PassResiduals, RangeRateResiduals},
+    statistics::{mean, rms},
+};
+
+/// [BeaconOffset] is the residual frequency offset of a beacon,
+/// estimated over one [Pass].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BeaconOffset {
+    /// [Pass] being analyzed
+    pub pass: Pass,
+
+    /// Number of range rate samples
+    pub samples: usize,
+
+    /// Relative frequency offset (δf/f), at the center of the [Pass]
+    pub relative_offset: f64,
+
+    /// Relative frequency drift, in s⁻¹, when estimated
+    pub relative_drift: Option<f64>,
+
+    /// Post fit residuals RMS, in m/s
+    pub post_fit_rms: f64,
+
+    /// True when the offset jumped with respect to the previous
+    /// [Pass] of this beacon, see [BeaconOffsetEstimator::jump_threshold]
+    pub jump: bool,
+}
+
+impl BeaconOffset {
+    /// Returns the frequency offset on S1, in Hertz
+    pub fn s1_offset_hz(&self) -> f64 {
+        self.relative_offset * self.pass.station.s1_frequency_shift()
+    }
+
+    /// Returns the frequency offset on U2, in Hertz
+    pub fn u2_offset_hz(&self) -> f64 {
+        self.relative_offset * self.pass.station.u2_frequency_shift()
+    }
+}
+
+/// [BeaconOffsetEstimator] estimates, by least squares, the residual frequency
+/// offset (and optionally drift) of each beacon, over each [Pass], from
+/// the [RangeRateResiduals]. A frequency offset δf/f appears as
+/// a constant range rate residual c.δf/f.
+///
+/// ```no_run
+/// use doris_rs::prelude::*;
+///
+/// let sinex = Sinex::from_gzip_file("data/SNX/dpod2020_031.snx.gz")
+///     .unwrap();
+///
+/// let sp3 = SP3::from_gzip_file("data/SP3/ssacs220.b18164.e18165.DG_.sp3.001.gz")
+///     .unwrap();
+///
+/// // coordinates propagated to the beginning of the day
+/// let t0 = Epoch::from_gregorian_utc_at_midnight(2018, 6, 13);
+///
+/// let doris = DORIS::from_gzip_file("data/DOR/V3/cs2rx18164.gz")
+///     .unwrap()
+///     .with_station_coordinates(&sinex, t0);
+///
+/// let residuals = doris.range_rate_residuals(&sp3, &RangeRateModel::default())
+///     .unwrap();
+///
+/// let estimator = BeaconOffsetEstimator::default()
+///     .with_drift(true);
+///
+/// for offset in estimator.estimate(&residuals) {
+///     if offset.jump {
+///         println!("{}: offset jumped to {:.3E}", offset.pass.station.label, offset.relative_offset);
+///     }
+/// }
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BeaconOffsetEstimator {
+    /// Estimate the frequency drift as well
+    pub drift: bool,
+
+    /// Minimal number of samples per [Pass]
+    pub min_samples: usize,
+
+    /// Relative offset variation between two successive [Pass]es
+    /// of a beacon, above which the beacon offset is considered to have jumped.
+    pub jump_threshold: f64,
+}
+
+impl Default for BeaconOffsetEstimator {
+    fn default() -> Self {
+        Self {
+            drift: false,
+            min_samples: 5,
+            jump_threshold: 1.0E-10,
+        }
+    }
+}
+
+impl BeaconOffsetEstimator {
+    /// Copies and returns [BeaconOffsetEstimator] with drift estimation enabled or not
+    pub fn with_drift(&self, drift: bool) -> Self {
+        let mut s = *self;
+        s.drift = drift;
+        s
+    }
+
+    /// Copies and returns [BeaconOffsetEstimator] with updated minimal number of samples per pass
+    pub fn with_min_samples(&self, min_samples: usize) -> Self {
+        let mut s = *self;
+        s.min_samples = min_samples;
+        s
+    }
+
+    /// Copies and returns [BeaconOffsetEstimator] with updated relative offset jump threshold
+    pub fn with_jump_threshold(&self, threshold: f64) -> Self {
+        let mut s = *self;
+        s.jump_threshold = threshold;
+        s
+    }
+
+    /// Least squares fit of one [PassResiduals].
+    /// Returns (offset, drift, post fit rms), in m/s units.
+    fn fit(&self, pass: &PassResiduals) -> Option<(f64, Option<f64>, f64)> {
+        if pass.samples.len() < self.min_samples.max(2) {
+            return None;
+        }
+
+        let t_ref = pass.samples[0].epoch;
+
+        let t = pass
+            .samples
+            .iter()
+            .map(|sample| (sample.epoch - t_ref).to_seconds())
+            .collect::<Vec<_>>();
+
+        let y = pass
+            .samples
+            .iter()
+            .map(|sample| sample.residual())
+            .collect::<Vec<_>>();
+
+        let t_mid = mean(&t)?;
+        let y_mean = mean(&y)?;
+
+        let (offset, drift) = if self.drift {
+            let s_tt = t.iter().map(|t| (t - t_mid).powi(2)).sum::<f64>();
+
+            if s_tt == 0.0 {
+                return None;
+            }
+
+            let s_ty = t
+                .iter()
+                .zip(y.iter())
+                .map(|(t, y)| (t - t_mid) * (y - y_mean))
+                .sum::<f64>();
+
+            (y_mean, Some(s_ty / s_tt))
+        } else {
+            (y_mean, None)
+        };
+
+        let post_fit = t
+            .iter()
+            .zip(y.iter())
+            .map(|(t, y)| y - offset - drift.unwrap_or_default() * (t - t_mid))
+            .collect::<Vec<_>>();
+
+        Some((offset, drift, rms(&post_fit)?))
+    }
+
+    /// Estimates the [BeaconOffset] of each [Pass] of these [RangeRateResiduals].
+    /// Passes that do not have enough samples are not reported.
+    pub fn estimate(&self, residuals: &RangeRateResiduals) -> Vec<BeaconOffset> {
+        let mut offsets = residuals
+            .passes
+            .iter()
+            .filter_map(|pass| {
+                let (offset, drift, post_fit_rms) = self.fit(pass)?;
+
+                Some(BeaconOffset {
+                    pass: pass.pass.clone(),
+                    samples: pass.samples.len(),
+                    relative_offset: offset / SPEED_OF_LIGHT_M_S,
+                    relative_drift: drift.map(|drift| drift / SPEED_OF_LIGHT_M_S),
+                    post_fit_rms,
+                    jump: false,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        offsets.sort_by(|a, b| a.pass.cmp(&b.pass));
+
+        for i in 1..offsets.len() {
+            let (previous, current) = (&offsets[i - 1], &offsets[i]);
+
+            if previous.pass.station == current.pass.station
+                && (current.relative_offset - previous.relative_offset).abs() > self.jump_threshold
+            {
+                offsets[i].jump = true;
+            }
+        }
+
+        offsets
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::{Duration, GroundStation, RangeRateSample};
+    use crate::tests::toolkit::synthetic_t0;
+
+    #[test]
+    fn beacon_offset_estimation() {
+        let t0 = synthetic_t0();
+
+        let station = GroundStation::default()
+            .with_site_label("TLSB")
+            .with_unique_id(13);
+
+        let pass = |start: f64, bias: f64, drift: f64| {
+            let samples = (0..20)
+                .map(|i| {
+                    let t = i as f64 * 10.0;
+                    RangeRateSample {
+                        epoch: t0 + Duration::from_seconds(start + t),
+                        interval: Duration::from_seconds(10.0),
+                        observed: bias + drift * (t - 95.0) + 1.0E-3 * (-1.0_f64).powi(i),
+                        computed: 0.0,
+                        elevation_deg: 45.0,
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            PassResiduals {
+                pass: Pass {
+                    station: station.clone(),
+                    start: samples[0].epoch,
+                    end: samples[19].epoch,
+                },
+                samples,
+            }
+        };
+
+        let residuals = RangeRateResiduals {
+            passes: vec![
+                pass(6000.0, 0.03, 0.0),
+                pass(0.0, 0.03, 1.0E-4),
+                pass(12000.0, 0.3, 0.0),
+            ],
+        };
+
+        let offsets = BeaconOffsetEstimator::default().estimate(&residuals);
+        assert_eq!(offsets.len(), 3);
+
+        assert!((offsets[0].relative_offset - 1.0E-10).abs() < 1.0E-12);
+        assert!(offsets[0].relative_drift.is_none());
+        assert!(offsets[0].post_fit_rms > 1.0E-3);
+        assert!(!offsets[0].jump);
+        assert!(!offsets[1].jump);
+        assert!(offsets[2].jump);
+
+        assert!((offsets[0].s1_offset_hz() - 1.0E-10 * 2036.25E6).abs() < 1.0E-3);
+
+        let offsets = BeaconOffsetEstimator::default()
+            .with_drift(true)
+            .estimate(&residuals);
+
+        let drift = offsets[0].relative_drift.unwrap();
+        assert!((drift - 1.0E-4 / SPEED_OF_LIGHT_M_S).abs() < 1.0E-14);
+        assert!((offsets[0].post_fit_rms - 1.0E-3).abs() < 1.0E-5);
+
+        let offsets = BeaconOffsetEstimator::default()
+            .with_min_samples(30)
+            .estimate(&residuals);
+
+        assert!(offsets.is_empty());
+    }
+}
@@ -0,0 +1,341 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use crate::{
+    ccsds::{Tdm, TdmMetadata, TdmObservation},
+    prelude::{Epoch, FormattingError, TimeScale},
+};
+
+/// Supported TDM version
+const TDM_VERSION: &str = "2.0";
+
+/// Returns the TDM designation of this [TimeScale]
+fn fmt_time_system(time_scale: TimeScale) -> String {
+    match time_scale {
+        TimeScale::GPST => "GPS".to_string(),
+        time_scale => time_scale.to_string(),
+    }
+}
+
+/// Formats this [Epoch] as TDM epoch (YYYY-MM-DDThh:mm:ss.ssssss), in given [TimeScale]
+fn fmt_epoch(epoch: Epoch, time_scale: TimeScale) -> String {
+    let (year, month, day, hours, mins, secs, nanos) = epoch.to_gregorian(time_scale);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:09.6}",
+        year,
+        month,
+        day,
+        hours,
+        mins,
+        secs as f64 + nanos as f64 * 1.0E-9
+    )
+}
+
+/// Escapes XML special characters
+fn xml_escape(content: &str) -> String {
+    content
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+impl TdmMetadata {
+    /// Returns the (keyword, value) pairs of this [TdmMetadata], in order of appearance
+    fn keywords(&self) -> Vec<(String, String)> {
+        let mut keywords = vec![("TIME_SYSTEM".to_string(), fmt_time_system(self.time_system))];
+
+        for (i, participant) in self.participants.iter().enumerate() {
+            keywords.push((format!("PARTICIPANT_{}", i + 1), participant.clone()));
+        }
+
+        keywords.push(("MODE".to_string(), "SEQUENTIAL".to_string()));
+
+        keywords.push((
+            "PATH".to_string(),
+            self.path
+                .iter()
+                .map(|n| n.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+        ));
+
+        if let Some(band) = &self.transmit_band {
+            keywords.push(("TRANSMIT_BAND".to_string(), band.clone()));
+        }
+
+        if let Some(band) = &self.receive_band {
+            keywords.push(("RECEIVE_BAND".to_string(), band.clone()));
+        }
+
+        if let Some(interval) = self.integration_interval {
+            keywords.push((
+                "INTEGRATION_INTERVAL".to_string(),
+                interval.to_seconds().to_string(),
+            ));
+
+            keywords.push((
+                "INTEGRATION_REF".to_string(),
+                self.integration_ref.to_string(),
+            ));
+        }
+
+        keywords
+    }
+}
+
+impl TdmObservation {
+    /// Formats this [TdmObservation] as KVN data line
+    fn fmt_kvn(&self, time_scale: TimeScale) -> String {
+        format!(
+            "{} = {} {}",
+            self.data_type,
+            fmt_epoch(self.epoch, time_scale),
+            self.value
+        )
+    }
+}
+
+impl Tdm {
+    /// Formats this [Tdm] in Keyword Value Notation (KVN), into [Write]able interface.
+    pub fn format_kvn<W: Write>(&self, w: &mut BufWriter<W>) -> Result<(), FormattingError> {
+        writeln!(w, "CCSDS_TDM_VERS = {}", TDM_VERSION)?;
+
+        for comment in self.comments.iter() {
+            writeln!(w, "COMMENT {}", comment)?;
+        }
+
+        writeln!(
+            w,
+            "CREATION_DATE = {}",
+            fmt_epoch(self.creation_date, TimeScale::UTC)
+        )?;
+
+        writeln!(w, "ORIGINATOR = {}", self.originator)?;
+
+        for segment in self.segments.iter() {
+            let time_scale = segment.metadata.time_system;
+
+            writeln!(w)?;
+            writeln!(w, "META_START")?;
+
+            for comment in segment.metadata.comments.iter() {
+                writeln!(w, "COMMENT {}", comment)?;
+            }
+
+            for (keyword, value) in segment.metadata.keywords() {
+                writeln!(w, "{} = {}", keyword, value)?;
+            }
+
+            writeln!(w, "META_STOP")?;
+            writeln!(w)?;
+            writeln!(w, "DATA_START")?;
+
+            for comment in segment.comments.iter() {
+                writeln!(w, "COMMENT {}", comment)?;
+            }
+
+            for observation in segment.observations.iter() {
+                writeln!(w, "{}", observation.fmt_kvn(time_scale))?;
+            }
+
+            writeln!(w, "DATA_STOP")?;
+        }
+
+        w.flush()?;
+        Ok(())
+    }
+
+    /// Formats this [Tdm] in XML, into [Write]able interface.
+    pub fn format_xml<W: Write>(&self, w: &mut BufWriter<W>) -> Result<(), FormattingError> {
+        writeln!(w, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(w, "<tdm id=\"CCSDS_TDM_VERS\" version=\"{}\">", TDM_VERSION)?;
+
+        writeln!(w, "  <header>")?;
+
+        for comment in self.comments.iter() {
+            writeln!(w, "    <COMMENT>{}</COMMENT>", xml_escape(comment))?;
+        }
+
+        writeln!(
+            w,
+            "    <CREATION_DATE>{}</CREATION_DATE>",
+            fmt_epoch(self.creation_date, TimeScale::UTC)
+        )?;
+
+        writeln!(
+            w,
+            "    <ORIGINATOR>{}</ORIGINATOR>",
+            xml_escape(&self.originator)
+        )?;
+
+        writeln!(w, "  </header>")?;
+        writeln!(w, "  <body>")?;
+
+        for segment in self.segments.iter() {
+            let time_scale = segment.metadata.time_system;
+
+            writeln!(w, "    <segment>")?;
+            writeln!(w, "      <metadata>")?;
+
+            for comment in segment.metadata.comments.iter() {
+                writeln!(w, "        <COMMENT>{}</COMMENT>", xml_escape(comment))?;
+            }
+
+            for (keyword, value) in segment.metadata.keywords() {
+                writeln!(
+                    w,
+                    "        <{}>{}</{}>",
+                    keyword,
+                    xml_escape(&value),
+                    keyword
+                )?;
+            }
+
+            writeln!(w, "      </metadata>")?;
+            writeln!(w, "      <data>")?;
+
+            for comment in segment.comments.iter() {
+                writeln!(w, "        <COMMENT>{}</COMMENT>", xml_escape(comment))?;
+            }
+
+            for observation in segment.observations.iter() {
+                writeln!(w, "        <observation>")?;
+
+                writeln!(
+                    w,
+                    "          <EPOCH>{}</EPOCH>",
+                    fmt_epoch(observation.epoch, time_scale)
+                )?;
+
+                writeln!(
+                    w,
+                    "          <{}>{}</{}>",
+                    observation.data_type, observation.value, observation.data_type
+                )?;
+
+                writeln!(w, "        </observation>")?;
+            }
+
+            writeln!(w, "      </data>")?;
+            writeln!(w, "    </segment>")?;
+        }
+
+        writeln!(w, "  </body>")?;
+        writeln!(w, "</tdm>")?;
+
+        w.flush()?;
+        Ok(())
+    }
+
+    /// Dumps this [Tdm] into local KVN file.
+    pub fn to_kvn_file<P: AsRef<Path>>(&self, path: P) -> Result<(), FormattingError> {
+        let fd = File::create(path)?;
+        let mut writer = BufWriter::new(fd);
+        self.format_kvn(&mut writer)
+    }
+
+    /// Dumps this [Tdm] into local XML file.
+    pub fn to_xml_file<P: AsRef<Path>>(&self, path: P) -> Result<(), FormattingError> {
+        let fd = File::create(path)?;
+        let mut writer = BufWriter::new(fd);
+        self.format_xml(&mut writer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ccsds::{Tdm, TdmDataType, TdmMetadata, TdmObservation, TdmSegment};
+    use crate::prelude::Duration;
+    use crate::tests::toolkit::synthetic_t0;
+
+    use std::io::BufWriter;
+
+    fn tdm() -> Tdm {
+        let t0 = synthetic_t0();
+
+        Tdm {
+            comments: vec!["DORIS measurements of CRYOSAT-2".to_string()],
+            creation_date: t0,
+            originator: "CNES".to_string(),
+            segments: vec![TdmSegment {
+                metadata: TdmMetadata {
+                    participants: vec!["TLSB".to_string(), "CRYOSAT-2".to_string()],
+                    transmit_band: Some("S".to_string()),
+                    receive_band: Some("S".to_string()),
+                    integration_interval: Some(Duration::from_seconds(10.0)),
+                    ..Default::default()
+                },
+                comments: Vec::new(),
+                observations: vec![
+                    TdmObservation {
+                        data_type: TdmDataType::TransmitFrequency(1),
+                        epoch: t0,
+                        value: 2036239440.81,
+                    },
+                    TdmObservation {
+                        data_type: TdmDataType::DopplerIntegrated,
+                        epoch: t0 + Duration::from_seconds(10.5),
+                        value: -1.25,
+                    },
+                ],
+            }],
+        }
+    }
+
+    #[test]
+    fn tdm_kvn_formatting() {
+        let mut writer = BufWriter::new(Vec::new());
+        tdm().format_kvn(&mut writer).unwrap();
+
+        let content = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        let lines = content.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines[0], "CCSDS_TDM_VERS = 2.0");
+        assert_eq!(lines[1], "COMMENT DORIS measurements of CRYOSAT-2");
+        assert_eq!(lines[2], "CREATION_DATE = 2018-06-12T23:59:23.000000");
+        assert_eq!(lines[3], "ORIGINATOR = CNES");
+        assert_eq!(lines[4], "");
+        assert_eq!(lines[5], "META_START");
+        assert_eq!(lines[6], "TIME_SYSTEM = TAI");
+        assert_eq!(lines[7], "PARTICIPANT_1 = TLSB");
+        assert_eq!(lines[8], "PARTICIPANT_2 = CRYOSAT-2");
+        assert_eq!(lines[9], "MODE = SEQUENTIAL");
+        assert_eq!(lines[10], "PATH = 1,2");
+        assert_eq!(lines[11], "TRANSMIT_BAND = S");
+        assert_eq!(lines[12], "RECEIVE_BAND = S");
+        assert_eq!(lines[13], "INTEGRATION_INTERVAL = 10");
+        assert_eq!(lines[14], "INTEGRATION_REF = END");
+        assert_eq!(lines[15], "META_STOP");
+        assert_eq!(lines[17], "DATA_START");
+        assert_eq!(
+            lines[18],
+            "TRANSMIT_FREQ_1 = 2018-06-13T00:00:00.000000 2036239440.81"
+        );
+        assert_eq!(
+            lines[19],
+            "DOPPLER_INTEGRATED = 2018-06-13T00:00:10.500000 -1.25"
+        );
+        assert_eq!(lines[20], "DATA_STOP");
+    }
+
+    #[test]
+    fn tdm_xml_formatting() {
+        let mut writer = BufWriter::new(Vec::new());
+        tdm().format_xml(&mut writer).unwrap();
+
+        let content = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+
+        assert!(content.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"));
+        assert!(content.contains("<tdm id=\"CCSDS_TDM_VERS\" version=\"2.0\">"));
+        assert!(content.contains("<ORIGINATOR>CNES</ORIGINATOR>"));
+        assert!(content.contains("<PARTICIPANT_2>CRYOSAT-2</PARTICIPANT_2>"));
+        assert!(content.contains("<EPOCH>2018-06-13T00:00:10.500000</EPOCH>"));
+        assert!(content.contains("<DOPPLER_INTEGRATED>-1.25</DOPPLER_INTEGRATED>"));
+        assert_eq!(content.matches("<observation>").count(), 2);
+        assert!(content.trim_end().ends_with("</tdm>"));
+    }
+}
@@ -0,0 +1,1039 @@
+//! CCSDS Tracking Data Message (TDM) exchanges
+mod formatting;
+mod parsing;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+
+use itertools::Itertools;
+
+use crate::{
+    constants::SPEED_OF_LIGHT_M_S,
+    prelude::{
+        Duration, Epoch, EpochFlag, Frequency, GroundStation, Header, Key, Observable, Observation,
+        PhaseFlag, Record, StationHandle, TimeScale, DORIS,
+    },
+};
+
+/// [TdmDataType] describes the physics of a [TdmObservation]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TdmDataType {
+    /// Received carrier power, in dBW
+    CarrierPower,
+
+    /// Instantaneous range rate, in km/s (positive when the range increases)
+    DopplerInstantaneous,
+
+    /// Range rate averaged over the integration interval, in km/s
+    /// (positive when the range increases)
+    DopplerIntegrated,
+
+    /// Atmospheric pressure, in hPa
+    Pressure,
+
+    /// Relative humidity, in %
+    RelativeHumidity,
+
+    /// Temperature, in Kelvin
+    Temperature,
+
+    /// Number of carrier phase cycles received at participant n
+    ReceivePhaseCount(u8),
+
+    /// Frequency transmitted by participant n, in Hertz
+    TransmitFrequency(u8),
+
+    /// Frequency received at participant n, in Hertz
+    ReceiveFrequency(u8),
+}
+
+impl std::fmt::Display for TdmDataType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::CarrierPower => write!(f, "CARRIER_POWER"),
+            Self::DopplerInstantaneous => write!(f, "DOPPLER_INSTANTANEOUS"),
+            Self::DopplerIntegrated => write!(f, "DOPPLER_INTEGRATED"),
+            Self::Pressure => write!(f, "PRESSURE"),
+            Self::RelativeHumidity => write!(f, "RHUMIDITY"),
+            Self::Temperature => write!(f, "TEMPERATURE"),
+            Self::ReceivePhaseCount(n) => write!(f, "RECEIVE_PHASE_CT_{}", n),
+            Self::TransmitFrequency(n) => write!(f, "TRANSMIT_FREQ_{}", n),
+            Self::ReceiveFrequency(n) => write!(f, "RECEIVE_FREQ_{}", n),
+        }
+    }
+}
+
+/// [TdmIntegrationRef] describes which part of the integration interval
+/// the epoch of the integrated measurements refers to
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TdmIntegrationRef {
+    /// Epoch is the start of the integration interval
+    Start,
+
+    /// Epoch is the middle of the integration interval
+    Middle,
+
+    /// Epoch is the end of the integration interval
+    #[default]
+    End,
+}
+
+impl std::fmt::Display for TdmIntegrationRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Start => write!(f, "START"),
+            Self::Middle => write!(f, "MIDDLE"),
+            Self::End => write!(f, "END"),
+        }
+    }
+}
+
+impl TdmIntegrationRef {
+    /// Returns the end of the integration interval of [Duration],
+    /// for this referenced [Epoch]
+    pub(crate) fn interval_end(&self, epoch: Epoch, interval: Duration) -> Epoch {
+        match self {
+            Self::Start => epoch + interval,
+            Self::Middle => epoch + interval / 2,
+            Self::End => epoch,
+        }
+    }
+}
+
+/// [TdmObservation] is one tracking data record
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TdmObservation {
+    /// [TdmDataType]
+    pub data_type: TdmDataType,
+
+    /// Sampling [Epoch]
+    pub epoch: Epoch,
+
+    /// Value, in the [TdmDataType] units
+    pub value: f64,
+}
+
+/// [TdmMetadata] describes the tracking link of a [TdmSegment]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TdmMetadata {
+    /// Metadata comments
+    pub comments: Vec<String>,
+
+    /// [TimeScale] of all epochs
+    pub time_system: TimeScale,
+
+    /// Participants to the tracking session, participant n is `participants[n - 1]`
+    pub participants: Vec<String>,
+
+    /// Signal path, as participant numbers (for example, 1,2 for a one-way uplink)
+    pub path: Vec<u8>,
+
+    /// Frequency band transmitted by the first participant of the path
+    pub transmit_band: Option<String>,
+
+    /// Frequency band received by the last participant of the path
+    pub receive_band: Option<String>,
+
+    /// Integration interval of the integrated measurements
+    pub integration_interval: Option<Duration>,
+
+    /// [TdmIntegrationRef] of the integrated measurements epochs
+    pub integration_ref: TdmIntegrationRef,
+}
+
+impl Default for TdmMetadata {
+    fn default() -> Self {
+        Self {
+            comments: Default::default(),
+            time_system: TimeScale::TAI,
+            participants: Default::default(),
+            path: vec![1, 2],
+            transmit_band: None,
+            receive_band: None,
+            integration_interval: None,
+            integration_ref: TdmIntegrationRef::End,
+        }
+    }
+}
+
+/// [TdmSegment] gathers the [TdmObservation]s of one tracking link
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TdmSegment {
+    /// [TdmMetadata]
+    pub metadata: TdmMetadata,
+
+    /// Data comments
+    pub comments: Vec<String>,
+
+    /// [TdmObservation]s, in chronological order
+    pub observations: Vec<TdmObservation>,
+}
+
+/// CCSDS Tracking Data Message (CCSDS 503.0-B-2), which may be
+/// formatted in KVN or XML. The DORIS measurements may be converted to [Tdm]
+/// with [DORIS::to_tdm], and KVN messages converted to [DORIS] with [DORIS::from_tdm].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Tdm {
+    /// Header comments
+    pub comments: Vec<String>,
+
+    /// Message creation [Epoch]
+    pub creation_date: Epoch,
+
+    /// Message originator
+    pub originator: String,
+
+    /// [TdmSegment]s
+    pub segments: Vec<TdmSegment>,
+}
+
+impl Default for Tdm {
+    fn default() -> Self {
+        Self {
+            comments: Default::default(),
+            creation_date: Epoch::from_gregorian_at_midnight(2000, 1, 1, TimeScale::TAI),
+            originator: Default::default(),
+            segments: Default::default(),
+        }
+    }
+}
+
+/// Returns the CCSDS frequency band of this [Frequency]
+fn band(frequency: Frequency) -> &'static str {
+    match frequency {
+        Frequency::DORIS1 => "S",
+        Frequency::DORIS2 => "UHF",
+    }
+}
+
+/// Returns the actual frequency of this beacon, in Hertz
+fn beacon_frequency_hz(station: &GroundStation, frequency: Frequency) -> f64 {
+    match frequency {
+        Frequency::DORIS1 => station.s1_frequency_shift(),
+        Frequency::DORIS2 => station.u2_frequency_shift(),
+    }
+}
+
+/// Returns the DORIS [Frequency] of this CCSDS band
+fn band_frequency(band: &str) -> Option<Frequency> {
+    match band.trim().to_uppercase().as_str() {
+        "S" => Some(Frequency::DORIS1),
+        "UHF" => Some(Frequency::DORIS2),
+        _ => None,
+    }
+}
+
+/// Integrates these range rate samples (in m/s) into phase range (in meters),
+/// as (epoch, phase, new arc) triplets. When the integration interval is known,
+/// each sample is the average range rate over the interval that ends at this epoch,
+/// otherwise samples are considered instantaneous.
+pub(crate) fn integrate_range_rate(
+    samples: &[(Epoch, f64)],
+    interval: Option<Duration>,
+) -> Vec<(Epoch, f64, bool)> {
+    let mut phases = Vec::<(Epoch, f64, bool)>::new();
+
+    match interval {
+        Some(interval) => {
+            let mut phase = 0.0;
+
+            for (epoch, range_rate) in samples.iter() {
+                let start = *epoch - interval;
+
+                if phases.last().map(|(t, _, _)| *t) != Some(start) {
+                    phase = 0.0;
+                    phases.push((start, phase, true));
+                }
+
+                phase += range_rate * interval.to_seconds();
+                phases.push((*epoch, phase, false));
+            }
+        },
+        None => {
+            if let Some((epoch, _)) = samples.first() {
+                phases.push((*epoch, 0.0, true));
+            }
+
+            for window in samples.windows(2) {
+                let ((t_1, range_rate_1), (t_2, range_rate_2)) = (window[0], window[1]);
+
+                let phase = phases
+                    .last()
+                    .map(|(_, phase, _)| *phase)
+                    .unwrap_or_default()
+                    + (range_rate_1 + range_rate_2) / 2.0 * (t_2 - t_1).to_seconds();
+
+                phases.push((t_2, phase, false));
+            }
+        },
+    }
+
+    phases
+}
+
+impl TdmSegment {
+    /// Returns the (transmitter, receiver) participants of this link
+    fn link(&self) -> Option<(&str, &str)> {
+        let transmitter = *self.metadata.path.first()? as usize;
+        let receiver = *self.metadata.path.last()? as usize;
+
+        if transmitter == 0 || receiver == 0 || transmitter == receiver {
+            return None;
+        }
+
+        let transmitter = self.metadata.participants.get(transmitter - 1)?;
+        let receiver = self.metadata.participants.get(receiver - 1)?;
+
+        Some((transmitter, receiver))
+    }
+
+    /// Returns the frequency transmitted on this link, in Hertz, when described
+    fn transmit_frequency_hz(&self) -> Option<f64> {
+        let transmitter = *self.metadata.path.first()?;
+
+        self.observations
+            .iter()
+            .find(|obs| obs.data_type == TdmDataType::TransmitFrequency(transmitter))
+            .map(|obs| obs.value)
+    }
+
+    /// Returns the DORIS [Frequency] of this link, from its frequency bands,
+    /// or its transmitted frequency. Defaults to [Frequency::DORIS1].
+    fn frequency(&self) -> Frequency {
+        let band = self
+            .metadata
+            .transmit_band
+            .as_deref()
+            .or(self.metadata.receive_band.as_deref())
+            .and_then(band_frequency);
+
+        if let Some(frequency) = band {
+            return frequency;
+        }
+
+        match self.transmit_frequency_hz() {
+            Some(frequency_hz) => [Frequency::DORIS1, Frequency::DORIS2]
+                .into_iter()
+                .min_by(|a, b| {
+                    let a = (a.frequency_hz() - frequency_hz).abs();
+                    let b = (b.frequency_hz() - frequency_hz).abs();
+                    a.total_cmp(&b)
+                })
+                .unwrap_or_default(),
+            None => Frequency::DORIS1,
+        }
+    }
+}
+
+impl DORIS {
+    /// Returns the [TdmSegment] describing this beacon [Frequency] link.
+    /// The S1 link also carries the ionosphere free integrated Doppler and the meteo
+    /// observations.
+    fn tdm_segment(
+        &self,
+        station: &GroundStation,
+        frequency: Frequency,
+        interval: Option<Duration>,
+    ) -> TdmSegment {
+        let primary = frequency == Frequency::DORIS1;
+        let frequency_hz = beacon_frequency_hz(station, frequency);
+
+        let alpha = (Frequency::DORIS1.frequency_hz() / Frequency::DORIS2.frequency_hz()).powi(2);
+
+        let mut observations = Vec::new();
+
+        // (L1, L2, discontinuity), per epoch
+        let mut phases = BTreeMap::<Epoch, (Option<f64>, Option<f64>, bool)>::new();
+
+        for (key, measurements) in self.record.measurements.iter() {
+            for (obs_key, observation) in measurements.observations.iter() {
+                if *obs_key.station != *station {
+                    continue;
+                }
+
+                let (data_type, value) = match obs_key.observable {
+                    Observable::UnambiguousPhaseRange(freq) => {
+                        if primary {
+                            let phase = phases.entry(key.epoch).or_default();
+
+                            if freq == Frequency::DORIS1 {
+                                phase.0 = Some(observation.value);
+                            } else {
+                                phase.1 = Some(observation.value);
+                            }
+
+                            if observation.phase_flag == Some(PhaseFlag::Discontinuity) {
+                                phase.2 = true;
+                            }
+                        }
+
+                        if freq != frequency {
+                            continue;
+                        }
+
+                        (
+                            TdmDataType::ReceivePhaseCount(2),
+                            observation.value * frequency_hz / SPEED_OF_LIGHT_M_S,
+                        )
+                    },
+                    Observable::Power(freq) if freq == frequency => {
+                        (TdmDataType::CarrierPower, observation.value - 30.0)
+                    },
+                    Observable::Pressure if primary => (TdmDataType::Pressure, observation.value),
+                    Observable::Temperature if primary => {
+                        (TdmDataType::Temperature, observation.value + 273.15)
+                    },
+                    Observable::HumidityRate if primary => {
+                        (TdmDataType::RelativeHumidity, observation.value)
+                    },
+                    _ => continue,
+                };
+
+                observations.push(TdmObservation {
+                    data_type,
+                    epoch: key.epoch,
+                    value,
+                });
+            }
+        }
+
+        if let Some(interval) = interval {
+            let iono_free = phases
+                .iter()
+                .filter_map(|(epoch, (l1, l2, discontinuity))| {
+                    let (l1, l2) = ((*l1)?, (*l2)?);
+                    Some((*epoch, (alpha * l1 - l2) / (alpha - 1.0), *discontinuity))
+                })
+                .collect::<Vec<_>>();
+
+            for window in iono_free.windows(2) {
+                let ((t_1, phase_1, _), (t_2, phase_2, discontinuity)) = (window[0], window[1]);
+
+                if discontinuity || t_2 - t_1 != interval {
+                    continue;
+                }
+
+                observations.push(TdmObservation {
+                    data_type: TdmDataType::DopplerIntegrated,
+                    epoch: t_2,
+                    value: (phase_2 - phase_1) / interval.to_seconds() / 1.0E3,
+                });
+            }
+        }
+
+        observations.sort_by_key(|obs| obs.epoch);
+
+        if let Some(first) = observations.first() {
+            observations.insert(
+                0,
+                TdmObservation {
+                    data_type: TdmDataType::TransmitFrequency(1),
+                    epoch: first.epoch,
+                    value: frequency_hz,
+                },
+            );
+        }
+
+        let mut comments = vec![format!(
+            "DORIS{} link, beacon {} ({})",
+            frequency, station.label, station.site
+        )];
+
+        if primary {
+            comments.push("DOPPLER_INTEGRATED is the ionosphere free phase rate".to_string());
+        }
+
+        TdmSegment {
+            metadata: TdmMetadata {
+                comments,
+                time_system: TimeScale::TAI,
+                participants: vec![station.label.clone(), self.header.satellite.clone()],
+                path: vec![1, 2],
+                transmit_band: Some(band(frequency).to_string()),
+                receive_band: Some(band(frequency).to_string()),
+                integration_interval: if primary { interval } else { None },
+                integration_ref: TdmIntegrationRef::End,
+            },
+            comments: Vec::new(),
+            observations,
+        }
+    }
+
+    /// Converts this [DORIS] file to a CCSDS [Tdm], with two segments per ground station
+    /// (one per beacon frequency). The beacon (participant 1) transmits and the satellite
+    /// (participant 2) receives, as one-way uplink. Each segment contains:
+    /// - the beacon frequency (TRANSMIT_FREQ_1), shifted as per [GroundStation::s1_frequency_shift]
+    /// - the phase observations, as received phase cycles (RECEIVE_PHASE_CT_2)
+    /// - the received power (CARRIER_POWER), in dBW
+    ///
+    /// The S band segment also contains the meteo observations and the ionosphere free
+    /// range rate (DOPPLER_INTEGRATED), over the dominant sampling period.
+    /// Epochs are expressed in [TimeScale::TAI]. The message `creation_date`
+    /// is provided by the caller, so the conversion is deterministic.
+    ///
+    /// ```
+    /// use doris_rs::prelude::*;
+    ///
+    /// let doris = DORIS::from_gzip_file("data/DOR/V3/cs2rx18164.gz")
+    ///     .unwrap();
+    ///
+    /// let creation_date = Epoch::now()
+    ///     .unwrap();
+    ///
+    /// let tdm = doris.to_tdm(creation_date);
+    ///
+    /// let directory = std::env::temp_dir();
+    ///
+    /// tdm.to_kvn_file(directory.join("cs2rx18164.kvn"))
+    ///     .unwrap();
+    ///
+    /// tdm.to_xml_file(directory.join("cs2rx18164.xml"))
+    ///     .unwrap();
+    /// ```
+    pub fn to_tdm(&self, creation_date: Epoch) -> Tdm {
+        let interval = self.dominant_sampling_period();
+
+        let mut comments = vec![format!("DORIS measurements of {}", self.header.satellite)];
+
+        if let Some(cospar) = &self.header.cospar {
+            comments.push(format!("COSPAR ID {}", cospar));
+        }
+
+        let originator = match &self.header.agency {
+            Some(agency) => agency.clone(),
+            None => format!(
+                "doris-rs v{}",
+                Header::format_pkg_version(env!("CARGO_PKG_VERSION"))
+            ),
+        };
+
+        let segments = self
+            .header
+            .ground_stations
+            .iter()
+            .flat_map(|station| {
+                [Frequency::DORIS1, Frequency::DORIS2]
+                    .into_iter()
+                    .map(move |frequency| (station, frequency))
+            })
+            .map(|(station, frequency)| self.tdm_segment(station, frequency, interval))
+            .filter(|segment| !segment.observations.is_empty())
+            .collect();
+
+        Tdm {
+            comments,
+            creation_date,
+            originator,
+            segments,
+        }
+    }
+
+    /// Returns the [GroundStation] described by this transmitting participant.
+    /// The beacon frequency shift is retrieved from the transmitted frequency.
+    fn tdm_ground_station(participant: &str, segment: &TdmSegment, code: u16) -> GroundStation {
+        let frequency = segment.frequency();
+
+        let shift = segment
+            .transmit_frequency_hz()
+            .map(|frequency_hz| {
+                let relative = frequency_hz / frequency.frequency_hz() - 1.0;
+                let k = relative * 3.0 / 4.0 * 5.0 * 2.0_f64.powi(26) / 87.0;
+                k.round().clamp(i8::MIN as f64, i8::MAX as f64) as i8
+            })
+            .unwrap_or_default();
+
+        let label = participant
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .take(4)
+            .collect::<String>()
+            .to_uppercase();
+
+        GroundStation::default()
+            .with_site_label(&label)
+            .with_site_name(participant)
+            .with_frequency_shift(shift)
+            .with_unique_id(code)
+    }
+
+    /// Converts a CCSDS [Tdm] to [DORIS]. The transmitting participant of each link
+    /// is described as a [GroundStation] and the receiving participant as the satellite.
+    /// The link [Frequency] is determined from the frequency bands (S or UHF),
+    /// or the transmitted frequency. Data types are converted as follows:
+    /// - RECEIVE_PHASE_CT_n (at the receiver) to [Observable::UnambiguousPhaseRange]
+    /// - CARRIER_POWER to [Observable::Power]
+    /// - PRESSURE, TEMPERATURE and RHUMIDITY to the meteo [Observable]s
+    /// - on links without phase counts, one-way Doppler (DOPPLER_INTEGRATED,
+    ///   DOPPLER_INSTANTANEOUS, RECEIVE_FREQ_n at the receiver) is integrated into
+    ///   [Observable::UnambiguousPhaseRange]. Each integration arc starts at zero
+    ///   and is marked by a [PhaseFlag::Discontinuity]. DOPPLER_INTEGRATED epochs are
+    ///   first referred to the end of their interval, following [TdmIntegrationRef].
+    ///
+    /// Epochs are converted to [TimeScale::TAI].
+    ///
+    /// ```
+    /// use doris_rs::prelude::*;
+    /// use std::io::BufReader;
+    ///
+    /// let content = "CCSDS_TDM_VERS = 2.0
+    /// CREATION_DATE = 2018-06-14T12:00:00
+    /// ORIGINATOR = PARTNER
+    /// META_START
+    /// TIME_SYSTEM = UTC
+    /// PARTICIPANT_1 = GODDARD
+    /// PARTICIPANT_2 = JASON-3
+    /// PATH = 1,2
+    /// TRANSMIT_BAND = S
+    /// INTEGRATION_INTERVAL = 10.0
+    /// META_STOP
+    /// DATA_START
+    /// DOPPLER_INTEGRATED = 2018-06-13T00:00:10 -1.25
+    /// DOPPLER_INTEGRATED = 2018-06-13T00:00:20 -1.5
+    /// DATA_STOP";
+    ///
+    /// let tdm = Tdm::parse_kvn(&mut BufReader::new(content.as_bytes()))
+    ///     .unwrap();
+    ///
+    /// let doris = DORIS::from_tdm(&tdm);
+    ///
+    /// assert_eq!(doris.header.satellite, "JASON-3");
+    /// assert_eq!(doris.header.ground_stations[0].label, "GODD");
+    ///
+    /// doris.to_file(std::env::temp_dir().join("partner.rnx"))
+    ///     .unwrap();
+    /// ```
+    pub fn from_tdm(tdm: &Tdm) -> Self {
+        let mut header = Header {
+            comments: tdm.comments.clone(),
+            program: Some(format!(
+                "doris-rs v{}",
+                Header::format_pkg_version(env!("CARGO_PKG_VERSION"))
+            )),
+            agency: if tdm.originator.is_empty() {
+                None
+            } else {
+                Some(tdm.originator.clone())
+            },
+            ..Default::default()
+        };
+
+        // transmitting participants
+        let mut stations = Vec::<(String, StationHandle)>::new();
+
+        for segment in tdm.segments.iter() {
+            let Some((transmitter, receiver)) = segment.link() else {
+                continue;
+            };
+
+            if header.satellite.is_empty() {
+                header.satellite = receiver.to_string();
+            }
+
+            if !stations.iter().any(|(name, _)| name == transmitter) {
+                let code = stations.len() as u16 + 1;
+                let station = Self::tdm_ground_station(transmitter, segment, code);
+                stations.push((transmitter.to_string(), StationHandle::from(station)));
+            }
+        }
+
+        let mut record = Record::default();
+
+        let mut insert = |epoch: Epoch, station: &StationHandle, observable, observation| {
+            record
+                .measurements
+                .entry(Key {
+                    epoch,
+                    flag: EpochFlag::OK,
+                })
+                .or_default()
+                .add_observation(station.clone(), observable, observation);
+        };
+
+        for segment in tdm.segments.iter() {
+            let Some((transmitter, _)) = segment.link() else {
+                continue;
+            };
+
+            let Some((_, station)) = stations.iter().find(|(name, _)| name == transmitter) else {
+                continue;
+            };
+
+            let receiver = *segment.metadata.path.last().unwrap_or(&2);
+
+            let frequency = segment.frequency();
+
+            let transmit_hz = segment
+                .transmit_frequency_hz()
+                .unwrap_or(beacon_frequency_hz(station, frequency));
+
+            let phase_count = TdmDataType::ReceivePhaseCount(receiver);
+
+            let has_phase = segment
+                .observations
+                .iter()
+                .any(|obs| obs.data_type == phase_count);
+
+            let mut integrated = Vec::new();
+            let mut instantaneous = Vec::new();
+
+            for obs in segment.observations.iter() {
+                let epoch = obs.epoch.to_time_scale(TimeScale::TAI);
+
+                let (observable, value) = match obs.data_type {
+                    data_type if data_type == phase_count => (
+                        Observable::UnambiguousPhaseRange(frequency),
+                        obs.value * SPEED_OF_LIGHT_M_S / transmit_hz,
+                    ),
+                    TdmDataType::CarrierPower => (Observable::Power(frequency), obs.value + 30.0),
+                    TdmDataType::Pressure => (Observable::Pressure, obs.value),
+                    TdmDataType::Temperature => (Observable::Temperature, obs.value - 273.15),
+                    TdmDataType::RelativeHumidity => (Observable::HumidityRate, obs.value),
+                    TdmDataType::DopplerIntegrated if !has_phase => {
+                        let epoch = match segment.metadata.integration_interval {
+                            Some(interval) => segment
+                                .metadata
+                                .integration_ref
+                                .interval_end(epoch, interval),
+                            None => epoch,
+                        };
+
+                        integrated.push((epoch, obs.value * 1.0E3));
+                        continue;
+                    },
+                    TdmDataType::DopplerInstantaneous if !has_phase => {
+                        instantaneous.push((epoch, obs.value * 1.0E3));
+                        continue;
+                    },
+                    TdmDataType::ReceiveFrequency(n) if n == receiver && !has_phase => {
+                        let range_rate = SPEED_OF_LIGHT_M_S * (1.0 - obs.value / transmit_hz);
+                        instantaneous.push((epoch, range_rate));
+                        continue;
+                    },
+                    _ => continue,
+                };
+
+                insert(
+                    epoch,
+                    station,
+                    observable,
+                    Observation::default().with_value(value),
+                );
+            }
+
+            let integrated_interval = segment.metadata.integration_interval;
+
+            for (samples, interval) in [(integrated, integrated_interval), (instantaneous, None)] {
+                for (epoch, phase, new_arc) in integrate_range_rate(&samples, interval) {
+                    let mut observation = Observation::default().with_value(phase);
+
+                    if new_arc {
+                        observation = observation.with_phase_flag(PhaseFlag::Discontinuity);
+                    }
+
+                    insert(
+                        epoch,
+                        station,
+                        Observable::UnambiguousPhaseRange(frequency),
+                        observation,
+                    );
+                }
+            }
+        }
+
+        header.ground_stations = stations
+            .into_iter()
+            .map(|(_, station)| station.station().clone())
+            .collect();
+
+        header.observables = record
+            .measurements
+            .values()
+            .flat_map(|measurements| measurements.observables())
+            .unique()
+            .sorted()
+            .collect();
+
+        header.time_of_first_observation = record.measurements.keys().next().map(|k| k.epoch);
+        header.time_of_last_observation = record.measurements.keys().last().map(|k| k.epoch);
+
+        DORIS::new(header, record)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tests::toolkit::{synthetic_record, synthetic_t0};
+
+    #[test]
+    fn doris_to_tdm() {
+        let station = GroundStation::default()
+            .with_site_label("TLSB")
+            .with_site_name("TOULOUSE")
+            .with_frequency_shift(-15)
+            .with_unique_id(13);
+
+        let l1 = Observable::UnambiguousPhaseRange(Frequency::DORIS1);
+        let l2 = Observable::UnambiguousPhaseRange(Frequency::DORIS2);
+        let w1 = Observable::Power(Frequency::DORIS1);
+
+        let samples = (0..4)
+            .map(|i| {
+                let range = 1.0E6 + 100.0 * i as f64;
+                (
+                    10.0 * i as f64,
+                    vec![
+                        (l1, range),
+                        (l2, range),
+                        (w1, -120.0),
+                        (Observable::Temperature, 20.0),
+                    ],
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mut doris = DORIS {
+            record: synthetic_record(&station, &samples),
+            ..Default::default()
+        };
+
+        doris.header.satellite = "CRYOSAT-2".to_string();
+        doris.header.ground_stations.push(station.clone());
+
+        let creation_date = synthetic_t0() + Duration::from_days(1.0);
+
+        let tdm = doris.to_tdm(creation_date);
+        assert_eq!(tdm.creation_date, creation_date);
+        assert_eq!(tdm, doris.to_tdm(creation_date));
+        assert_eq!(tdm.segments.len(), 2);
+
+        let (s1, u2) = (&tdm.segments[0], &tdm.segments[1]);
+
+        assert_eq!(s1.metadata.participants, vec!["TLSB", "CRYOSAT-2"]);
+        assert_eq!(s1.metadata.transmit_band.as_deref(), Some("S"));
+        assert_eq!(u2.metadata.transmit_band.as_deref(), Some("UHF"));
+        assert_eq!(
+            s1.metadata.integration_interval,
+            Some(Duration::from_seconds(10.0))
+        );
+        assert!(u2.metadata.integration_interval.is_none());
+
+        let f1 = station.s1_frequency_shift();
+        assert!((f1 - 2036.25E6 + 10559.19).abs() < 1.0E-2);
+
+        assert_eq!(
+            s1.observations[0],
+            TdmObservation {
+                data_type: TdmDataType::TransmitFrequency(1),
+                epoch: synthetic_t0(),
+                value: f1,
+            }
+        );
+
+        let count = |segment: &TdmSegment, data_type: TdmDataType| {
+            segment
+                .observations
+                .iter()
+                .filter(|obs| obs.data_type == data_type)
+                .count()
+        };
+
+        assert_eq!(count(s1, TdmDataType::ReceivePhaseCount(2)), 4);
+        assert_eq!(count(s1, TdmDataType::CarrierPower), 4);
+        assert_eq!(count(s1, TdmDataType::Temperature), 4);
+        assert_eq!(count(s1, TdmDataType::DopplerIntegrated), 3);
+
+        assert_eq!(count(u2, TdmDataType::ReceivePhaseCount(2)), 4);
+        assert_eq!(count(u2, TdmDataType::CarrierPower), 0);
+        assert_eq!(count(u2, TdmDataType::Temperature), 0);
+
+        for obs in s1.observations.iter() {
+            match obs.data_type {
+                TdmDataType::DopplerIntegrated => assert!((obs.value - 0.01).abs() < 1.0E-9),
+                TdmDataType::CarrierPower => assert_eq!(obs.value, -150.0),
+                TdmDataType::Temperature => assert_eq!(obs.value, 293.15),
+                TdmDataType::ReceivePhaseCount(_) => {
+                    let range = obs.value * SPEED_OF_LIGHT_M_S / f1;
+                    assert!((range - 1.0E6).abs() < 1.0E3);
+                },
+                _ => {},
+            }
+        }
+    }
+
+    #[test]
+    fn tdm_to_doris() {
+        let station = GroundStation::default()
+            .with_site_label("TLSB")
+            .with_site_name("TOULOUSE")
+            .with_frequency_shift(-15)
+            .with_unique_id(13);
+
+        let l1 = Observable::UnambiguousPhaseRange(Frequency::DORIS1);
+        let l2 = Observable::UnambiguousPhaseRange(Frequency::DORIS2);
+        let w2 = Observable::Power(Frequency::DORIS2);
+
+        let samples = (0..4)
+            .map(|i| {
+                let range = 1.0E6 + 100.0 * i as f64;
+                (
+                    10.0 * i as f64,
+                    vec![
+                        (l1, range),
+                        (l2, range + 1.0),
+                        (w2, -125.0),
+                        (Observable::Pressure, 1013.0),
+                    ],
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mut doris = DORIS {
+            record: synthetic_record(&station, &samples),
+            ..Default::default()
+        };
+
+        doris.header.satellite = "CRYOSAT-2".to_string();
+        doris.header.ground_stations.push(station.clone());
+
+        let converted = DORIS::from_tdm(&doris.to_tdm(synthetic_t0()));
+
+        assert_eq!(converted.header.satellite, "CRYOSAT-2");
+        assert_eq!(converted.header.ground_stations.len(), 1);
+
+        let converted_station = &converted.header.ground_stations[0];
+        assert_eq!(converted_station.label, "TLSB");
+        assert_eq!(converted_station.k_frequency_shift, -15);
+
+        assert_eq!(
+            converted.header.observables,
+            vec![l1, l2, w2, Observable::Pressure]
+        );
+
+        assert_eq!(converted.record.measurements.len(), 4);
+
+        for (i, (key, measurements)) in converted.record.measurements.iter().enumerate() {
+            assert_eq!(
+                key.epoch,
+                synthetic_t0() + Duration::from_seconds(10.0 * i as f64)
+            );
+
+            let range = 1.0E6 + 100.0 * i as f64;
+
+            for (obs_key, observation) in measurements.observations.iter() {
+                let expected = match obs_key.observable {
+                    Observable::UnambiguousPhaseRange(Frequency::DORIS1) => range,
+                    Observable::UnambiguousPhaseRange(Frequency::DORIS2) => range + 1.0,
+                    Observable::Power(_) => -125.0,
+                    _ => 1013.0,
+                };
+
+                assert!((observation.value - expected).abs() < 1.0E-6);
+                assert!(observation.phase_flag.is_none());
+            }
+        }
+    }
+
+    #[test]
+    fn tdm_doppler_integration() {
+        let t0 = synthetic_t0();
+
+        let doppler = |data_type: TdmDataType, t: f64, value: f64| TdmObservation {
+            data_type,
+            epoch: t0 + Duration::from_seconds(t),
+            value,
+        };
+
+        let tdm = Tdm {
+            segments: vec![TdmSegment {
+                metadata: TdmMetadata {
+                    participants: vec!["GODDARD".to_string(), "JASON-3".to_string()],
+                    receive_band: Some("UHF".to_string()),
+                    integration_interval: Some(Duration::from_seconds(10.0)),
+                    ..Default::default()
+                },
+                comments: Vec::new(),
+                observations: vec![
+                    doppler(TdmDataType::DopplerIntegrated, 10.0, -1.0),
+                    doppler(TdmDataType::DopplerIntegrated, 20.0, -2.0),
+                    // data gap: new arc
+                    doppler(TdmDataType::DopplerIntegrated, 60.0, 1.0),
+                ],
+            }],
+            ..Default::default()
+        };
+
+        let doris = DORIS::from_tdm(&tdm);
+
+        // same samples, referenced to the start of the integration interval
+        let mut start_ref = tdm.clone();
+        start_ref.segments[0].metadata.integration_ref = TdmIntegrationRef::Start;
+
+        let start_ref = DORIS::from_tdm(&start_ref);
+
+        let epochs = start_ref
+            .record
+            .measurements
+            .keys()
+            .map(|key| (key.epoch - t0).to_seconds())
+            .collect::<Vec<_>>();
+
+        assert_eq!(epochs, vec![10.0, 20.0, 30.0, 60.0, 70.0]);
+
+        let station = &doris.header.ground_stations[0];
+        assert_eq!(station.label, "GODD");
+        assert_eq!(station.site, "GODDARD");
+        assert_eq!(station.k_frequency_shift, 0);
+
+        let l2 = Observable::UnambiguousPhaseRange(Frequency::DORIS2);
+        assert_eq!(doris.header.observables, vec![l2]);
+
+        let phases = doris
+            .record
+            .measurements
+            .iter()
+            .flat_map(|(key, measurements)| {
+                measurements
+                    .observations
+                    .values()
+                    .map(move |observation| (key.epoch, *observation))
+            })
+            .collect::<Vec<_>>();
+
+        let expected = [
+            (0.0, 0.0, true),
+            (10.0, -10.0E3, false),
+            (20.0, -30.0E3, false),
+            (50.0, 0.0, true),
+            (60.0, 10.0E3, false),
+        ];
+
+        assert_eq!(phases.len(), expected.len());
+
+        for ((epoch, observation), (t, phase, new_arc)) in phases.iter().zip(expected) {
+            assert_eq!(*epoch, t0 + Duration::from_seconds(t));
+            assert!((observation.value - phase).abs() < 1.0E-6);
+
+            assert_eq!(
+                observation.phase_flag == Some(PhaseFlag::Discontinuity),
+                new_arc
+            );
+        }
+
+        // instantaneous Doppler: trapezoidal integration
+        let samples = [(t0, 1.0), (t0 + Duration::from_seconds(10.0), 3.0)];
+        let phases = integrate_range_rate(&samples, None);
+
+        assert_eq!(phases.len(), 2);
+        assert_eq!(phases[0], (t0, 0.0, true));
+        assert_eq!(phases[1], (samples[1].0, 20.0, false));
+    }
+}
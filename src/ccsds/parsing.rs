@@ -0,0 +1,439 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Read},
+    path::Path,
+    str::FromStr,
+};
+
+use crate::{
+    ccsds::{Tdm, TdmDataType, TdmIntegrationRef, TdmMetadata, TdmObservation, TdmSegment},
+    prelude::{Duration, Epoch, ParsingError, TimeScale},
+};
+
+impl FromStr for TdmDataType {
+    type Err = ParsingError;
+
+    fn from_str(content: &str) -> Result<Self, Self::Err> {
+        let content = content.trim();
+
+        let participant = |prefix: &str| -> Option<u8> {
+            let n = content.strip_prefix(prefix)?.parse::<u8>().ok()?;
+
+            if (1..=5).contains(&n) {
+                Some(n)
+            } else {
+                None
+            }
+        };
+
+        match content {
+            "CARRIER_POWER" => Ok(Self::CarrierPower),
+            "DOPPLER_INSTANTANEOUS" => Ok(Self::DopplerInstantaneous),
+            "DOPPLER_INTEGRATED" => Ok(Self::DopplerIntegrated),
+            "PRESSURE" => Ok(Self::Pressure),
+            "RHUMIDITY" => Ok(Self::RelativeHumidity),
+            "TEMPERATURE" => Ok(Self::Temperature),
+            _ => {
+                if let Some(n) = participant("RECEIVE_PHASE_CT_") {
+                    Ok(Self::ReceivePhaseCount(n))
+                } else if let Some(n) = participant("TRANSMIT_FREQ_") {
+                    Ok(Self::TransmitFrequency(n))
+                } else if let Some(n) = participant("RECEIVE_FREQ_") {
+                    Ok(Self::ReceiveFrequency(n))
+                } else {
+                    Err(ParsingError::TdmDataType)
+                }
+            },
+        }
+    }
+}
+
+impl FromStr for TdmIntegrationRef {
+    type Err = ParsingError;
+
+    fn from_str(content: &str) -> Result<Self, Self::Err> {
+        match content.trim() {
+            "START" => Ok(Self::Start),
+            "MIDDLE" => Ok(Self::Middle),
+            "END" => Ok(Self::End),
+            _ => Err(ParsingError::TdmFormat),
+        }
+    }
+}
+
+/// Parses the TDM TIME_SYSTEM designation
+fn parse_time_system(content: &str) -> Result<TimeScale, ParsingError> {
+    match content.trim() {
+        "GPS" => Ok(TimeScale::GPST),
+        content => TimeScale::from_str(content).map_err(|_| ParsingError::TdmFormat),
+    }
+}
+
+/// Parses TDM epoch (YYYY-MM-DDThh:mm:ss.s or YYYY-DDDThh:mm:ss.s), in given [TimeScale]
+fn parse_epoch(content: &str, time_scale: TimeScale) -> Result<Epoch, ParsingError> {
+    let content = content.trim().trim_end_matches('Z');
+
+    let (date, time) = content.split_once('T').ok_or(ParsingError::EpochFormat)?;
+
+    let date = date
+        .split('-')
+        .map(|item| item.parse::<i32>().map_err(|_| ParsingError::EpochFormat))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let time = time.split(':').collect::<Vec<_>>();
+
+    if time.len() != 3 {
+        return Err(ParsingError::EpochFormat);
+    }
+
+    let hours = time[0]
+        .parse::<u8>()
+        .map_err(|_| ParsingError::EpochFormat)?;
+
+    let mins = time[1]
+        .parse::<u8>()
+        .map_err(|_| ParsingError::EpochFormat)?;
+
+    let secs = time[2]
+        .parse::<f64>()
+        .map_err(|_| ParsingError::EpochFormat)?;
+
+    let nanos = ((secs - secs.floor()) * 1.0E9).round() as u32;
+    let secs = secs.floor() as u8;
+
+    match date[..] {
+        [year, month, day] => Ok(Epoch::maybe_from_gregorian(
+            year,
+            month as u8,
+            day as u8,
+            hours,
+            mins,
+            secs,
+            nanos,
+            time_scale,
+        )?),
+        [year, doy] => {
+            let epoch =
+                Epoch::maybe_from_gregorian(year, 1, 1, hours, mins, secs, nanos, time_scale)?;
+
+            Ok(epoch + Duration::from_days((doy - 1) as f64))
+        },
+        _ => Err(ParsingError::EpochFormat),
+    }
+}
+
+/// Splits this KVN line as (keyword, value)
+fn parse_keyword(line: &str) -> Option<(&str, &str)> {
+    let (keyword, value) = line.split_once('=')?;
+    Some((keyword.trim(), value.trim()))
+}
+
+/// Parses one KVN metadata line into [TdmMetadata]
+fn parse_metadata(metadata: &mut TdmMetadata, line: &str) -> Result<(), ParsingError> {
+    if let Some(comment) = line.strip_prefix("COMMENT") {
+        metadata.comments.push(comment.trim().to_string());
+        return Ok(());
+    }
+
+    let (keyword, value) = parse_keyword(line).ok_or(ParsingError::TdmFormat)?;
+
+    match keyword {
+        "TIME_SYSTEM" => {
+            metadata.time_system = parse_time_system(value)?;
+        },
+        "PATH" | "PATH_1" => {
+            metadata.path = value
+                .split(',')
+                .map(|n| n.trim().parse::<u8>().map_err(|_| ParsingError::TdmFormat))
+                .collect::<Result<Vec<_>, _>>()?;
+        },
+        "TRANSMIT_BAND" => {
+            metadata.transmit_band = Some(value.to_string());
+        },
+        "RECEIVE_BAND" => {
+            metadata.receive_band = Some(value.to_string());
+        },
+        "INTEGRATION_INTERVAL" => {
+            let seconds = value.parse::<f64>().map_err(|_| ParsingError::TdmFormat)?;
+            metadata.integration_interval = Some(Duration::from_seconds(seconds));
+        },
+        "INTEGRATION_REF" => {
+            metadata.integration_ref = TdmIntegrationRef::from_str(value)?;
+        },
+        keyword => {
+            if let Some(n) = keyword.strip_prefix("PARTICIPANT_") {
+                let n = n.parse::<usize>().map_err(|_| ParsingError::TdmFormat)?;
+
+                if n == 0 {
+                    return Err(ParsingError::TdmFormat);
+                }
+
+                if metadata.participants.len() < n {
+                    metadata.participants.resize(n, String::new());
+                }
+
+                metadata.participants[n - 1] = value.to_string();
+            }
+        },
+    }
+
+    Ok(())
+}
+
+/// Parses one KVN data line. Returns None for data types we do not support.
+fn parse_observation(
+    line: &str,
+    time_scale: TimeScale,
+) -> Result<Option<TdmObservation>, ParsingError> {
+    let (keyword, value) = parse_keyword(line).ok_or(ParsingError::TdmFormat)?;
+
+    let Ok(data_type) = TdmDataType::from_str(keyword) else {
+        return Ok(None);
+    };
+
+    let mut items = value.split_ascii_whitespace();
+
+    let epoch = parse_epoch(items.next().ok_or(ParsingError::TdmFormat)?, time_scale)?;
+
+    let value = items
+        .next()
+        .ok_or(ParsingError::TdmFormat)?
+        .parse::<f64>()
+        .map_err(|_| ParsingError::TdmFormat)?;
+
+    Ok(Some(TdmObservation {
+        data_type,
+        epoch,
+        value,
+    }))
+}
+
+/// KVN sections
+#[derive(PartialEq)]
+enum Section {
+    Header,
+    Metadata,
+    /// Between META_STOP and DATA_START
+    Pending,
+    Data,
+    /// Between segments
+    Idle,
+}
+
+impl Tdm {
+    /// Parses [Tdm] from Keyword Value Notation (KVN), from [Read]able interface.
+    /// Data types that are not described by [TdmDataType] are ignored.
+    pub fn parse_kvn<R: Read>(reader: &mut BufReader<R>) -> Result<Self, ParsingError> {
+        let mut tdm = Tdm::default();
+
+        let mut section = Section::Header;
+        let mut segment = TdmSegment::default();
+        let mut versioned = false;
+
+        for line in reader.lines() {
+            let line = line?;
+            let line = line.trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            match line {
+                "META_START" => {
+                    segment = TdmSegment::default();
+                    section = Section::Metadata;
+                    continue;
+                },
+                "META_STOP" => {
+                    section = Section::Pending;
+                    continue;
+                },
+                "DATA_START" => {
+                    section = Section::Data;
+                    continue;
+                },
+                "DATA_STOP" => {
+                    tdm.segments.push(segment.clone());
+                    section = Section::Idle;
+                    continue;
+                },
+                _ => {},
+            }
+
+            match section {
+                Section::Header => {
+                    if let Some(comment) = line.strip_prefix("COMMENT") {
+                        tdm.comments.push(comment.trim().to_string());
+                        continue;
+                    }
+
+                    let (keyword, value) = parse_keyword(line).ok_or(ParsingError::TdmFormat)?;
+
+                    match keyword {
+                        "CCSDS_TDM_VERS" => {
+                            if !value.starts_with('1') && !value.starts_with('2') {
+                                return Err(ParsingError::TdmFormat);
+                            }
+
+                            versioned = true;
+                        },
+                        "CREATION_DATE" => {
+                            tdm.creation_date = parse_epoch(value, TimeScale::UTC)?;
+                        },
+                        "ORIGINATOR" => {
+                            tdm.originator = value.to_string();
+                        },
+                        _ => {},
+                    }
+                },
+                Section::Metadata => parse_metadata(&mut segment.metadata, line)?,
+                Section::Data => {
+                    if let Some(comment) = line.strip_prefix("COMMENT") {
+                        segment.comments.push(comment.trim().to_string());
+                    } else if let Some(observation) =
+                        parse_observation(line, segment.metadata.time_system)?
+                    {
+                        segment.observations.push(observation);
+                    }
+                },
+                Section::Pending | Section::Idle => {
+                    let comment = line
+                        .strip_prefix("COMMENT")
+                        .ok_or(ParsingError::TdmFormat)?
+                        .trim()
+                        .to_string();
+
+                    // comments between segments describe the previous segment
+                    if section == Section::Pending {
+                        segment.comments.push(comment);
+                    } else if let Some(previous) = tdm.segments.last_mut() {
+                        previous.comments.push(comment);
+                    }
+                },
+            }
+        }
+
+        if !versioned || section != Section::Idle {
+            return Err(ParsingError::TdmFormat);
+        }
+
+        Ok(tdm)
+    }
+
+    /// Parses [Tdm] from local KVN file.
+    pub fn from_kvn_file<P: AsRef<Path>>(path: P) -> Result<Self, ParsingError> {
+        let fd = File::open(path)?;
+        let mut reader = BufReader::new(fd);
+        Self::parse_kvn(&mut reader)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_epoch;
+    use crate::ccsds::{Tdm, TdmDataType, TdmIntegrationRef};
+    use crate::prelude::{Duration, Epoch, TimeScale};
+
+    use std::io::BufReader;
+    use std::str::FromStr;
+
+    #[test]
+    fn tdm_epoch_parsing() {
+        let expected = Epoch::from_gregorian(2018, 6, 13, 0, 0, 10, 500_000_000, TimeScale::UTC);
+
+        assert_eq!(
+            parse_epoch("2018-06-13T00:00:10.5", TimeScale::UTC).unwrap(),
+            expected
+        );
+
+        assert_eq!(
+            parse_epoch("2018-164T00:00:10.500Z", TimeScale::UTC).unwrap(),
+            expected
+        );
+
+        assert!(parse_epoch("2018-06-13 00:00:10", TimeScale::UTC).is_err());
+    }
+
+    #[test]
+    fn tdm_data_types() {
+        for (content, expected) in [
+            ("CARRIER_POWER", TdmDataType::CarrierPower),
+            ("DOPPLER_INTEGRATED", TdmDataType::DopplerIntegrated),
+            ("RECEIVE_PHASE_CT_2", TdmDataType::ReceivePhaseCount(2)),
+            ("TRANSMIT_FREQ_1", TdmDataType::TransmitFrequency(1)),
+            ("RECEIVE_FREQ_3", TdmDataType::ReceiveFrequency(3)),
+        ] {
+            let data_type = TdmDataType::from_str(content).unwrap();
+            assert_eq!(data_type, expected);
+            assert_eq!(data_type.to_string(), content);
+        }
+
+        assert!(TdmDataType::from_str("RECEIVE_FREQ_6").is_err());
+        assert!(TdmDataType::from_str("ANGLE_1").is_err());
+    }
+
+    #[test]
+    fn tdm_kvn_parsing() {
+        let content = "CCSDS_TDM_VERS = 2.0
+COMMENT partner tracking data
+CREATION_DATE = 2018-165T12:00:00
+ORIGINATOR = NASA
+
+META_START
+TIME_SYSTEM = UTC
+PARTICIPANT_1 = GODDARD
+PARTICIPANT_2 = JASON-3
+MODE = SEQUENTIAL
+PATH = 1,2
+TRANSMIT_BAND = S
+INTEGRATION_INTERVAL = 10.0
+INTEGRATION_REF = MIDDLE
+META_STOP
+
+COMMENT data follows
+DATA_START
+COMMENT one-way Doppler
+DOPPLER_INTEGRATED = 2018-06-13T00:00:10 -1.25
+ANGLE_1 = 2018-06-13T00:00:10 45.0
+DOPPLER_INTEGRATED = 2018-06-13T00:00:20 -1.5
+DATA_STOP
+COMMENT end of segment
+";
+
+        let tdm = Tdm::parse_kvn(&mut BufReader::new(content.as_bytes())).unwrap();
+
+        assert_eq!(tdm.comments, vec!["partner tracking data"]);
+        assert_eq!(tdm.originator, "NASA");
+        assert_eq!(tdm.segments.len(), 1);
+
+        let segment = &tdm.segments[0];
+        assert_eq!(segment.metadata.time_system, TimeScale::UTC);
+        assert_eq!(segment.metadata.participants, vec!["GODDARD", "JASON-3"]);
+        assert_eq!(segment.metadata.path, vec![1, 2]);
+        assert_eq!(segment.metadata.transmit_band.as_deref(), Some("S"));
+        assert_eq!(
+            segment.metadata.integration_interval,
+            Some(Duration::from_seconds(10.0))
+        );
+        assert_eq!(segment.metadata.integration_ref, TdmIntegrationRef::Middle);
+
+        assert_eq!(
+            segment.comments,
+            vec!["data follows", "one-way Doppler", "end of segment"]
+        );
+        assert_eq!(segment.observations.len(), 2);
+        assert_eq!(segment.observations[1].value, -1.5);
+        assert_eq!(
+            segment.observations[1].epoch,
+            Epoch::from_gregorian_utc(2018, 6, 13, 0, 0, 20, 0)
+        );
+
+        assert!(Tdm::parse_kvn(&mut BufReader::new("META_START\n".as_bytes())).is_err());
+
+        let invalid = content.replace("COMMENT data follows", "ANGLE_1 = 2018-06-13T00:00:10 45.0");
+        assert!(Tdm::parse_kvn(&mut BufReader::new(invalid.as_bytes())).is_err());
+
+        let invalid = content.replace("INTEGRATION_REF = MIDDLE", "INTEGRATION_REF = CENTER");
+        assert!(Tdm::parse_kvn(&mut BufReader::new(invalid.as_bytes())).is_err());
+    }
+}
@@ -1 +1,4 @@
 pub const USO_FREQ_HZ: f64 = 5.0E6_f64;
+
+/// Speed of light in vacuum, in m/s
+pub const SPEED_OF_LIGHT_M_S: f64 = 299792458.0;
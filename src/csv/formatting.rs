@@ -0,0 +1,286 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use itertools::Itertools;
+
+use crate::{
+    csv::{
+        CsvEpochFormat, CsvLayout, CsvOptions, COMMENT_KEY, COSPAR_KEY, OBSERVABLES_KEY,
+        SATELLITE_KEY, STATION_KEY, VERSION_KEY,
+    },
+    prelude::{Duration, Epoch, FormattingError, Key, Measurements, Observation, DORIS},
+    record::columns::{
+        epoch_flag_value, phase_flag_value, snr_value, wide_observables, CLOCK_EXTRAPOLATED_COLUMN,
+        CLOCK_OFFSET_COLUMN, FLAG_COLUMN, OBSERVABLE_COLUMN, PHASE_FLAG_COLUMN, PHASE_FLAG_SUFFIX,
+        SNR_COLUMN, SNR_SUFFIX, VALUE_COLUMN,
+    },
+};
+
+impl CsvEpochFormat {
+    /// Formats this [Epoch] according to this [CsvEpochFormat]
+    pub(crate) fn format(&self, epoch: Epoch) -> String {
+        match self {
+            Self::MJD => format!("{:.11}", epoch.to_mjd_tai_days()),
+            _ => {
+                let (year, month, day, hours, mins, secs, nanos) =
+                    epoch.to_gregorian(self.time_scale());
+
+                format!(
+                    "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:09}",
+                    year, month, day, hours, mins, secs, nanos
+                )
+            },
+        }
+    }
+}
+
+/// Formats this [Duration] in seconds, with nanosecond resolution
+fn fmt_seconds(duration: Duration) -> String {
+    let nanos = duration.total_nanoseconds();
+    let sign = if nanos < 0 { "-" } else { "" };
+
+    format!(
+        "{}{}.{:09}",
+        sign,
+        nanos.abs() / 1_000_000_000,
+        nanos.abs() % 1_000_000_000
+    )
+}
+
+/// Formats an optional field, empty when unknown
+fn fmt_optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+/// Formats the (value, SNR, phase flag) fields of this [Observation]
+fn fmt_observation(observation: Option<&Observation>) -> String {
+    match observation {
+        Some(observation) => format!(
+            "{},{},{}",
+            observation.value,
+            fmt_optional(observation.snr.map(snr_value)),
+            fmt_optional(observation.phase_flag.map(phase_flag_value)),
+        ),
+        None => ",,".to_string(),
+    }
+}
+
+impl DORIS {
+    /// Formats the (epoch, flag, station) fields of one row
+    fn fmt_csv_key(key: &Key, station: &str, options: &CsvOptions) -> String {
+        format!(
+            "{},{},{}",
+            options.epoch_format.format(key.epoch),
+            epoch_flag_value(key.flag),
+            station,
+        )
+    }
+
+    /// Formats the satellite clock fields of one row
+    fn fmt_csv_clock(measurements: &Measurements) -> String {
+        match measurements.satellite_clock_offset {
+            Some(clock) => format!("{},{}", fmt_seconds(clock.offset), clock.extrapolated),
+            None => ",".to_string(),
+        }
+    }
+
+    /// Formats the [Header] information and record comments, as comment lines
+    fn format_csv_metadata<W: Write>(&self, w: &mut BufWriter<W>) -> Result<(), FormattingError> {
+        writeln!(
+            w,
+            "# {}: {}.{:02}",
+            VERSION_KEY, self.header.version.major, self.header.version.minor
+        )?;
+
+        writeln!(w, "# {}: {}", SATELLITE_KEY, self.header.satellite)?;
+
+        if let Some(cospar) = &self.header.cospar {
+            writeln!(w, "# {}: {}", COSPAR_KEY, cospar)?;
+        }
+
+        if !self.header.observables.is_empty() {
+            writeln!(
+                w,
+                "# {}: {}",
+                OBSERVABLES_KEY,
+                self.header
+                    .observables
+                    .iter()
+                    .map(|observable| format!("{:x}", observable))
+                    .join(" ")
+            )?;
+        }
+
+        for station in self.header.ground_stations.iter() {
+            writeln!(
+                w,
+                "# {}: {} {} {} {}",
+                STATION_KEY, station.code, station.label, station.domes, station.site
+            )?;
+        }
+
+        for comment in self.record.comments.iter() {
+            writeln!(w, "# {}: {}", COMMENT_KEY, comment)?;
+        }
+
+        Ok(())
+    }
+
+    /// Formats this [DORIS] record as CSV, into [Write]able interface.
+    /// See the [module](crate::csv) documentation for the table description.
+    ///
+    /// ```
+    /// use doris_rs::prelude::*;
+    /// use std::io::BufWriter;
+    ///
+    /// let doris = DORIS::from_gzip_file("data/DOR/V3/cs2rx18164.gz")
+    ///     .unwrap();
+    ///
+    /// let options = CsvOptions::default()
+    ///     .with_layout(CsvLayout::Wide)
+    ///     .with_epoch_format(CsvEpochFormat::UTC)
+    ///     .with_station(CsvStation::DOMES);
+    ///
+    /// let mut writer = BufWriter::new(Vec::new());
+    ///
+    /// doris.format_csv(&mut writer, &options)
+    ///     .unwrap();
+    /// ```
+    pub fn format_csv<W: Write>(
+        &self,
+        w: &mut BufWriter<W>,
+        options: &CsvOptions,
+    ) -> Result<(), FormattingError> {
+        if options.metadata {
+            self.format_csv_metadata(w)?;
+        }
+
+        let epoch_column = options.epoch_format.column();
+        let station_column = options.station.column();
+
+        let mut identifiers = HashMap::new();
+
+        match options.layout {
+            CsvLayout::Long => {
+                writeln!(
+                    w,
+                    "{},{},{},{},{},{},{},{},{}",
+                    epoch_column,
+                    FLAG_COLUMN,
+                    station_column,
+                    OBSERVABLE_COLUMN,
+                    VALUE_COLUMN,
+                    SNR_COLUMN,
+                    PHASE_FLAG_COLUMN,
+                    CLOCK_OFFSET_COLUMN,
+                    CLOCK_EXTRAPOLATED_COLUMN,
+                )?;
+
+                for (key, measurements) in self.record.measurements.iter() {
+                    let clock = Self::fmt_csv_clock(measurements);
+
+                    if measurements.observations.is_empty() {
+                        writeln!(
+                            w,
+                            "{},,{},{}",
+                            Self::fmt_csv_key(key, "", options),
+                            fmt_observation(None),
+                            clock
+                        )?;
+                    }
+
+                    for (obs_key, observation) in measurements.observations.iter() {
+                        let station = identifiers
+                            .entry(obs_key.station.clone())
+                            .or_insert_with(|| options.station.identifier(&obs_key.station));
+
+                        writeln!(
+                            w,
+                            "{},{:x},{},{}",
+                            Self::fmt_csv_key(key, station, options),
+                            obs_key.observable,
+                            fmt_observation(Some(observation)),
+                            clock
+                        )?;
+                    }
+                }
+            },
+            CsvLayout::Wide => {
+                let observables = wide_observables(&self.header, &self.record);
+
+                write!(w, "{},{},{}", epoch_column, FLAG_COLUMN, station_column)?;
+
+                for observable in observables.iter() {
+                    let code = format!("{:x}", observable);
+                    write!(
+                        w,
+                        ",{},{}{},{}{}",
+                        code, code, SNR_SUFFIX, code, PHASE_FLAG_SUFFIX
+                    )?;
+                }
+
+                writeln!(w, ",{},{}", CLOCK_OFFSET_COLUMN, CLOCK_EXTRAPOLATED_COLUMN)?;
+
+                for (key, measurements) in self.record.measurements.iter() {
+                    let clock = Self::fmt_csv_clock(measurements);
+
+                    if measurements.observations.is_empty() {
+                        write!(w, "{}", Self::fmt_csv_key(key, "", options))?;
+
+                        for _ in observables.iter() {
+                            write!(w, ",{}", fmt_observation(None))?;
+                        }
+
+                        writeln!(w, ",{}", clock)?;
+                    }
+
+                    // observations are sorted by station
+                    for (station, observations) in &measurements
+                        .observations
+                        .iter()
+                        .chunk_by(|(obs_key, _)| &obs_key.station)
+                    {
+                        let observations = observations
+                            .map(|(obs_key, observation)| (obs_key.observable, observation))
+                            .collect::<HashMap<_, _>>();
+
+                        let station = identifiers
+                            .entry(station.clone())
+                            .or_insert_with(|| options.station.identifier(station));
+
+                        write!(w, "{}", Self::fmt_csv_key(key, station, options))?;
+
+                        for observable in observables.iter() {
+                            write!(
+                                w,
+                                ",{}",
+                                fmt_observation(observations.get(observable).copied())
+                            )?;
+                        }
+
+                        writeln!(w, ",{}", clock)?;
+                    }
+                }
+            },
+        }
+
+        w.flush()?;
+        Ok(())
+    }
+
+    /// Dumps this [DORIS] record into local CSV file.
+    /// See the [module](crate::csv) documentation for the table description.
+    pub fn to_csv_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+        options: &CsvOptions,
+    ) -> Result<(), FormattingError> {
+        let fd = File::create(path)?;
+        let mut writer = BufWriter::new(fd);
+        self.format_csv(&mut writer, options)
+    }
+}
@@ -0,0 +1,215 @@
+//! Comma Separated Values (CSV) exchanges of the observations.
+//!
+//! Lines starting with `#` are comments. When requested, the [Header](crate::prelude::Header)
+//! information is described by `# key: value` comment lines, prior to the table.
+//! The first line of the table names the columns, empty fields are unknown values.
+//! In the long layout ([CsvLayout::Long]), each row describes one observation:
+//!
+//! | Column               | Content                                                      |
+//! |----------------------|--------------------------------------------------------------|
+//! | `epoch_<format>`     | [Epoch](crate::prelude::Epoch), see [CsvEpochFormat]         |
+//! | `flag`               | [EpochFlag](crate::prelude::EpochFlag) (RINEX value)         |
+//! | `station_<id>`       | [GroundStation], see [CsvStation]                            |
+//! | `observable`         | [Observable](crate::prelude::Observable) (RINEX code)         |
+//! | `value`              | Observed value, [Observable](crate::prelude::Observable) dependent |
+//! | `snr`                | [SNR](crate::prelude::SNR) (RINEX value)                     |
+//! | `phase_flag`         | [PhaseFlag](crate::prelude::PhaseFlag) (RINEX value)         |
+//! | `clock_offset`       | Satellite [ClockOffset](crate::prelude::ClockOffset), in seconds |
+//! | `clock_extrapolated` | True for extrapolated [ClockOffset](crate::prelude::ClockOffset) |
+//!
+//! In the wide layout ([CsvLayout::Wide]), each row describes one epoch and station,
+//! and the `observable`, `value`, `snr` and `phase_flag` columns are replaced
+//! by three columns per [Observable](crate::prelude::Observable): `<code>`, `<code>_snr`
+//! and `<code>_phase_flag` (for example `L1`, `L1_snr` and `L1_phase_flag`).
+//! Epochs that do not contain any observation (like events) are described by a row
+//! without station.
+mod formatting;
+mod parsing;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    prelude::{GroundStation, TimeScale},
+    record::columns::{STATION_CODE_COLUMN, STATION_DOMES_COLUMN, STATION_LABEL_COLUMN},
+};
+
+/// Metadata comment key of the satellite name
+pub(crate) const SATELLITE_KEY: &str = "satellite";
+
+/// Metadata comment key of the satellite COSPAR number
+pub(crate) const COSPAR_KEY: &str = "cospar";
+
+/// Metadata comment key of the RINEX revision
+pub(crate) const VERSION_KEY: &str = "version";
+
+/// Metadata comment key of the [Observable](crate::prelude::Observable)s
+pub(crate) const OBSERVABLES_KEY: &str = "observables";
+
+/// Metadata comment key of the [GroundStation]s (code, label, DOMES and name)
+pub(crate) const STATION_KEY: &str = "station";
+
+/// Metadata comment key of the record comments
+pub(crate) const COMMENT_KEY: &str = "comment";
+
+/// [CsvLayout] describes the organization of the CSV table
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CsvLayout {
+    /// One row per observation
+    #[default]
+    Long,
+
+    /// One row per epoch and station, one column per [Observable](crate::prelude::Observable)
+    Wide,
+}
+
+/// [CsvEpochFormat] describes how [Epoch](crate::prelude::Epoch)s are formatted.
+/// The epoch column is named after the format (for example `epoch_utc`).
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CsvEpochFormat {
+    /// ISO 8601 datetime (YYYY-MM-DDThh:mm:ss.nnnnnnnnn) in [TimeScale::TAI]
+    #[default]
+    TAI,
+
+    /// ISO 8601 datetime (YYYY-MM-DDThh:mm:ss.nnnnnnnnn) in [TimeScale::UTC]
+    UTC,
+
+    /// ISO 8601 datetime (YYYY-MM-DDThh:mm:ss.nnnnnnnnn) in [TimeScale::GPST]
+    GPST,
+
+    /// Modified Julian Date in [TimeScale::TAI], as decimal days.
+    /// This format is limited to the microsecond precision.
+    MJD,
+}
+
+impl CsvEpochFormat {
+    /// Returns the name of the epoch column
+    pub(crate) fn column(&self) -> &'static str {
+        match self {
+            Self::TAI => "epoch_tai",
+            Self::UTC => "epoch_utc",
+            Self::GPST => "epoch_gpst",
+            Self::MJD => "epoch_mjd",
+        }
+    }
+
+    /// Returns the [CsvEpochFormat] described by this column name
+    pub(crate) fn from_column(name: &str) -> Option<Self> {
+        [Self::TAI, Self::UTC, Self::GPST, Self::MJD]
+            .into_iter()
+            .find(|format| format.column() == name)
+    }
+
+    /// Returns the [TimeScale] of the ISO 8601 formats
+    pub(crate) fn time_scale(&self) -> TimeScale {
+        match self {
+            Self::UTC => TimeScale::UTC,
+            Self::GPST => TimeScale::GPST,
+            Self::TAI | Self::MJD => TimeScale::TAI,
+        }
+    }
+}
+
+/// [CsvStation] describes how [GroundStation]s are identified
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CsvStation {
+    /// Identified by unique code, which is file dependent
+    Code,
+
+    /// Identified by site label (mnemonic)
+    #[default]
+    Label,
+
+    /// Identified by DOMES site number
+    DOMES,
+}
+
+impl CsvStation {
+    /// Returns the name of the station column
+    pub(crate) fn column(&self) -> &'static str {
+        match self {
+            Self::Code => STATION_CODE_COLUMN,
+            Self::Label => STATION_LABEL_COLUMN,
+            Self::DOMES => STATION_DOMES_COLUMN,
+        }
+    }
+
+    /// Returns the [CsvStation] described by this column name
+    pub(crate) fn from_column(name: &str) -> Option<Self> {
+        [Self::Code, Self::Label, Self::DOMES]
+            .into_iter()
+            .find(|station| station.column() == name)
+    }
+
+    /// Returns the identifier of this [GroundStation]
+    pub(crate) fn identifier(&self, station: &GroundStation) -> String {
+        match self {
+            Self::Code => station.code.to_string(),
+            Self::Label => station.label.clone(),
+            Self::DOMES => station.domes.to_string(),
+        }
+    }
+}
+
+/// [CsvOptions] describes the CSV content produced by
+/// [DORIS::format_csv](crate::prelude::DORIS::format_csv).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CsvOptions {
+    /// [CsvLayout] of the table
+    pub layout: CsvLayout,
+
+    /// [CsvEpochFormat] of the epoch column
+    pub epoch_format: CsvEpochFormat,
+
+    /// [CsvStation] identification
+    pub station: CsvStation,
+
+    /// Describe the [Header](crate::prelude::Header) information
+    /// and the record comments, as `#` comment lines
+    pub metadata: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            layout: Default::default(),
+            epoch_format: Default::default(),
+            station: Default::default(),
+            metadata: true,
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Copies and returns [CsvOptions] with updated [CsvLayout]
+    pub fn with_layout(&self, layout: CsvLayout) -> Self {
+        let mut s = *self;
+        s.layout = layout;
+        s
+    }
+
+    /// Copies and returns [CsvOptions] with updated [CsvEpochFormat]
+    pub fn with_epoch_format(&self, format: CsvEpochFormat) -> Self {
+        let mut s = *self;
+        s.epoch_format = format;
+        s
+    }
+
+    /// Copies and returns [CsvOptions] with updated [CsvStation] identification
+    pub fn with_station(&self, station: CsvStation) -> Self {
+        let mut s = *self;
+        s.station = station;
+        s
+    }
+
+    /// Copies and returns [CsvOptions] with (or without) metadata comment lines
+    pub fn with_metadata(&self, metadata: bool) -> Self {
+        let mut s = *self;
+        s.metadata = metadata;
+        s
+    }
+}
@@ -0,0 +1,546 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufRead, BufReader, Read},
+    path::Path,
+    str::FromStr,
+};
+
+use crate::{
+    csv::{CsvEpochFormat, CsvStation, COMMENT_KEY},
+    prelude::{
+        ClockOffset, Epoch, Header, Key, Observable, Observation, ParsingError, Record,
+        StationHandle, DORIS, SNR,
+    },
+    record::columns::{
+        duration_from_seconds, epoch_flag, phase_flag, StationResolver, CLOCK_EXTRAPOLATED_COLUMN,
+        CLOCK_OFFSET_COLUMN, FLAG_COLUMN, OBSERVABLE_COLUMN, PHASE_FLAG_COLUMN, PHASE_FLAG_SUFFIX,
+        SNR_COLUMN, SNR_SUFFIX, VALUE_COLUMN,
+    },
+};
+
+#[cfg(doc)]
+use crate::prelude::GroundStation;
+
+impl CsvEpochFormat {
+    /// Parses [Epoch] formatted according to this [CsvEpochFormat]
+    pub(crate) fn parse(&self, content: &str) -> Result<Epoch, ParsingError> {
+        if *self == Self::MJD {
+            let days = content
+                .parse::<f64>()
+                .map_err(|_| ParsingError::EpochFormat)?;
+
+            return Ok(Epoch::from_mjd_tai(days));
+        }
+
+        let (date, time) = content.split_once('T').ok_or(ParsingError::EpochFormat)?;
+
+        let mut date = date.split('-');
+        let mut time = time.split(':');
+
+        let next = |items: &mut dyn Iterator<Item = &str>| -> Result<u8, ParsingError> {
+            items
+                .next()
+                .and_then(|item| item.parse::<u8>().ok())
+                .ok_or(ParsingError::EpochFormat)
+        };
+
+        let year = date
+            .next()
+            .and_then(|year| year.parse::<i32>().ok())
+            .ok_or(ParsingError::EpochFormat)?;
+
+        let month = next(&mut date)?;
+        let day = next(&mut date)?;
+        let hours = next(&mut time)?;
+        let mins = next(&mut time)?;
+
+        let seconds = time.next().ok_or(ParsingError::EpochFormat)?;
+
+        let (secs, nanos) = match seconds.split_once('.') {
+            Some((secs, fraction)) => {
+                if fraction.len() > 9 {
+                    return Err(ParsingError::EpochFormat);
+                }
+
+                let nanos = format!("{:0<9}", fraction)
+                    .parse::<u32>()
+                    .map_err(|_| ParsingError::EpochFormat)?;
+
+                (secs, nanos)
+            },
+            None => (seconds, 0),
+        };
+
+        let secs = secs.parse::<u8>().map_err(|_| ParsingError::EpochFormat)?;
+
+        Ok(Epoch::maybe_from_gregorian(
+            year,
+            month,
+            day,
+            hours,
+            mins,
+            secs,
+            nanos,
+            self.time_scale(),
+        )?)
+    }
+}
+
+impl CsvStation {
+    /// Returns the [StationHandle] described by this identifier
+    fn resolve(
+        &self,
+        resolver: &mut StationResolver,
+        identifier: &str,
+    ) -> Result<StationHandle, ParsingError> {
+        match self {
+            Self::Code => {
+                let code = identifier
+                    .parse::<u16>()
+                    .map_err(|_| ParsingError::CsvFormat)?;
+
+                resolver.resolve(Some(code), None, None)
+            },
+            Self::Label => resolver.resolve(None, Some(identifier), None),
+            Self::DOMES => resolver.resolve(None, None, Some(identifier)),
+        }
+    }
+}
+
+/// Observation columns: (value, SNR, phase flag) column indices
+type ObservationColumns = (usize, Option<usize>, Option<usize>);
+
+/// Parses an optional field, which is empty when unknown
+fn parse_optional<T: FromStr>(field: &str) -> Result<Option<T>, ParsingError> {
+    if field.is_empty() {
+        Ok(None)
+    } else {
+        let value = field.parse::<T>().map_err(|_| ParsingError::CsvFormat)?;
+        Ok(Some(value))
+    }
+}
+
+/// Parses the [Observation] described by these columns, if any
+fn parse_observation(
+    fields: &[&str],
+    columns: &ObservationColumns,
+) -> Result<Option<Observation>, ParsingError> {
+    let (value, snr, phase) = *columns;
+
+    let Some(value) = parse_optional::<f64>(fields[value])? else {
+        return Ok(None);
+    };
+
+    let mut observation = Observation::default().with_value(value);
+
+    if let Some(snr) = snr.and_then(|snr| parse_optional::<u8>(fields[snr]).transpose()) {
+        observation = observation.with_snr(SNR::from(snr?));
+    }
+
+    if let Some(flag) = phase.and_then(|phase| parse_optional::<u8>(fields[phase]).transpose()) {
+        observation = observation.with_phase_flag(phase_flag(flag?)?);
+    }
+
+    Ok(Some(observation))
+}
+
+impl DORIS {
+    /// Parses [DORIS] from CSV content, from [Read]able interface.
+    /// The layout, epoch format and station identification are identified
+    /// from the column names, see the [module](crate::csv) documentation.
+    /// The [Header] template describes the resulting [DORIS] file and its [GroundStation]s:
+    /// stations that are not described by the template are given a new unique code.
+    /// The [GroundStation]s, [Observable]s and time frame of the record are then
+    /// described in the resulting [Header], see [Header::describe_record].
+    /// Record comments are recovered from the `# comment:` lines,
+    /// other comment lines are ignored.
+    ///
+    /// ```
+    /// use doris_rs::prelude::*;
+    /// use std::io::BufReader;
+    ///
+    /// let content = "epoch_utc,flag,station_label,L1,L1_snr,L1_phase_flag,clock_offset,clock_extrapolated
+    /// 2018-06-13T00:00:00.000000000,0,TLSB,1.5,,,,
+    /// 2018-06-13T00:00:10.000000000,0,TLSB,2.5,,,-0.001,false";
+    ///
+    /// let template = Header::default();
+    ///
+    /// let doris = DORIS::parse_csv(&mut BufReader::new(content.as_bytes()), &template)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(doris.record.measurements.len(), 2);
+    ///
+    /// // the header now describes the record
+    /// assert_eq!(doris.header.ground_stations[0].label, "TLSB");
+    /// assert_eq!(doris.header.observables, vec![Observable::UnambiguousPhaseRange(Frequency::DORIS1)]);
+    /// ```
+    pub fn parse_csv<R: Read>(
+        reader: &mut BufReader<R>,
+        template: &Header,
+    ) -> Result<Self, ParsingError> {
+        let mut record = Record::default();
+
+        let mut columns = HashMap::<String, usize>::new();
+        let mut names = Vec::<String>::new();
+
+        let mut epoch_format = CsvEpochFormat::default();
+        let mut identification = CsvStation::default();
+        let mut stations = StationResolver::new(&template.ground_stations);
+
+        // long layout: (observable, observation) columns,
+        // wide layout: observation columns per observable
+        let mut long = None::<(usize, ObservationColumns)>;
+        let mut wide = Vec::<(Observable, ObservationColumns)>::new();
+
+        let mut epoch_index = 0;
+        let mut flag_index = 0;
+        let mut station_index = 0;
+        let mut clock_indices = None::<(usize, Option<usize>)>;
+
+        for line in reader.lines() {
+            let line = line?;
+            let line = line.trim_end();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(comment) = line.strip_prefix('#') {
+                if let Some((key, value)) = comment.split_once(':') {
+                    if key.trim() == COMMENT_KEY {
+                        record.comments.push(value.trim().to_string());
+                    }
+                }
+
+                continue;
+            }
+
+            let fields = line
+                .split(',')
+                .map(|field| field.trim())
+                .collect::<Vec<_>>();
+
+            if names.is_empty() {
+                // table header
+                names = fields.iter().map(|name| name.to_string()).collect();
+
+                for (index, name) in names.iter().enumerate() {
+                    columns.insert(name.clone(), index);
+                }
+
+                let column = |name: &str| columns.get(name).copied();
+
+                (epoch_index, epoch_format) = names
+                    .iter()
+                    .enumerate()
+                    .find_map(|(index, name)| {
+                        CsvEpochFormat::from_column(name).map(|format| (index, format))
+                    })
+                    .ok_or(ParsingError::CsvFormat)?;
+
+                (station_index, identification) = names
+                    .iter()
+                    .enumerate()
+                    .find_map(|(index, name)| {
+                        CsvStation::from_column(name).map(|station| (index, station))
+                    })
+                    .ok_or(ParsingError::CsvFormat)?;
+
+                flag_index = column(FLAG_COLUMN).ok_or(ParsingError::CsvFormat)?;
+
+                clock_indices = column(CLOCK_OFFSET_COLUMN)
+                    .map(|offset| (offset, column(CLOCK_EXTRAPOLATED_COLUMN)));
+
+                if let Some(observable) = column(OBSERVABLE_COLUMN) {
+                    let value = column(VALUE_COLUMN).ok_or(ParsingError::CsvFormat)?;
+                    long = Some((
+                        observable,
+                        (value, column(SNR_COLUMN), column(PHASE_FLAG_COLUMN)),
+                    ));
+                } else {
+                    for (index, name) in names.iter().enumerate() {
+                        if name.ends_with(SNR_SUFFIX) || name.ends_with(PHASE_FLAG_SUFFIX) {
+                            continue;
+                        }
+
+                        let Ok(observable) = Observable::from_str(name) else {
+                            continue;
+                        };
+
+                        wide.push((
+                            observable,
+                            (
+                                index,
+                                column(&format!("{}{}", name, SNR_SUFFIX)),
+                                column(&format!("{}{}", name, PHASE_FLAG_SUFFIX)),
+                            ),
+                        ));
+                    }
+                }
+
+                continue;
+            }
+
+            if fields.len() != names.len() {
+                return Err(ParsingError::CsvFormat);
+            }
+
+            let flag = fields[flag_index]
+                .parse::<u8>()
+                .map_err(|_| ParsingError::CsvFormat)?;
+
+            let key = Key {
+                epoch: epoch_format.parse(fields[epoch_index])?,
+                flag: epoch_flag(flag)?,
+            };
+
+            let measurements = record.measurements.entry(key).or_default();
+
+            if let Some((offset, extrapolated)) = clock_indices {
+                if let Some(offset) = parse_optional::<f64>(fields[offset])? {
+                    let extrapolated = match extrapolated {
+                        Some(index) => parse_optional::<bool>(fields[index])?.unwrap_or_default(),
+                        None => false,
+                    };
+
+                    measurements.satellite_clock_offset = Some(ClockOffset {
+                        offset: duration_from_seconds(offset),
+                        extrapolated,
+                    });
+                }
+            }
+
+            if fields[station_index].is_empty() {
+                continue;
+            }
+
+            let station = identification.resolve(&mut stations, fields[station_index])?;
+
+            if let Some((observable, observation)) = &long {
+                if fields[*observable].is_empty() {
+                    continue;
+                }
+
+                let observable = Observable::from_str(fields[*observable])?;
+
+                let observation =
+                    parse_observation(&fields, observation)?.ok_or(ParsingError::CsvFormat)?;
+
+                measurements.add_observation(station, observable, observation);
+            } else {
+                for (observable, observation) in wide.iter() {
+                    if let Some(observation) = parse_observation(&fields, observation)? {
+                        measurements.add_observation(station.clone(), *observable, observation);
+                    }
+                }
+            }
+        }
+
+        if names.is_empty() {
+            return Err(ParsingError::CsvFormat);
+        }
+
+        let mut header = template.clone();
+        header.describe_record(&record);
+
+        Ok(DORIS::new(header, record))
+    }
+
+    /// Parses [DORIS] from local CSV file, see [Self::parse_csv].
+    pub fn from_csv_file<P: AsRef<Path>>(path: P, template: &Header) -> Result<Self, ParsingError> {
+        let fd = File::open(path)?;
+        let mut reader = BufReader::new(fd);
+        Self::parse_csv(&mut reader, template)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::csv::{CsvEpochFormat, CsvLayout, CsvOptions, CsvStation};
+    use crate::prelude::{
+        ClockOffset, Duration, EpochFlag, Frequency, GroundStation, Key, Observable, PhaseFlag,
+        DORIS, SNR,
+    };
+    use crate::tests::toolkit::{add_synthetic_samples, synthetic_record, synthetic_t0};
+
+    use std::io::{BufReader, BufWriter};
+
+    #[test]
+    fn csv_epoch_parsing() {
+        let t0 = synthetic_t0();
+
+        assert_eq!(
+            CsvEpochFormat::TAI.parse("2018-06-13T00:00:00.5").unwrap(),
+            t0 + Duration::from_seconds(0.5)
+        );
+
+        for invalid in [
+            "2018-02-30T00:00:00",
+            "2018-06-13T25:00:00",
+            "2018-06-13T00:61:00",
+            "2018-13-01T00:00:00",
+        ] {
+            assert!(CsvEpochFormat::TAI.parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn csv_round_trip() {
+        let l1 = Observable::UnambiguousPhaseRange(Frequency::DORIS1);
+        let l2 = Observable::UnambiguousPhaseRange(Frequency::DORIS2);
+
+        let toulouse = GroundStation::default()
+            .with_site_label("TLSB")
+            .with_site_name("TOULOUSE")
+            .with_domes_str("10003S005")
+            .unwrap()
+            .with_unique_id(1);
+
+        let grasse = GroundStation::default()
+            .with_site_label("GR4B")
+            .with_domes_str("10002S019")
+            .unwrap()
+            .with_unique_id(2);
+
+        let mut record = synthetic_record(
+            &toulouse,
+            &[
+                (0.0, vec![(l1, 1.0), (l2, 2.0)]),
+                (10.0, vec![(l1, 3.0), (Observable::Temperature, 20.5)]),
+            ],
+        );
+
+        add_synthetic_samples(&mut record, &grasse, &[(10.0, vec![(l2, 4.125)])]);
+
+        let t0 = synthetic_t0();
+
+        let measurements = record.measurements.values_mut().next().unwrap();
+
+        measurements.satellite_clock_offset = Some(ClockOffset::from_extrapolated_offset(
+            Duration::from_seconds(-4.25E-3),
+        ));
+
+        for observation in measurements.observations.values_mut() {
+            *observation = observation
+                .with_snr(SNR::DbHz42_47)
+                .with_phase_flag(PhaseFlag::Discontinuity);
+        }
+
+        record.measurements.insert(
+            Key {
+                epoch: t0 + Duration::from_seconds(5.0),
+                flag: EpochFlag::ExternalEvent,
+            },
+            Default::default(),
+        );
+
+        record.comments.push("record comment".to_string());
+
+        let mut doris = DORIS::default();
+        doris.header.satellite = "CRYOSAT-2".to_string();
+        doris.header.observables = vec![l1, l2];
+        doris.header.ground_stations = vec![toulouse.clone(), grasse.clone()];
+        doris.record = record;
+
+        for layout in [CsvLayout::Long, CsvLayout::Wide] {
+            for epoch_format in [
+                CsvEpochFormat::TAI,
+                CsvEpochFormat::UTC,
+                CsvEpochFormat::GPST,
+            ] {
+                for station in [CsvStation::Code, CsvStation::Label, CsvStation::DOMES] {
+                    let options = CsvOptions::default()
+                        .with_layout(layout)
+                        .with_epoch_format(epoch_format)
+                        .with_station(station);
+
+                    let mut writer = BufWriter::new(Vec::new());
+                    doris.format_csv(&mut writer, &options).unwrap();
+
+                    let content = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+
+                    let parsed =
+                        DORIS::parse_csv(&mut BufReader::new(content.as_bytes()), &doris.header)
+                            .unwrap();
+
+                    assert_eq!(parsed.record, doris.record, "{:?}\n{}", options, content);
+                }
+            }
+        }
+
+        let options = CsvOptions::default()
+            .with_layout(CsvLayout::Wide)
+            .with_epoch_format(CsvEpochFormat::UTC)
+            .with_metadata(false);
+
+        let mut writer = BufWriter::new(Vec::new());
+        doris.format_csv(&mut writer, &options).unwrap();
+
+        let content = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        let mut lines = content.lines();
+
+        assert_eq!(
+            lines.next(),
+            Some("epoch_utc,flag,station_label,L1,L1_snr,L1_phase_flag,L2,L2_snr,L2_phase_flag,T,T_snr,T_phase_flag,clock_offset,clock_extrapolated")
+        );
+
+        assert_eq!(
+            lines.next(),
+            Some("2018-06-12T23:59:23.000000000,0,TLSB,1,7,1,2,7,1,,,,-0.004250000,true")
+        );
+
+        // unknown stations are given a new unique code
+        let parsed =
+            DORIS::parse_csv(&mut BufReader::new(content.as_bytes()), &Default::default()).unwrap();
+
+        let (_, measurements) = parsed.record.measurements.iter().nth(1).unwrap();
+
+        let codes = measurements
+            .observations
+            .keys()
+            .map(|obs_key| (obs_key.station.label.as_str(), obs_key.station.code))
+            .collect::<Vec<_>>();
+
+        assert_eq!(codes, vec![("TLSB", 1), ("TLSB", 1), ("GR4B", 2)]);
+
+        // the header describes the record
+        let stations = parsed
+            .header
+            .ground_stations
+            .iter()
+            .map(|station| (station.label.as_str(), station.code))
+            .collect::<Vec<_>>();
+
+        assert_eq!(stations, vec![("TLSB", 1), ("GR4B", 2)]);
+
+        assert_eq!(parsed.header.observables.len(), 3);
+        assert!(parsed.header.observables.contains(&Observable::Temperature));
+
+        assert_eq!(parsed.header.time_of_first_observation, Some(t0));
+        assert_eq!(
+            parsed.header.time_of_last_observation,
+            Some(t0 + Duration::from_seconds(10.0))
+        );
+
+        // MJD: microsecond precision
+        let options = options.with_epoch_format(CsvEpochFormat::MJD);
+
+        let mut writer = BufWriter::new(Vec::new());
+        doris.format_csv(&mut writer, &options).unwrap();
+
+        let content = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+
+        let parsed =
+            DORIS::parse_csv(&mut BufReader::new(content.as_bytes()), &doris.header).unwrap();
+
+        for (parsed, key) in parsed
+            .record
+            .measurements
+            .keys()
+            .zip(doris.record.measurements.keys())
+        {
+            assert!((parsed.epoch - key.epoch).abs() < Duration::from_microseconds(1.0));
+        }
+    }
+}
@@ -0,0 +1,371 @@
+//! Phase discontinuity (cycle slip like break) detection
+use std::collections::HashMap;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    pass::Pass,
+    prelude::{
+        Duration, Epoch, Frequency, GroundStation, Observable, PhaseFlag, Record, StationHandle,
+    },
+};
+
+/// [PhaseBreak] describes one phase discontinuity, detected
+/// on a [GroundStation] observation.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PhaseBreak {
+    /// [GroundStation] being observed
+    pub station: GroundStation,
+
+    /// [Epoch] of the first observation following the discontinuity
+    pub epoch: Epoch,
+
+    /// [Frequency] affected by this discontinuity.
+    /// None when the break was only visible on the geometry free combination,
+    /// which cannot be attributed to one frequency in particular.
+    pub frequency: Option<Frequency>,
+
+    /// Magnitude of the phase jump, in meters.
+    pub magnitude: f64,
+}
+
+/// [DiscontinuityDetector] searches for phase jumps (following phase resets for example)
+/// in [Observable::UnambiguousPhaseRange] observations, independently for each [GroundStation].
+/// Two tests are performed between successive observations of the same [Pass]:
+/// - the code minus phase rate, on each [Frequency]
+/// - the geometry free (L1 - L2) rate
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DiscontinuityDetector {
+    /// Maximal data gap: a new [Pass] starts after this [Duration] without observations
+    pub max_gap: Duration,
+
+    /// Code minus phase rate threshold, in m/s
+    pub cmc_rate_threshold: f64,
+
+    /// Geometry free phase combination rate threshold, in m/s
+    pub gf_rate_threshold: f64,
+}
+
+impl Default for DiscontinuityDetector {
+    /// Builds a default [DiscontinuityDetector], using 10' maximal
+    /// data gap, 1 m/s code minus phase and 0.1 m/s geometry free thresholds.
+    fn default() -> Self {
+        Self {
+            max_gap: Duration::from_seconds(600.0),
+            cmc_rate_threshold: 1.0,
+            gf_rate_threshold: 0.1,
+        }
+    }
+}
+
+/// Phase and code observations of one [GroundStation] at one [Epoch]
+#[derive(Default)]
+struct Sample {
+    epoch: Epoch,
+    phase: [Option<f64>; 2],
+    code: [Option<f64>; 2],
+}
+
+impl Sample {
+    /// Code minus phase, on given frequency
+    fn code_minus_phase(&self, freq: usize) -> Option<f64> {
+        Some(self.code[freq]? - self.phase[freq]?)
+    }
+
+    /// Geometry free phase combination
+    fn geometry_free(&self) -> Option<f64> {
+        Some(self.phase[0]? - self.phase[1]?)
+    }
+}
+
+const FREQUENCIES: [Frequency; 2] = [Frequency::DORIS1, Frequency::DORIS2];
+
+fn frequency_index(frequency: Frequency) -> usize {
+    match frequency {
+        Frequency::DORIS1 => 0,
+        Frequency::DORIS2 => 1,
+    }
+}
+
+impl DiscontinuityDetector {
+    /// Copies and returns [DiscontinuityDetector] with updated maximal data gap
+    pub fn with_max_gap(&self, max_gap: Duration) -> Self {
+        let mut s = *self;
+        s.max_gap = max_gap;
+        s
+    }
+
+    /// Copies and returns [DiscontinuityDetector] with updated code minus phase
+    /// rate threshold, in m/s.
+    pub fn with_code_minus_phase_threshold(&self, threshold_m_s: f64) -> Self {
+        let mut s = *self;
+        s.cmc_rate_threshold = threshold_m_s;
+        s
+    }
+
+    /// Copies and returns [DiscontinuityDetector] with updated geometry free
+    /// rate threshold, in m/s.
+    pub fn with_geometry_free_threshold(&self, threshold_m_s: f64) -> Self {
+        let mut s = *self;
+        s.gf_rate_threshold = threshold_m_s;
+        s
+    }
+
+    /// Gathers phase and code observations, per [GroundStation], in chronological order
+    fn station_samples(record: &Record) -> HashMap<StationHandle, Vec<Sample>> {
+        let mut samples = HashMap::<StationHandle, Vec<Sample>>::new();
+
+        for (key, measurements) in record.measurements.iter() {
+            for (obs_key, observation) in measurements.observations.iter() {
+                let (is_phase, freq) = match obs_key.observable {
+                    Observable::UnambiguousPhaseRange(freq) => (true, frequency_index(freq)),
+                    Observable::PseudoRange(freq) => (false, frequency_index(freq)),
+                    _ => continue,
+                };
+
+                let station_samples = samples.entry(obs_key.station.clone()).or_default();
+
+                if station_samples.last().map(|s| s.epoch) != Some(key.epoch) {
+                    station_samples.push(Sample {
+                        epoch: key.epoch,
+                        ..Default::default()
+                    });
+                }
+
+                if let Some(sample) = station_samples.last_mut() {
+                    if is_phase {
+                        sample.phase[freq] = Some(observation.value);
+                    } else {
+                        sample.code[freq] = Some(observation.value);
+                    }
+                }
+            }
+        }
+
+        samples
+    }
+
+    /// Searches for phase discontinuities in this [Record].
+    /// Returns the list of [PhaseBreak]s in chronological order.
+    pub fn detect(&self, record: &Record) -> Vec<PhaseBreak> {
+        let mut breaks = Vec::new();
+
+        for (station, samples) in Self::station_samples(record) {
+            for (prev, sample) in samples.iter().zip(samples.iter().skip(1)) {
+                let dt = sample.epoch - prev.epoch;
+
+                if dt > self.max_gap {
+                    // new pass: nothing to compare to
+                    continue;
+                }
+
+                let dt = dt.to_seconds();
+                let mut detected = false;
+
+                for (freq, frequency) in FREQUENCIES.iter().enumerate() {
+                    if let (Some(prev_cmc), Some(cmc)) =
+                        (prev.code_minus_phase(freq), sample.code_minus_phase(freq))
+                    {
+                        // phase jump appears with opposite sign in code minus phase
+                        let jump = prev_cmc - cmc;
+
+                        if jump.abs() / dt > self.cmc_rate_threshold {
+                            detected = true;
+
+                            breaks.push(PhaseBreak {
+                                station: station.station().clone(),
+                                epoch: sample.epoch,
+                                frequency: Some(*frequency),
+                                magnitude: jump,
+                            });
+                        }
+                    }
+                }
+
+                if detected {
+                    continue;
+                }
+
+                if let (Some(prev_gf), Some(gf)) = (prev.geometry_free(), sample.geometry_free()) {
+                    let jump = gf - prev_gf;
+
+                    if jump.abs() / dt > self.gf_rate_threshold {
+                        breaks.push(PhaseBreak {
+                            station: station.station().clone(),
+                            epoch: sample.epoch,
+                            frequency: None,
+                            magnitude: jump,
+                        });
+                    }
+                }
+            }
+        }
+
+        breaks.sort_by(|a, b| (a.epoch, &a.station).cmp(&(b.epoch, &b.station)));
+        breaks
+    }
+
+    /// Searches for phase discontinuities in this [Record] and marks
+    /// the affected [Observable::UnambiguousPhaseRange] observations
+    /// with [PhaseFlag::Discontinuity]. Breaks that cannot be attributed
+    /// to one [Frequency] mark both phase observations.
+    /// Returns the list of [PhaseBreak]s in chronological order.
+    pub fn mark(&self, record: &mut Record) -> Vec<PhaseBreak> {
+        let breaks = self.detect(record);
+
+        let mut by_epoch = HashMap::<Epoch, Vec<&PhaseBreak>>::new();
+
+        for phase_break in breaks.iter() {
+            by_epoch
+                .entry(phase_break.epoch)
+                .or_default()
+                .push(phase_break);
+        }
+
+        for (key, measurements) in record.measurements.iter_mut() {
+            let epoch_breaks = match by_epoch.get(&key.epoch) {
+                Some(epoch_breaks) => epoch_breaks,
+                None => continue,
+            };
+
+            for (obs_key, observation) in measurements.observations.iter_mut() {
+                let frequency = match obs_key.observable {
+                    Observable::UnambiguousPhaseRange(freq) => freq,
+                    _ => continue,
+                };
+
+                let affected = epoch_breaks.iter().any(|phase_break| {
+                    phase_break.station == *obs_key.station
+                        && phase_break.frequency.unwrap_or(frequency) == frequency
+                });
+
+                if affected {
+                    observation.phase_flag = Some(PhaseFlag::Discontinuity);
+                }
+            }
+        }
+
+        breaks
+    }
+
+    /// Segments this [Record] into [GroundStation] [Pass]es,
+    /// and splits each [Pass] at every detected phase discontinuity.
+    pub fn split_passes(&self, record: &Record) -> Vec<Pass> {
+        let breaks = self.detect(record);
+
+        let mut passes = Vec::new();
+
+        for pass in record.station_passes(self.max_gap) {
+            let mut pass = pass;
+
+            for phase_break in breaks.iter() {
+                if phase_break.station != pass.station {
+                    continue;
+                }
+
+                if let Some((first, second)) = pass.split_at(record, phase_break.epoch) {
+                    passes.push(first);
+                    pass = second;
+                }
+            }
+
+            passes.push(pass);
+        }
+
+        passes.sort_by(|a, b| (a.start, &a.station).cmp(&(b.start, &b.station)));
+        passes
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tests::toolkit::{synthetic_record, synthetic_t0};
+
+    fn phase_jump_record(jump: f64) -> (GroundStation, Record) {
+        let station = GroundStation::default()
+            .with_site_label("TLSB")
+            .with_unique_id(13);
+
+        let l1 = Observable::UnambiguousPhaseRange(Frequency::DORIS1);
+        let l2 = Observable::UnambiguousPhaseRange(Frequency::DORIS2);
+        let c1 = Observable::PseudoRange(Frequency::DORIS1);
+        let c2 = Observable::PseudoRange(Frequency::DORIS2);
+
+        // smooth geometry with L1 jump @ t=50s
+        let samples = (0..10)
+            .map(|i| {
+                let t = i as f64 * 10.0;
+                let range = 1.0E6 + 5.0E3 * t;
+                let l1_jump = if t >= 50.0 { jump } else { 0.0 };
+
+                (
+                    t,
+                    vec![
+                        (l1, range + l1_jump),
+                        (l2, range + 1.0),
+                        (c1, range),
+                        (c2, range + 1.0),
+                    ],
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let record = synthetic_record(&station, &samples);
+        (station, record)
+    }
+
+    #[test]
+    fn phase_break_detection() {
+        let detector = DiscontinuityDetector::default();
+
+        let (_, record) = phase_jump_record(0.0);
+        assert!(detector.detect(&record).is_empty());
+
+        let (station, mut record) = phase_jump_record(25.0);
+        let breaks = detector.detect(&record);
+
+        assert_eq!(
+            breaks,
+            vec![PhaseBreak {
+                station: station.clone(),
+                epoch: synthetic_t0() + Duration::from_seconds(50.0),
+                frequency: Some(Frequency::DORIS1),
+                magnitude: 25.0,
+            }]
+        );
+
+        let passes = detector.split_passes(&record);
+        assert_eq!(passes.len(), 2);
+        assert_eq!(passes[1].start, breaks[0].epoch);
+        assert_eq!(
+            passes[0].end,
+            breaks[0].epoch - Duration::from_seconds(10.0)
+        );
+        assert!(!passes[0].contains(breaks[0].epoch));
+
+        detector.mark(&mut record);
+
+        let flagged = record
+            .measurements
+            .iter()
+            .flat_map(|(k, m)| {
+                m.observations
+                    .iter()
+                    .filter(|(_, obs)| obs.phase_flag == Some(PhaseFlag::Discontinuity))
+                    .map(move |(obs_k, _)| (k.epoch, obs_k.observable))
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            flagged,
+            vec![(
+                breaks[0].epoch,
+                Observable::UnambiguousPhaseRange(Frequency::DORIS1)
+            )]
+        );
+    }
+}
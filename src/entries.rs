@@ -0,0 +1,83 @@
+//! Serde representations of the maps, so they can be described
+//! by formats that only support string keys (like JSON).
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+};
+
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::prelude::Observable;
+
+/// One map entry, described by the fields of its key and value
+#[derive(Serialize, Deserialize)]
+pub(crate) struct Entry<K, V> {
+    #[serde(flatten)]
+    pub key: K,
+
+    #[serde(flatten)]
+    pub value: V,
+}
+
+/// Describes a [BTreeMap] as a sequence of entries, each entry merging the fields
+/// of its key and value (which must be structures).
+pub(crate) mod flattened {
+    use super::*;
+
+    pub fn serialize<S, K, V>(map: &BTreeMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        K: Serialize,
+        V: Serialize,
+    {
+        serializer.collect_seq(map.iter().map(|(key, value)| Entry { key, value }))
+    }
+
+    pub fn deserialize<'de, D, K, V>(deserializer: D) -> Result<BTreeMap<K, V>, D::Error>
+    where
+        D: Deserializer<'de>,
+        K: Deserialize<'de> + Ord,
+        V: Deserialize<'de>,
+    {
+        let entries = Vec::<Entry<K, V>>::deserialize(deserializer)?;
+
+        Ok(entries
+            .into_iter()
+            .map(|entry| (entry.key, entry.value))
+            .collect())
+    }
+}
+
+/// Describes a [HashMap] indexed by [Observable], as a map indexed by RINEX code,
+/// in alphabetical order.
+pub(crate) mod observables {
+    use super::*;
+
+    pub fn serialize<S, V>(map: &HashMap<Observable, V>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        V: Serialize,
+    {
+        let map = map
+            .iter()
+            .map(|(observable, value)| (format!("{:x}", observable), value))
+            .collect::<BTreeMap<_, _>>();
+
+        serializer.collect_map(map)
+    }
+
+    pub fn deserialize<'de, D, V>(deserializer: D) -> Result<HashMap<Observable, V>, D::Error>
+    where
+        D: Deserializer<'de>,
+        V: Deserialize<'de>,
+    {
+        let map = BTreeMap::<String, V>::deserialize(deserializer)?;
+
+        map.into_iter()
+            .map(|(code, value)| {
+                let observable = Observable::from_str(&code).map_err(D::Error::custom)?;
+                Ok((observable, value))
+            })
+            .collect()
+    }
+}
@@ -6,6 +6,23 @@ use hifitime::{HifitimeError, ParsingError as HifitimeParsingError};
 
 use std::io::Error as IoError;
 
+#[cfg(feature = "arrow")]
+use arrow::error::ArrowError;
+
+#[cfg(feature = "arrow")]
+use parquet::errors::ParquetError;
+
+#[cfg(feature = "polars")]
+use polars::error::PolarsError;
+
+#[cfg(feature = "json")]
+use serde_json::Error as JsonError;
+
+#[cfg(feature = "sqlite")]
+use rusqlite::Error as SqliteError;
+
+use crate::prelude::Version;
+
 /// Errors that may rise when parsing DORIS files
 #[derive(Debug, Error)]
 pub enum ParsingError {
@@ -68,6 +85,68 @@ pub enum ParsingError {
 
     #[error("invalid station format")]
     StationFormat,
+
+    #[error("invalid SINEX content")]
+    SinexFormat,
+
+    #[error("invalid SP3 content")]
+    SP3Format,
+
+    #[error("invalid TDM content")]
+    TdmFormat,
+
+    #[error("unsupported TDM data type")]
+    TdmDataType,
+
+    #[error("invalid DORIS 2.2 record")]
+    LegacyFormat,
+
+    #[error("invalid CSV content")]
+    CsvFormat,
+
+    #[cfg(feature = "serde")]
+    #[error("inconsistent columnar record")]
+    ColumnarRecord,
+
+    #[cfg(feature = "arrow")]
+    #[error("arrow error: {0}")]
+    Arrow(#[from] ArrowError),
+
+    #[cfg(feature = "arrow")]
+    #[error("parquet error: {0}")]
+    Parquet(#[from] ParquetError),
+
+    #[cfg(feature = "arrow")]
+    #[error("invalid arrow schema")]
+    ArrowSchema,
+
+    #[cfg(feature = "polars")]
+    #[error("polars error: {0}")]
+    Polars(#[from] PolarsError),
+
+    #[cfg(feature = "polars")]
+    #[error("invalid dataframe layout")]
+    DataFrameLayout,
+
+    #[cfg(feature = "json")]
+    #[error("json error: {0}")]
+    Json(#[from] JsonError),
+
+    #[cfg(feature = "json")]
+    #[error("invalid or unsupported JSON document")]
+    JsonSchema,
+
+    #[cfg(feature = "sqlite")]
+    #[error("sqlite error: {0}")]
+    Sqlite(#[from] SqliteError),
+
+    #[cfg(feature = "sqlite")]
+    #[error("invalid sqlite archive")]
+    SqliteSchema,
+
+    #[cfg(feature = "sqlite")]
+    #[error("sqlite query matches several satellites")]
+    SqliteSatellites,
 }
 
 /// Errors that may rise when formatting DORIS files
@@ -75,4 +154,35 @@ pub enum ParsingError {
 pub enum FormattingError {
     #[error("i/o: output error")]
     OutputError(#[from] IoError),
+
+    #[error("unsupported RINEX revision {0}")]
+    RinexRevision(Version),
+
+    #[cfg(feature = "arrow")]
+    #[error("arrow error: {0}")]
+    Arrow(#[from] ArrowError),
+
+    #[cfg(feature = "arrow")]
+    #[error("parquet error: {0}")]
+    Parquet(#[from] ParquetError),
+
+    #[cfg(feature = "polars")]
+    #[error("polars error: {0}")]
+    Polars(#[from] PolarsError),
+
+    #[cfg(feature = "json")]
+    #[error("json error: {0}")]
+    Json(#[from] JsonError),
+
+    #[cfg(feature = "json")]
+    #[error("distinct ground stations share code {0}")]
+    StationCode(u16),
+
+    #[cfg(feature = "sqlite")]
+    #[error("sqlite error: {0}")]
+    Sqlite(#[from] SqliteError),
+
+    #[cfg(feature = "sqlite")]
+    #[error("missing production attributes")]
+    MissingProductionAttributes,
 }
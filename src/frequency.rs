@@ -1,7 +1,7 @@
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use crate::error::ParsingError;
+use crate::{constants::USO_FREQ_HZ, error::ParsingError};
 
 #[derive(Debug, Copy, Default, Clone, PartialEq, PartialOrd, Hash, Ord, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -47,11 +47,12 @@ impl std::fmt::Display for Frequency {
 }
 
 impl Frequency {
-    /// Returns frequency value in Hertz
+    /// Returns nominal frequency value in Hertz
+    /// (2036.25 MHz for DORIS #1 and 401.25 MHz for DORIS #2).
     pub fn frequency_hz(&self) -> f64 {
         match self {
-            Self::DORIS1 => 1.0,
-            Self::DORIS2 => 2.0,
+            Self::DORIS1 => 543.0 * USO_FREQ_HZ * 3.0 / 4.0,
+            Self::DORIS2 => 107.0 * USO_FREQ_HZ * 3.0 / 4.0,
         }
     }
 }
@@ -71,4 +72,16 @@ mod test {
             assert_eq!(freq, expected, "wrong value for {}", value);
         }
     }
+
+    #[test]
+    fn nominal_frequencies() {
+        assert_eq!(Frequency::DORIS1.frequency_hz(), 2036.25E6);
+        assert_eq!(Frequency::DORIS2.frequency_hz(), 401.25E6);
+
+        // 5 MHz USO: 543 / 107 carrier ratio
+        assert_eq!(
+            Frequency::DORIS1.frequency_hz() / Frequency::DORIS2.frequency_hz(),
+            543.0 / 107.0
+        );
+    }
 }
@@ -135,6 +135,19 @@ impl Header {
         Ok(())
     }
 
+    /// Formats [Header] as DORIS RINEX header section
+    #[cfg(any(feature = "arrow", feature = "sqlite"))]
+    pub(crate) fn format_string(&self) -> Result<String, FormattingError> {
+        let mut writer = BufWriter::new(Vec::new());
+        self.format(&mut writer)?;
+
+        let content = writer
+            .into_inner()
+            .map_err(|e| FormattingError::OutputError(e.into_error()))?;
+
+        Ok(String::from_utf8_lossy(&content).to_string())
+    }
+
     /// Formats all comments
     fn format_comments<W: Write>(&self, w: &mut BufWriter<W>) -> Result<(), FormattingError> {
         for comment in self.comments.iter() {
@@ -9,13 +9,10 @@ use itertools::Itertools;
 use std::collections::HashMap;
 
 use crate::{
-    prelude::{Duration, Epoch, GroundStation, Observable, COSPAR},
+    prelude::{Duration, Epoch, GroundStation, Observable, Record, COSPAR},
     Comments,
 };
 
-#[cfg(doc)]
-use crate::prelude::Record;
-
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
@@ -73,6 +70,7 @@ pub struct Header {
     pub observables: Vec<Observable>,
 
     /// Possible scalings to apply to attached [Observable]s
+    #[cfg_attr(feature = "serde", serde(with = "crate::entries::observables"))]
     pub scaling_factors: HashMap<Observable, f64>,
 
     /// DORIS [GroundStation]s
@@ -96,6 +94,46 @@ impl Header {
             .cloned()
     }
 
+    /// Describes the content of this [Record] in [Header] (in place):
+    /// [GroundStation]s and [Observable]s that are not described yet are appended
+    /// (in code and alphabetical order), and the time of first and last observation
+    /// are updated. This is required to format the [Record] as DORIS RINEX.
+    pub fn describe_record(&mut self, record: &Record) {
+        let stations = record
+            .measurements
+            .values()
+            .flat_map(|measurements| measurements.observations.keys())
+            .map(|obs_key| &obs_key.station)
+            .unique()
+            .filter(|station| !self.ground_stations.contains(station))
+            .sorted()
+            .map(|station| station.station().clone())
+            .collect::<Vec<_>>();
+
+        self.ground_stations.extend(stations);
+
+        let observables = record
+            .measurements
+            .values()
+            .flat_map(|measurements| measurements.observables())
+            .unique()
+            .filter(|observable| !self.observables.contains(observable))
+            .sorted()
+            .collect::<Vec<_>>();
+
+        self.observables.extend(observables);
+
+        let epochs = record.measurements.keys().map(|key| key.epoch);
+
+        if let Some(first) = epochs.clone().min() {
+            self.time_of_first_observation = Some(first);
+        }
+
+        if let Some(last) = epochs.max() {
+            self.time_of_last_observation = Some(last);
+        }
+    }
+
     /// Formats the package version (possibly shortenned, in case of lengthy release)
     /// to fit within a formatted COMMENT
     pub(crate) fn format_pkg_version(version: &str) -> String {
@@ -0,0 +1,684 @@
+//! JSON and JSON Lines support (requires the `json` feature).
+//!
+//! [DORIS] files are described by a versioned JSON document (see [JSON_SCHEMA_VERSION]),
+//! which does not depend on the internal representation of this library:
+//! - [Observable]s are described by their standard RINEX code (`"L1"`, `"C2"`, `"P"`..)
+//! - [SNR], [EpochFlag] and [PhaseFlag] are described by their standard RINEX value
+//! - [Duration]s are described in seconds
+//! - [Epoch]s are described as strings, in their time scale (`"2018-06-13T00:00:00 TAI"`)
+//! - [GroundStation]s are described once, in the `stations` table, and referenced
+//!   by their unique code everywhere else
+//!
+//! ```json
+//! {
+//!   "schema_version": 1,
+//!   "header": {
+//!     "version": { "major": 3, "minor": 0 },
+//!     "satellite": "CRYOSAT-2",
+//!     "cospar": "2010-013A",
+//!     "observables": ["L1", "L2", "C1", "C2", "W1", "W2", "F", "P", "T", "H"],
+//!     "scaling_factors": { "L1": 100.0 },
+//!     "l1_l2_date_offset": 0.0,
+//!     "ground_stations": [1],
+//!     "time_of_first_observation": "2018-06-13T00:00:28.999446333 TAI",
+//!     ...
+//!   },
+//!   "stations": [
+//!     {
+//!       "code": 1,
+//!       "label": "TLSB",
+//!       "site": "TOULOUSE",
+//!       "domes": "10003S005",
+//!       "beacon_revision": 3,
+//!       "k_frequency_shift": 0,
+//!       "coordinates": null
+//!     }
+//!   ],
+//!   "production": { "satellite": "CS2RX", "year": 2018, "doy": 164, "gzip_compressed": false },
+//!   "comments": [],
+//!   "epochs": [
+//!     {
+//!       "epoch": "2018-06-13T00:00:28.999446333 TAI",
+//!       "flag": 0,
+//!       "clock_offset": { "offset": -4.326631626, "extrapolated": false },
+//!       "observations": [
+//!         { "station": 1, "observable": "L1", "value": -2986.9, "snr": 7, "phase_flag": null }
+//!       ]
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! The JSON Lines representation (see [DORIS::format_json_lines]) describes the same
+//! document: the first line contains every field but `epochs`, and each following line
+//! contains one epoch object, in chronological order.
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Read, Write},
+    path::Path,
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    prelude::{
+        Antenna, ClockOffset, Epoch, FormattingError, GroundStation, Header, Key, Measurements,
+        Observable, Observation, ObservationKey, ParsingError, ProductionAttributes, Receiver,
+        StationCoordinates, StationHandle, Version, COSPAR, DORIS, SNR,
+    },
+    record::columns::{
+        duration_from_seconds, epoch_flag, epoch_flag_value, phase_flag, phase_flag_value,
+        snr_value,
+    },
+};
+
+#[cfg(doc)]
+use crate::prelude::{EpochFlag, PhaseFlag};
+
+/// Revision of the JSON document described in this module.
+/// It is increased on every incompatible modification of the document.
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+/// [DORIS] JSON document
+#[derive(Serialize, Deserialize)]
+struct DocumentJson {
+    schema_version: u32,
+    header: HeaderJson,
+    stations: Vec<StationJson>,
+    production: Option<ProductionJson>,
+    comments: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    epochs: Vec<EpochJson>,
+}
+
+/// [Header] JSON object
+#[derive(Serialize, Deserialize)]
+struct HeaderJson {
+    version: Version,
+    comments: Vec<String>,
+    satellite: String,
+    program: Option<String>,
+    run_by: Option<String>,
+    date: Option<String>,
+    observer: Option<String>,
+    agency: Option<String>,
+    cospar: Option<String>,
+    receiver: Option<Receiver>,
+    antenna: Option<Antenna>,
+    license: Option<String>,
+    doi: Option<String>,
+    l1_l2_date_offset: f64,
+    observables: Vec<String>,
+    scaling_factors: BTreeMap<String, f64>,
+    ground_stations: Vec<u16>,
+    time_of_first_observation: Option<String>,
+    time_of_last_observation: Option<String>,
+}
+
+/// [GroundStation] JSON object
+#[derive(Serialize, Deserialize)]
+struct StationJson {
+    code: u16,
+    label: String,
+    site: String,
+    domes: String,
+    beacon_revision: u8,
+    k_frequency_shift: i8,
+    coordinates: Option<CoordinatesJson>,
+}
+
+/// [StationCoordinates] JSON object
+#[derive(Serialize, Deserialize)]
+struct CoordinatesJson {
+    epoch: String,
+    position_m: [f64; 3],
+    velocity_m_yr: [f64; 3],
+}
+
+/// [ProductionAttributes] JSON object
+#[derive(Serialize, Deserialize)]
+struct ProductionJson {
+    satellite: String,
+    year: u32,
+    doy: u32,
+    #[serde(default)]
+    gzip_compressed: bool,
+}
+
+/// Epoch JSON object: [Key] and [Measurements]
+#[derive(Serialize, Deserialize)]
+struct EpochJson {
+    epoch: String,
+    flag: u8,
+    clock_offset: Option<ClockOffsetJson>,
+    observations: Vec<ObservationJson>,
+}
+
+/// [ClockOffset] JSON object
+#[derive(Serialize, Deserialize)]
+struct ClockOffsetJson {
+    offset: f64,
+    extrapolated: bool,
+}
+
+/// [Observation] JSON object
+#[derive(Serialize, Deserialize)]
+struct ObservationJson {
+    station: u16,
+    observable: String,
+    value: f64,
+    snr: Option<u8>,
+    phase_flag: Option<u8>,
+}
+
+fn parse_epoch(content: &str) -> Result<Epoch, ParsingError> {
+    Ok(Epoch::from_str(content)?)
+}
+
+impl From<&StationHandle> for StationJson {
+    fn from(station: &StationHandle) -> Self {
+        Self {
+            code: station.code,
+            label: station.label.clone(),
+            site: station.site.clone(),
+            domes: station.domes.to_string(),
+            beacon_revision: station.beacon_revision,
+            k_frequency_shift: station.k_frequency_shift,
+            coordinates: station.coordinates.map(|coordinates| CoordinatesJson {
+                epoch: coordinates.epoch.to_string(),
+                position_m: coordinates.position_m.into(),
+                velocity_m_yr: coordinates.velocity_m_yr.into(),
+            }),
+        }
+    }
+}
+
+impl StationJson {
+    fn to_station(&self) -> Result<GroundStation, ParsingError> {
+        let mut station = GroundStation::default()
+            .with_unique_id(self.code)
+            .with_site_label(&self.label)
+            .with_site_name(&self.site)
+            .with_domes_str(&self.domes)?
+            .with_beacon_revision(self.beacon_revision)
+            .with_frequency_shift(self.k_frequency_shift);
+
+        if let Some(coordinates) = &self.coordinates {
+            station = station.with_coordinates(StationCoordinates {
+                epoch: parse_epoch(&coordinates.epoch)?,
+                position_m: coordinates.position_m.into(),
+                velocity_m_yr: coordinates.velocity_m_yr.into(),
+            });
+        }
+
+        Ok(station)
+    }
+}
+
+impl From<&ProductionAttributes> for ProductionJson {
+    fn from(production: &ProductionAttributes) -> Self {
+        Self {
+            satellite: production.satellite.clone(),
+            year: production.year,
+            doy: production.doy,
+            #[cfg(feature = "flate2")]
+            gzip_compressed: production.gzip_compressed,
+            #[cfg(not(feature = "flate2"))]
+            gzip_compressed: false,
+        }
+    }
+}
+
+impl From<&ProductionJson> for ProductionAttributes {
+    fn from(production: &ProductionJson) -> Self {
+        Self {
+            satellite: production.satellite.clone(),
+            year: production.year,
+            doy: production.doy,
+            #[cfg(feature = "flate2")]
+            gzip_compressed: production.gzip_compressed,
+        }
+    }
+}
+
+impl From<&Header> for HeaderJson {
+    fn from(header: &Header) -> Self {
+        Self {
+            version: header.version,
+            comments: header.comments.clone(),
+            satellite: header.satellite.clone(),
+            program: header.program.clone(),
+            run_by: header.run_by.clone(),
+            date: header.date.clone(),
+            observer: header.observer.clone(),
+            agency: header.agency.clone(),
+            cospar: header.cospar.as_ref().map(|cospar| cospar.to_string()),
+            receiver: header.receiver.clone(),
+            antenna: header.antenna.clone(),
+            license: header.license.clone(),
+            doi: header.doi.clone(),
+            l1_l2_date_offset: header.l1_l2_date_offset.to_seconds(),
+            observables: header
+                .observables
+                .iter()
+                .map(|observable| format!("{:x}", observable))
+                .collect(),
+            scaling_factors: header
+                .scaling_factors
+                .iter()
+                .map(|(observable, factor)| (format!("{:x}", observable), *factor))
+                .collect(),
+            ground_stations: header
+                .ground_stations
+                .iter()
+                .map(|station| station.code)
+                .collect(),
+            time_of_first_observation: header.time_of_first_observation.map(|t| t.to_string()),
+            time_of_last_observation: header.time_of_last_observation.map(|t| t.to_string()),
+        }
+    }
+}
+
+impl HeaderJson {
+    fn to_header(&self, stations: &HashMap<u16, StationHandle>) -> Result<Header, ParsingError> {
+        let mut header = Header {
+            version: self.version,
+            comments: self.comments.clone(),
+            satellite: self.satellite.clone(),
+            program: self.program.clone(),
+            run_by: self.run_by.clone(),
+            date: self.date.clone(),
+            observer: self.observer.clone(),
+            agency: self.agency.clone(),
+            receiver: self.receiver.clone(),
+            antenna: self.antenna.clone(),
+            license: self.license.clone(),
+            doi: self.doi.clone(),
+            l1_l2_date_offset: duration_from_seconds(self.l1_l2_date_offset),
+            ..Default::default()
+        };
+
+        if let Some(cospar) = &self.cospar {
+            header.cospar = Some(COSPAR::from_str(cospar)?);
+        }
+
+        for observable in self.observables.iter() {
+            header.observables.push(Observable::from_str(observable)?);
+        }
+
+        for (observable, factor) in self.scaling_factors.iter() {
+            header
+                .scaling_factors
+                .insert(Observable::from_str(observable)?, *factor);
+        }
+
+        for code in self.ground_stations.iter() {
+            let station = stations.get(code).ok_or(ParsingError::JsonSchema)?;
+            header.ground_stations.push(station.station().clone());
+        }
+
+        if let Some(t) = &self.time_of_first_observation {
+            header.time_of_first_observation = Some(parse_epoch(t)?);
+        }
+
+        if let Some(t) = &self.time_of_last_observation {
+            header.time_of_last_observation = Some(parse_epoch(t)?);
+        }
+
+        Ok(header)
+    }
+}
+
+impl EpochJson {
+    fn new(key: &Key, measurements: &Measurements) -> Self {
+        Self {
+            epoch: key.epoch.to_string(),
+            flag: epoch_flag_value(key.flag),
+            clock_offset: measurements
+                .satellite_clock_offset
+                .map(|clock_offset| ClockOffsetJson {
+                    offset: clock_offset.offset.to_seconds(),
+                    extrapolated: clock_offset.extrapolated,
+                }),
+            observations: measurements
+                .observations
+                .iter()
+                .map(|(obs_key, observation)| ObservationJson {
+                    station: obs_key.station.code,
+                    observable: format!("{:x}", obs_key.observable),
+                    value: observation.value,
+                    snr: observation.snr.map(snr_value),
+                    phase_flag: observation.phase_flag.map(phase_flag_value),
+                })
+                .collect(),
+        }
+    }
+
+    fn to_entry(
+        &self,
+        stations: &HashMap<u16, StationHandle>,
+    ) -> Result<(Key, Measurements), ParsingError> {
+        let key = Key {
+            epoch: parse_epoch(&self.epoch)?,
+            flag: epoch_flag(self.flag)?,
+        };
+
+        let mut measurements = Measurements::default();
+
+        if let Some(clock_offset) = &self.clock_offset {
+            let offset = duration_from_seconds(clock_offset.offset);
+
+            measurements.satellite_clock_offset = Some(if clock_offset.extrapolated {
+                ClockOffset::from_extrapolated_offset(offset)
+            } else {
+                ClockOffset::from_measured_offset(offset)
+            });
+        }
+
+        for observation in self.observations.iter() {
+            let station = stations
+                .get(&observation.station)
+                .ok_or(ParsingError::JsonSchema)?;
+
+            let mut value = Observation::default().with_value(observation.value);
+
+            if let Some(snr) = observation.snr {
+                value = value.with_snr(SNR::from(snr));
+            }
+
+            if let Some(flag) = observation.phase_flag {
+                value = value.with_phase_flag(phase_flag(flag)?);
+            }
+
+            measurements.observations.insert(
+                ObservationKey {
+                    station: station.clone(),
+                    observable: Observable::from_str(&observation.observable)?,
+                },
+                value,
+            );
+        }
+
+        Ok((key, measurements))
+    }
+}
+
+impl DocumentJson {
+    /// Describes this [DORIS] file, without the epoch objects
+    fn new(doris: &DORIS) -> Result<Self, FormattingError> {
+        let mut stations = BTreeMap::<u16, StationHandle>::new();
+
+        let record_stations = doris
+            .record
+            .measurements
+            .values()
+            .flat_map(|measurements| measurements.observations.keys())
+            .map(|obs_key| obs_key.station.clone());
+
+        let header_stations = doris.header.ground_stations.iter().map(StationHandle::from);
+
+        for station in header_stations.chain(record_stations) {
+            match stations.get(&station.code) {
+                Some(known) if known != &station => {
+                    return Err(FormattingError::StationCode(station.code));
+                },
+                Some(_) => {},
+                None => {
+                    stations.insert(station.code, station);
+                },
+            }
+        }
+
+        Ok(Self {
+            schema_version: JSON_SCHEMA_VERSION,
+            header: HeaderJson::from(&doris.header),
+            stations: stations.values().map(StationJson::from).collect(),
+            production: doris.production.as_ref().map(ProductionJson::from),
+            comments: doris.record.comments.clone(),
+            epochs: Vec::new(),
+        })
+    }
+
+    /// Builds the [DORIS] file described by this document (and possible epoch objects)
+    fn to_doris(&self) -> Result<(DORIS, HashMap<u16, StationHandle>), ParsingError> {
+        if self.schema_version != JSON_SCHEMA_VERSION {
+            return Err(ParsingError::JsonSchema);
+        }
+
+        let mut stations = HashMap::<u16, StationHandle>::new();
+
+        for station in self.stations.iter() {
+            let handle = StationHandle::from(station.to_station()?);
+
+            if stations.insert(station.code, handle).is_some() {
+                return Err(ParsingError::JsonSchema);
+            }
+        }
+
+        let mut doris = DORIS {
+            header: self.header.to_header(&stations)?,
+            production: self.production.as_ref().map(ProductionAttributes::from),
+            ..Default::default()
+        };
+
+        doris.record.comments = self.comments.clone();
+
+        for epoch in self.epochs.iter() {
+            let (key, measurements) = epoch.to_entry(&stations)?;
+            doris.record.measurements.insert(key, measurements);
+        }
+
+        Ok((doris, stations))
+    }
+}
+
+impl DORIS {
+    /// Formats this [DORIS] structure as JSON, into [Write]able interface.
+    /// See the [module documentation](crate::json) for the document layout.
+    pub fn format_json<W: Write>(&self, w: &mut BufWriter<W>) -> Result<(), FormattingError> {
+        let mut document = DocumentJson::new(self)?;
+
+        document.epochs = self
+            .record
+            .measurements
+            .iter()
+            .map(|(key, measurements)| EpochJson::new(key, measurements))
+            .collect();
+
+        serde_json::to_writer(&mut *w, &document)?;
+        w.flush()?;
+        Ok(())
+    }
+
+    /// Dumps this [DORIS] structure into local JSON file.
+    pub fn to_json_file<P: AsRef<Path>>(&self, path: P) -> Result<(), FormattingError> {
+        let fd = File::create(path)?;
+        let mut writer = BufWriter::new(fd);
+        self.format_json(&mut writer)
+    }
+
+    /// Parses [DORIS] from JSON content, from [Read]able interface.
+    pub fn parse_json<R: Read>(reader: &mut BufReader<R>) -> Result<Self, ParsingError> {
+        let document = serde_json::from_reader::<_, DocumentJson>(reader)?;
+        let (doris, _) = document.to_doris()?;
+        Ok(doris)
+    }
+
+    /// Parses [DORIS] from local JSON file.
+    pub fn from_json_file<P: AsRef<Path>>(path: P) -> Result<Self, ParsingError> {
+        let fd = File::open(path)?;
+        let mut reader = BufReader::new(fd);
+        Self::parse_json(&mut reader)
+    }
+
+    /// Formats this [DORIS] structure as JSON Lines, into [Write]able interface:
+    /// the first line describes the [Header] and the station table,
+    /// each following line describes one epoch, in chronological order.
+    pub fn format_json_lines<W: Write>(&self, w: &mut BufWriter<W>) -> Result<(), FormattingError> {
+        serde_json::to_writer(&mut *w, &DocumentJson::new(self)?)?;
+        writeln!(w)?;
+
+        for (key, measurements) in self.record.measurements.iter() {
+            serde_json::to_writer(&mut *w, &EpochJson::new(key, measurements))?;
+            writeln!(w)?;
+        }
+
+        w.flush()?;
+        Ok(())
+    }
+
+    /// Parses [DORIS] from JSON Lines, from [Read]able interface.
+    /// See [Self::format_json_lines]. Empty lines are ignored.
+    pub fn parse_json_lines<R: Read>(reader: &mut BufReader<R>) -> Result<Self, ParsingError> {
+        let mut lines = reader.lines();
+
+        let document = loop {
+            match lines.next() {
+                Some(line) => {
+                    let line = line?;
+
+                    if !line.trim().is_empty() {
+                        break serde_json::from_str::<DocumentJson>(&line)?;
+                    }
+                },
+                None => return Err(ParsingError::JsonSchema),
+            }
+        };
+
+        let (mut doris, stations) = document.to_doris()?;
+
+        for line in lines {
+            let line = line?;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let epoch = serde_json::from_str::<EpochJson>(&line)?;
+            let (key, measurements) = epoch.to_entry(&stations)?;
+            doris.record.measurements.insert(key, measurements);
+        }
+
+        Ok(doris)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prelude::{
+        ClockOffset, Duration, EpochFlag, Frequency, GroundStation, Key, Observable, PhaseFlag,
+        DORIS, SNR,
+    };
+    use crate::tests::toolkit::{synthetic_record, synthetic_t0};
+
+    use std::io::{BufReader, BufWriter};
+
+    #[test]
+    fn json_round_trip() {
+        let l1 = Observable::UnambiguousPhaseRange(Frequency::DORIS1);
+
+        let toulouse = GroundStation::default()
+            .with_site_label("TLSB")
+            .with_site_name("TOULOUSE")
+            .with_domes_str("10003S005")
+            .unwrap()
+            .with_unique_id(1);
+
+        let mut record = synthetic_record(
+            &toulouse,
+            &[(0.0, vec![(l1, 1.5), (Observable::Temperature, 20.5)])],
+        );
+
+        let measurements = record.measurements.values_mut().next().unwrap();
+
+        measurements.satellite_clock_offset = Some(ClockOffset::from_extrapolated_offset(
+            Duration::from_seconds(-4.25E-3),
+        ));
+
+        for observation in measurements.observations.values_mut() {
+            *observation = observation
+                .with_snr(SNR::DbHz42_47)
+                .with_phase_flag(PhaseFlag::Discontinuity);
+        }
+
+        record.measurements.insert(
+            Key {
+                epoch: synthetic_t0() + Duration::from_seconds(5.0),
+                flag: EpochFlag::ExternalEvent,
+            },
+            Default::default(),
+        );
+
+        let mut doris = DORIS::default();
+        doris.header.satellite = "CRYOSAT-2".to_string();
+        doris.header.observables = vec![l1, Observable::Temperature];
+        doris.header.scaling_factors.insert(l1, 100.0);
+        doris.header.ground_stations = vec![toulouse];
+        doris.header.l1_l2_date_offset = Duration::from_seconds(1.5E-6);
+        doris.production = Some("CS2RX18164".parse().unwrap());
+        doris.record = record;
+
+        let mut writer = BufWriter::new(Vec::new());
+        doris.format_json(&mut writer).unwrap();
+
+        let content = writer.into_inner().unwrap();
+
+        let json = serde_json::from_slice::<serde_json::Value>(&content).unwrap();
+
+        assert_eq!(json["schema_version"], 1);
+        assert_eq!(json["header"]["observables"][0], "L1");
+        assert_eq!(json["header"]["scaling_factors"]["L1"], 100.0);
+        let offset = json["header"]["l1_l2_date_offset"].as_f64().unwrap();
+        assert!((offset - 1.5E-6).abs() < 1.0E-12);
+        assert_eq!(json["header"]["ground_stations"][0], 1);
+        assert_eq!(json["production"]["doy"], 164);
+
+        assert_eq!(json["stations"].as_array().unwrap().len(), 1);
+        assert_eq!(json["stations"][0]["code"], 1);
+        assert_eq!(json["stations"][0]["label"], "TLSB");
+        assert_eq!(json["stations"][0]["domes"], "10003S005");
+
+        let epoch = &json["epochs"][0];
+
+        assert_eq!(epoch["epoch"], "2018-06-13T00:00:00 TAI");
+        assert_eq!(epoch["flag"], 0);
+        let offset = epoch["clock_offset"]["offset"].as_f64().unwrap();
+        assert!((offset + 4.25E-3).abs() < 1.0E-12);
+        assert_eq!(epoch["clock_offset"]["extrapolated"], true);
+
+        let observation = &epoch["observations"][0];
+
+        assert_eq!(observation["station"], 1);
+        assert_eq!(observation["observable"], "L1");
+        assert_eq!(observation["snr"], 7);
+        assert_eq!(observation["phase_flag"], 1);
+        assert_eq!(epoch["observations"][1]["observable"], "T");
+
+        assert_eq!(json["epochs"][1]["flag"], 5);
+
+        let parsed = DORIS::parse_json(&mut BufReader::new(content.as_slice())).unwrap();
+        assert_eq!(parsed, doris);
+
+        // unsupported revision
+        let mut json = json;
+        json["schema_version"] = 2.into();
+        let content = serde_json::to_vec(&json).unwrap();
+        assert!(DORIS::parse_json(&mut BufReader::new(content.as_slice())).is_err());
+
+        // JSON lines
+        let mut writer = BufWriter::new(Vec::new());
+        doris.format_json_lines(&mut writer).unwrap();
+
+        let content = writer.into_inner().unwrap();
+
+        assert_eq!(
+            String::from_utf8_lossy(&content).lines().count(),
+            doris.record.measurements.len() + 1
+        );
+
+        let parsed = DORIS::parse_json_lines(&mut BufReader::new(content.as_slice())).unwrap();
+        assert_eq!(parsed, doris);
+    }
+}
@@ -0,0 +1,512 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use itertools::Itertools;
+
+use crate::{
+    legacy::{LegacyRecord, LegacyReport, STANDARD_COUNT_INTERVAL},
+    prelude::{
+        Epoch, EpochFlag, FormattingError, Frequency, GroundStation, Observable, PhaseFlag,
+        StationHandle, TimeScale, DORIS,
+    },
+};
+
+/// Formats this optional value, in given field width
+fn fmt_optional(value: Option<f64>, scaling: f64, width: usize) -> String {
+    match value {
+        Some(value) => format!(
+            "{:>width$}",
+            (value / scaling).round() as i64,
+            width = width
+        ),
+        None => " ".repeat(width),
+    }
+}
+
+/// Returns true if this scaled value fits in an integer field of this width
+fn fits(value: f64, scaling: f64, width: u32) -> bool {
+    let value = (value / scaling).round();
+    value.is_finite()
+        && value < 10.0_f64.powi(width as i32)
+        && value > -(10.0_f64.powi(width as i32 - 1))
+}
+
+impl LegacyRecord {
+    /// Returns the [TimeScale] in which this [LegacyRecord] is formatted
+    fn formatted_time_scale(&self) -> TimeScale {
+        if self.time_scale == TimeScale::UTC {
+            TimeScale::UTC
+        } else {
+            TimeScale::TAI
+        }
+    }
+
+    /// Returns true if this [LegacyRecord] may be formatted
+    /// without exceeding the capacity of its fields.
+    pub fn is_representable(&self) -> bool {
+        let (year, _, _, _, _, _, _) = self.epoch.to_gregorian(self.formatted_time_scale());
+
+        (1950..2050).contains(&year)
+            && self.station < 10_000
+            && self.measurement_type < 100
+            && fits(self.range_rate, 1.0E-6, 11)
+            && fits(self.iono_correction, 1.0E-6, 7)
+            && fits(self.tropo_correction, 1.0E-6, 7)
+            && self.pressure.map(|p| fits(p, 0.1, 5)).unwrap_or(true)
+            && self.temperature.map(|t| fits(t, 0.1, 4)).unwrap_or(true)
+            && self.humidity.map(|h| fits(h, 1.0, 3)).unwrap_or(true)
+            && fits(self.count_interval.to_seconds(), 1.0, 2)
+    }
+}
+
+impl std::fmt::Display for LegacyRecord {
+    /// Formats this [LegacyRecord] as DORIS 2.2 record, see the [module](crate::legacy)
+    /// documentation for the record layout.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let satellite = match &self.satellite {
+            Some(cospar)
+                if cospar.code.len() == 1 && cospar.code.as_bytes()[0].is_ascii_uppercase() =>
+            {
+                format!(
+                    "{:02}{:03}{:02}",
+                    cospar.year % 100,
+                    cospar.launch,
+                    cospar.code.as_bytes()[0] - b'A' + 1
+                )
+            },
+            _ => " ".repeat(7),
+        };
+
+        let time_scale = self.formatted_time_scale();
+
+        let (year, month, day, hours, mins, secs, nanos) = self.epoch.to_gregorian(time_scale);
+
+        let doy = (Epoch::from_gregorian_at_midnight(year, month, day, time_scale)
+            - Epoch::from_gregorian_at_midnight(year, 1, 1, time_scale))
+        .to_seconds()
+            / 86400.0;
+
+        let microseconds = (hours as i64 * 3600 + mins as i64 * 60 + secs as i64) * 1_000_000
+            + (nanos as f64 / 1.0E3).round() as i64;
+
+        write!(
+            f,
+            "{}{:>2}{}{:02}{:03}{:>11}{:>4}{:>11}{:>7}{:>7}{}{}{}{:>2}{}",
+            satellite,
+            self.measurement_type,
+            if time_scale == TimeScale::UTC { 0 } else { 1 },
+            year % 100,
+            doy.round() as i64 + 1,
+            microseconds,
+            self.station,
+            (self.range_rate * 1.0E6).round() as i64,
+            (self.iono_correction * 1.0E6).round() as i64,
+            (self.tropo_correction * 1.0E6).round() as i64,
+            fmt_optional(self.pressure, 0.1, 5),
+            fmt_optional(self.temperature, 0.1, 4),
+            fmt_optional(self.humidity, 1.0, 3),
+            self.count_interval.to_seconds().round() as i64,
+            self.restart as u8,
+        )
+    }
+}
+
+/// Phase and meteo observations of one station, at one [Epoch]
+#[derive(Default)]
+struct LegacySample {
+    l1: Option<f64>,
+    l2: Option<f64>,
+    interruption: bool,
+    pressure: Option<f64>,
+    temperature: Option<f64>,
+    humidity: Option<f64>,
+}
+
+impl LegacySample {
+    /// Returns both phases, when available
+    fn phases(&self) -> Option<(f64, f64)> {
+        Some((self.l1?, self.l2?))
+    }
+}
+
+/// Returns the DORIS 2.2 beacon number of this [GroundStation]:
+/// its label, when the label is a beacon number, its unique code otherwise.
+fn beacon_number(station: &GroundStation) -> Option<u16> {
+    let label = station.label.trim();
+
+    if !label.is_empty() && label.len() <= 4 && label.chars().all(|c| c.is_ascii_digit()) {
+        label.parse::<u16>().ok()
+    } else {
+        None
+    }
+}
+
+impl DORIS {
+    /// Converts this [DORIS] file to DORIS 2.2 Doppler records, over the
+    /// [STANDARD_COUNT_INTERVAL]. The 2 GHz range rate and the ionospheric correction are
+    /// derived from the [Observable::UnambiguousPhaseRange] observations, on both frequencies,
+    /// at the start and the end of each counting interval. Counting intervals across phase
+    /// discontinuities or power failures are not formed, the next record is then flagged as
+    /// restart. Meteo observations are converted, while the tropospheric correction is not computed
+    /// (set to zero). Records are expressed in [TimeScale::TAI].
+    /// Returns the records, sorted by beacon and in chronological order, and the [LegacyReport]
+    /// describing the information that could not be represented.
+    pub fn to_legacy_records(&self) -> (Vec<LegacyRecord>, LegacyReport) {
+        let alpha = (Frequency::DORIS1.frequency_hz() / Frequency::DORIS2.frequency_hz()).powi(2);
+
+        let mut report = LegacyReport::default();
+
+        let mut samples = BTreeMap::<StationHandle, BTreeMap<Epoch, LegacySample>>::new();
+
+        for (key, measurements) in self.record.measurements.iter() {
+            if !matches!(key.flag, EpochFlag::OK | EpochFlag::PowerFailure) {
+                report.events.push((key.epoch, key.flag));
+                continue;
+            }
+
+            if measurements.satellite_clock_offset.is_some() {
+                report.clock_offsets += 1;
+            }
+
+            for (obs_key, observation) in measurements.observations.iter() {
+                if observation.snr.is_some() {
+                    report.snr += 1;
+                }
+
+                let sample = samples
+                    .entry(obs_key.station.clone())
+                    .or_default()
+                    .entry(key.epoch)
+                    .or_default();
+
+                if key.flag == EpochFlag::PowerFailure {
+                    sample.interruption = true;
+                }
+
+                match obs_key.observable {
+                    Observable::UnambiguousPhaseRange(frequency) => {
+                        if frequency == Frequency::DORIS1 {
+                            sample.l1 = Some(observation.value);
+                        } else {
+                            sample.l2 = Some(observation.value);
+                        }
+
+                        if observation.phase_flag == Some(PhaseFlag::Discontinuity) {
+                            sample.interruption = true;
+                        }
+                    },
+                    Observable::Pressure => sample.pressure = Some(observation.value),
+                    Observable::Temperature => sample.temperature = Some(observation.value),
+                    Observable::HumidityRate => sample.humidity = Some(observation.value),
+                    observable => {
+                        if !report.observables.contains(&observable) {
+                            report.observables.push(observable);
+                        }
+                    },
+                }
+            }
+        }
+
+        report.events = report.events.into_iter().unique().collect();
+        report.observables.sort();
+
+        let mut records = Vec::new();
+
+        for (station, station_samples) in samples.iter() {
+            let number = match beacon_number(station) {
+                Some(number) => number,
+                None => {
+                    report.stations.push(station.station().clone());
+                    station.code
+                },
+            };
+
+            let mut used = Vec::<Epoch>::new();
+            let mut previous_end = Option::<Epoch>::None;
+
+            for (t_2, sample_2) in station_samples.iter() {
+                let t_1 = *t_2 - STANDARD_COUNT_INTERVAL;
+
+                if previous_end.map(|end| t_1 < end).unwrap_or(false) {
+                    continue;
+                }
+
+                let Some(sample_1) = station_samples.get(&t_1) else {
+                    continue;
+                };
+
+                let (Some((l1_1, l2_1)), Some((l1_2, l2_2))) =
+                    (sample_1.phases(), sample_2.phases())
+                else {
+                    continue;
+                };
+
+                if sample_2.interruption {
+                    continue;
+                }
+
+                let dt = STANDARD_COUNT_INTERVAL.to_seconds();
+
+                let range_rate = (l1_2 - l1_1) / dt;
+                let iono_free = (alpha * (l1_2 - l1_1) - (l2_2 - l2_1)) / (alpha - 1.0) / dt;
+
+                let record = LegacyRecord {
+                    satellite: self.header.cospar.clone(),
+                    time_scale: TimeScale::TAI,
+                    epoch: *t_2,
+                    station: number,
+                    range_rate,
+                    iono_correction: iono_free - range_rate,
+                    tropo_correction: 0.0,
+                    pressure: sample_2.pressure,
+                    temperature: sample_2.temperature,
+                    humidity: sample_2.humidity,
+                    count_interval: STANDARD_COUNT_INTERVAL,
+                    restart: previous_end != Some(t_1),
+                    ..Default::default()
+                };
+
+                if !record.is_representable() {
+                    report.overflows += 1;
+                    continue;
+                }
+
+                used.push(t_1);
+                used.push(*t_2);
+                previous_end = Some(*t_2);
+
+                records.push(record);
+            }
+
+            report.incomplete_intervals += station_samples
+                .iter()
+                .filter(|(epoch, sample)| {
+                    (sample.l1.is_some() || sample.l2.is_some()) && !used.contains(epoch)
+                })
+                .count();
+        }
+
+        report.records = records.len();
+
+        (records, report)
+    }
+
+    /// Formats this [DORIS] file as DORIS 2.2 Doppler records, into [Write]able interface.
+    /// See [Self::to_legacy_records] for more information.
+    /// Returns the [LegacyReport] describing the information that could not be represented.
+    pub fn format_legacy<W: Write>(
+        &self,
+        w: &mut BufWriter<W>,
+    ) -> Result<LegacyReport, FormattingError> {
+        let (records, report) = self.to_legacy_records();
+
+        for record in records.iter() {
+            writeln!(w, "{}", record)?;
+        }
+
+        w.flush()?;
+        Ok(report)
+    }
+
+    /// Dumps this [DORIS] file as DORIS 2.2 Doppler records, into local file.
+    /// See [Self::to_legacy_records] for more information.
+    ///
+    /// ```
+    /// use doris_rs::prelude::*;
+    ///
+    /// let doris = DORIS::from_gzip_file("data/DOR/V3/cs2rx18164.gz")
+    ///     .unwrap();
+    ///
+    /// let report = doris.to_legacy_file(std::env::temp_dir().join("cs2rx18164.dat"))
+    ///     .unwrap();
+    ///
+    /// if !report.is_lossless() {
+    ///     println!("{}", report);
+    /// }
+    /// ```
+    pub fn to_legacy_file<P: AsRef<Path>>(&self, path: P) -> Result<LegacyReport, FormattingError> {
+        let fd = File::create(path)?;
+        let mut writer = BufWriter::new(fd);
+        self.format_legacy(&mut writer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::legacy::LegacyRecord;
+    use crate::prelude::{
+        ClockOffset, Duration, EpochFlag, Frequency, GroundStation, Key, Observable, PhaseFlag,
+        DORIS, SNR,
+    };
+    use crate::tests::toolkit::{synthetic_record, synthetic_t0};
+
+    use std::io::BufWriter;
+    use std::str::FromStr;
+
+    #[test]
+    fn legacy_record_formatting() {
+        for line in [
+            "980430139198120   10000000 101-1234567800   1000   -20010132 153 55100",
+            "980430139198120   10000000 101-1234567800   1000   -200            101",
+        ] {
+            let record = LegacyRecord::from_str(line).unwrap();
+            assert_eq!(record.to_string(), line);
+            assert!(record.is_representable());
+        }
+
+        let record = LegacyRecord {
+            range_rate: 1.0E5,
+            ..Default::default()
+        };
+
+        assert!(!record.is_representable());
+    }
+
+    #[test]
+    fn doris_to_legacy() {
+        let l1 = Observable::UnambiguousPhaseRange(Frequency::DORIS1);
+        let l2 = Observable::UnambiguousPhaseRange(Frequency::DORIS2);
+        let c1 = Observable::PseudoRange(Frequency::DORIS1);
+
+        let beacon = GroundStation::default()
+            .with_site_label("0101")
+            .with_unique_id(1);
+
+        let station = GroundStation::default()
+            .with_site_label("TLSB")
+            .with_unique_id(2);
+
+        let alpha = (2036.25_f64 / 401.25).powi(2);
+
+        // S1 range rate -1 m/s, ionosphere free range rate -1.0001 m/s
+        let phases = |t: f64| {
+            let l1 = -t;
+            let l2 = alpha * l1 - (alpha - 1.0) * (-1.0001 * t);
+            (l1, l2)
+        };
+
+        let samples = (0..5)
+            .filter(|i| *i != 3)
+            .map(|i| {
+                let t = 10.0 * i as f64;
+                let (phase_1, phase_2) = phases(t);
+                (
+                    t,
+                    vec![
+                        (l1, phase_1),
+                        (l2, phase_2),
+                        (c1, 1.0E6),
+                        (Observable::Pressure, 1013.2),
+                    ],
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mut record = synthetic_record(&beacon, &samples);
+
+        let (phase_1, phase_2) = phases(10.0);
+
+        crate::tests::toolkit::add_synthetic_samples(
+            &mut record,
+            &station,
+            &[
+                (0.0, vec![(l1, 0.0), (l2, 0.0)]),
+                (10.0, vec![(l1, phase_1), (l2, phase_2)]),
+            ],
+        );
+
+        // SNR, clock offset and event are lost
+        let t0 = synthetic_t0();
+
+        let measurements = record
+            .measurements
+            .get_mut(&Key {
+                epoch: t0,
+                flag: EpochFlag::OK,
+            })
+            .unwrap();
+
+        measurements.satellite_clock_offset = Some(ClockOffset::from_measured_offset(
+            Duration::from_seconds(1.0E-3),
+        ));
+
+        for observation in measurements.observations.values_mut() {
+            *observation = observation.with_snr(SNR::DbHz54);
+        }
+
+        record.measurements.insert(
+            Key {
+                epoch: t0 + Duration::from_seconds(15.0),
+                flag: EpochFlag::ExternalEvent,
+            },
+            Default::default(),
+        );
+
+        let mut doris = DORIS {
+            record,
+            ..Default::default()
+        };
+
+        doris.header.cospar = Some("2010-013A".parse().unwrap());
+
+        let (records, report) = doris.to_legacy_records();
+
+        // beacon: [0, 10] [10, 20], gap, [30, 40] is lost (30 is missing)
+        assert_eq!(records.len(), 3);
+        assert_eq!(report.records, 3);
+
+        let record = &records[0];
+        assert_eq!(record.station, 101);
+        assert_eq!(record.epoch, t0 + Duration::from_seconds(10.0));
+        assert!((record.range_rate + 1.0).abs() < 1.0E-9);
+        assert!((record.iono_correction + 1.0E-4).abs() < 1.0E-9);
+        assert_eq!(record.pressure, Some(1013.2));
+        assert!(record.restart);
+        assert!(!records[1].restart);
+
+        assert_eq!(
+            record.to_string(),
+            "100130139118164   10000000 101   -1000000   -100      010132       101"
+        );
+
+        // station identified by its code
+        assert_eq!(records[2].station, 2);
+        assert_eq!(report.stations, vec![station]);
+
+        assert_eq!(report.observables, vec![c1]);
+        assert_eq!(report.events.len(), 1);
+        assert_eq!(report.clock_offsets, 1);
+        assert_eq!(report.snr, 6);
+        assert_eq!(report.incomplete_intervals, 1);
+        assert_eq!(report.overflows, 0);
+        assert!(!report.is_lossless());
+
+        // phase discontinuity: no interval, next record restarts
+        let mut doris = doris.clone();
+
+        for (key, measurements) in doris.record.measurements.iter_mut() {
+            if key.epoch == t0 + Duration::from_seconds(10.0) {
+                for (obs_key, observation) in measurements.observations.iter_mut() {
+                    if obs_key.station.code == 1 && obs_key.observable == l1 {
+                        *observation = observation.with_phase_flag(PhaseFlag::Discontinuity);
+                    }
+                }
+            }
+        }
+
+        let (records, _) = doris.to_legacy_records();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].epoch, t0 + Duration::from_seconds(20.0));
+        assert!(records[0].restart);
+
+        let mut writer = BufWriter::new(Vec::new());
+        let report = doris.format_legacy(&mut writer).unwrap();
+
+        let content = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(content.lines().count(), report.records);
+    }
+}
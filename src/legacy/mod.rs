@@ -0,0 +1,204 @@
+//! Legacy DORIS 2.2 Doppler data format.
+//!
+//! Prior to RINEX, DORIS measurements were distributed as fixed length Doppler
+//! records, one measurement per line and without header section.
+//! Each record describes the range rate measured on the 2 GHz frequency,
+//! averaged over one (≈10 s) counting interval, and the ionospheric correction
+//! derived from the 400 MHz frequency. Record layout (1-based columns):
+//!
+//! | Columns | Format | Content                                                      |
+//! |---------|--------|--------------------------------------------------------------|
+//! |  1-7    |  I7    | Satellite ID (COSPAR YYNNNPP, piece number A=01)             |
+//! |  8-9    |  I2    | Measurement type (39: DORIS Doppler)                         |
+//! |  10     |  I1    | Time reference (0: UTC, 1: TAI)                              |
+//! | 11-12   |  I2    | Year (two digits)                                            |
+//! | 13-15   |  I3    | Day of year                                                  |
+//! | 16-26   |  I11   | Time of day (end of counting interval), in µs                |
+//! | 27-30   |  I4    | Beacon (station) number                                      |
+//! | 31-41   |  I11   | Range rate (2 GHz), in µm/s                                  |
+//! | 42-48   |  I7    | Ionospheric correction, in µm/s                              |
+//! | 49-55   |  I7    | Tropospheric correction, in µm/s                             |
+//! | 56-60   |  I5    | Ground pressure, in 0.1 hPa (blank if unknown)               |
+//! | 61-64   |  I4    | Ground temperature, in 0.1 °C (blank if unknown)             |
+//! | 65-67   |  I3    | Ground relative humidity, in % (blank if unknown)            |
+//! | 68-69   |  I2    | Counting interval, in seconds                                |
+//! | 70      |  I1    | Restart flag (1: first count after a measurement interruption) |
+mod formatting;
+mod parsing;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::{Duration, Epoch, EpochFlag, GroundStation, Observable, TimeScale, COSPAR};
+
+/// DORIS Doppler measurement type
+pub const DORIS_DOPPLER_MEASUREMENT: u8 = 39;
+
+/// Standard DORIS counting interval
+pub const STANDARD_COUNT_INTERVAL: Duration = Duration::from_seconds(10.0);
+
+/// Length of a 2.2 record
+pub(crate) const RECORD_LENGTH: usize = 70;
+
+/// One legacy DORIS 2.2 [LegacyRecord], see the [module](crate::legacy)
+/// documentation for the record layout.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LegacyRecord {
+    /// Satellite [COSPAR] identifier, when defined
+    pub satellite: Option<COSPAR>,
+
+    /// Measurement type (see [DORIS_DOPPLER_MEASUREMENT])
+    pub measurement_type: u8,
+
+    /// [TimeScale] of the record
+    pub time_scale: TimeScale,
+
+    /// End of the counting interval
+    pub epoch: Epoch,
+
+    /// Beacon (station) number
+    pub station: u16,
+
+    /// Range rate measured on the 2 GHz frequency, averaged
+    /// over the counting interval, in m/s
+    pub range_rate: f64,
+
+    /// Ionospheric correction, to be added to [Self::range_rate], in m/s
+    pub iono_correction: f64,
+
+    /// Tropospheric correction, to be added to [Self::range_rate], in m/s
+    pub tropo_correction: f64,
+
+    /// Ground pressure, in hPa
+    pub pressure: Option<f64>,
+
+    /// Ground temperature, in °C
+    pub temperature: Option<f64>,
+
+    /// Ground relative humidity, in %
+    pub humidity: Option<f64>,
+
+    /// Counting interval
+    pub count_interval: Duration,
+
+    /// True for the first count after a measurement interruption
+    pub restart: bool,
+}
+
+impl Default for LegacyRecord {
+    fn default() -> Self {
+        Self {
+            satellite: None,
+            measurement_type: DORIS_DOPPLER_MEASUREMENT,
+            time_scale: TimeScale::TAI,
+            epoch: Epoch::from_gregorian_at_midnight(2000, 1, 1, TimeScale::TAI),
+            station: 0,
+            range_rate: 0.0,
+            iono_correction: 0.0,
+            tropo_correction: 0.0,
+            pressure: None,
+            temperature: None,
+            humidity: None,
+            count_interval: STANDARD_COUNT_INTERVAL,
+            restart: false,
+        }
+    }
+}
+
+/// [LegacyReport] describes the information that could not be represented,
+/// when converting [DORIS](crate::prelude::DORIS) to DORIS 2.2 records.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LegacyReport {
+    /// Number of DORIS 2.2 records produced
+    pub records: usize,
+
+    /// [Observable]s without DORIS 2.2 equivalent, that were not converted
+    pub observables: Vec<Observable>,
+
+    /// Event epochs, that were not converted
+    pub events: Vec<(Epoch, EpochFlag)>,
+
+    /// Number of satellite clock offsets, that were not converted
+    pub clock_offsets: usize,
+
+    /// Number of observations whose SNR was not converted
+    pub snr: usize,
+
+    /// Number of phase observations that did not permit to form a counting
+    /// interval (single frequency, phase discontinuity or data gap)
+    pub incomplete_intervals: usize,
+
+    /// Number of counting intervals, whose values exceed the record capacity
+    pub overflows: usize,
+
+    /// [GroundStation]s that are not labeled by a beacon number:
+    /// they are identified by their unique code, and their identification is lost
+    pub stations: Vec<GroundStation>,
+}
+
+impl LegacyReport {
+    /// Returns true if all the information was converted
+    pub fn is_lossless(&self) -> bool {
+        self.observables.is_empty()
+            && self.events.is_empty()
+            && self.clock_offsets == 0
+            && self.snr == 0
+            && self.incomplete_intervals == 0
+            && self.overflows == 0
+            && self.stations.is_empty()
+    }
+}
+
+impl std::fmt::Display for LegacyReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "{} DORIS 2.2 records", self.records)?;
+
+        if !self.observables.is_empty() {
+            writeln!(
+                f,
+                "observables not converted: {}",
+                self.observables
+                    .iter()
+                    .map(|observable| observable.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )?;
+        }
+
+        if !self.events.is_empty() {
+            writeln!(f, "{} event epochs not converted", self.events.len())?;
+        }
+
+        if self.clock_offsets > 0 {
+            writeln!(f, "{} clock offsets not converted", self.clock_offsets)?;
+        }
+
+        if self.snr > 0 {
+            writeln!(f, "{} SNR values not converted", self.snr)?;
+        }
+
+        if self.incomplete_intervals > 0 {
+            writeln!(
+                f,
+                "{} phase observations without counting interval",
+                self.incomplete_intervals
+            )?;
+        }
+
+        if self.overflows > 0 {
+            writeln!(f, "{} counting intervals overflow", self.overflows)?;
+        }
+
+        for station in self.stations.iter() {
+            writeln!(
+                f,
+                "station {} ({}) identified by number {}",
+                station.label, station.site, station.code
+            )?;
+        }
+
+        Ok(())
+    }
+}
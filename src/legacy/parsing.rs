@@ -0,0 +1,487 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufRead, BufReader, Read},
+    path::Path,
+    str::FromStr,
+};
+
+use itertools::Itertools;
+
+use crate::{
+    ccsds::integrate_range_rate,
+    legacy::{LegacyRecord, DORIS_DOPPLER_MEASUREMENT, RECORD_LENGTH},
+    prelude::{
+        Duration, Epoch, EpochFlag, Frequency, GroundStation, Header, Key, Observable, Observation,
+        ParsingError, PhaseFlag, Record, StationHandle, TimeScale, COSPAR, DORIS,
+    },
+};
+
+/// Returns the (trimmed) content of this 1-based column range,
+/// None when the field is blank.
+fn field(line: &str, start: usize, end: usize) -> Option<&str> {
+    let content = line.get(start - 1..end)?.trim();
+
+    if content.is_empty() {
+        None
+    } else {
+        Some(content)
+    }
+}
+
+/// Parses this mandatory integer field
+fn integer(line: &str, start: usize, end: usize) -> Result<i64, ParsingError> {
+    field(line, start, end)
+        .ok_or(ParsingError::LegacyFormat)?
+        .parse::<i64>()
+        .map_err(|_| ParsingError::LegacyFormat)
+}
+
+/// Parses this optional integer field, scaled to physical units
+fn optional(
+    line: &str,
+    start: usize,
+    end: usize,
+    scaling: f64,
+) -> Result<Option<f64>, ParsingError> {
+    match field(line, start, end) {
+        Some(content) => {
+            let value = content
+                .parse::<i64>()
+                .map_err(|_| ParsingError::LegacyFormat)?;
+
+            Ok(Some(value as f64 * scaling))
+        },
+        None => Ok(None),
+    }
+}
+
+/// Parses the 7 digit (YYNNNPP) satellite identifier
+fn parse_satellite(content: &str) -> Option<COSPAR> {
+    if content.len() != 7 || content.chars().any(|c| !c.is_ascii_digit()) {
+        return None;
+    }
+
+    let year = content[..2].parse::<u16>().ok()?;
+    let launch = content[2..5].parse::<u16>().ok()?;
+    let piece = content[5..].parse::<u8>().ok()?;
+
+    if piece == 0 || piece > 26 {
+        return None;
+    }
+
+    Some(COSPAR {
+        year: if year < 50 { 2000 + year } else { 1900 + year },
+        launch,
+        code: ((b'A' + piece - 1) as char).to_string(),
+    })
+}
+
+impl FromStr for LegacyRecord {
+    type Err = ParsingError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        if line.trim_end().len() < RECORD_LENGTH - 1 {
+            return Err(ParsingError::LegacyFormat);
+        }
+
+        let satellite = field(line, 1, 7).and_then(parse_satellite);
+
+        let measurement_type = integer(line, 8, 9)? as u8;
+
+        let time_scale = match integer(line, 10, 10)? {
+            0 => TimeScale::UTC,
+            1 => TimeScale::TAI,
+            _ => return Err(ParsingError::LegacyFormat),
+        };
+
+        let year = integer(line, 11, 12)? as i32;
+        let year = if year < 50 { 2000 + year } else { 1900 + year };
+
+        let doy = integer(line, 13, 15)?;
+        let microseconds = integer(line, 16, 26)?;
+
+        if !(1..=366).contains(&doy) || !(0..86_400_000_000).contains(&microseconds) {
+            return Err(ParsingError::LegacyFormat);
+        }
+
+        let epoch = Epoch::from_gregorian_at_midnight(year, 1, 1, time_scale)
+            + Duration::from_days((doy - 1) as f64)
+            + Duration::from_microseconds(microseconds as f64);
+
+        let station = integer(line, 27, 30)? as u16;
+
+        let range_rate = integer(line, 31, 41)? as f64 * 1.0E-6;
+        let iono_correction = integer(line, 42, 48)? as f64 * 1.0E-6;
+        let tropo_correction = integer(line, 49, 55)? as f64 * 1.0E-6;
+
+        let pressure = optional(line, 56, 60, 0.1)?;
+        let temperature = optional(line, 61, 64, 0.1)?;
+        let humidity = optional(line, 65, 67, 1.0)?;
+
+        let count_interval = Duration::from_seconds(integer(line, 68, 69)? as f64);
+
+        let restart = field(line, 70, 70) == Some("1");
+
+        Ok(Self {
+            satellite,
+            measurement_type,
+            time_scale,
+            epoch,
+            station,
+            range_rate,
+            iono_correction,
+            tropo_correction,
+            pressure,
+            temperature,
+            humidity,
+            count_interval,
+            restart,
+        })
+    }
+}
+
+impl DORIS {
+    /// Converts these [LegacyRecord]s to [DORIS]. Each beacon number is described
+    /// as a [GroundStation] labeled by this number. The 2 GHz and 400 MHz range rates
+    /// (the latter being retrieved from the ionospheric correction) are integrated into
+    /// [Observable::UnambiguousPhaseRange]. Each integration arc starts at zero, after
+    /// an interruption (or a restart flag), and is marked by a [PhaseFlag::Discontinuity].
+    /// Meteo observations are preserved, while tropospheric corrections are dropped,
+    /// because they do not have a RINEX equivalent.
+    /// Only DORIS Doppler measurements are converted, epochs are expressed in [TimeScale::TAI].
+    pub fn from_legacy_records(records: &[LegacyRecord]) -> Self {
+        let alpha = (Frequency::DORIS1.frequency_hz() / Frequency::DORIS2.frequency_hz()).powi(2);
+
+        let records = records
+            .iter()
+            .filter(|record| record.measurement_type == DORIS_DOPPLER_MEASUREMENT)
+            .collect::<Vec<_>>();
+
+        let cospar = records.iter().find_map(|record| record.satellite.clone());
+
+        let mut header = Header {
+            satellite: cospar
+                .as_ref()
+                .map(|cospar| cospar.to_string())
+                .unwrap_or_default(),
+            cospar,
+            program: Some(format!(
+                "doris-rs v{}",
+                Header::format_pkg_version(env!("CARGO_PKG_VERSION"))
+            )),
+            comments: vec!["Converted from DORIS 2.2 Doppler data".to_string()],
+            ..Default::default()
+        };
+
+        // one ground station per beacon number
+        let stations = records
+            .iter()
+            .map(|record| record.station)
+            .unique()
+            .sorted()
+            .enumerate()
+            .map(|(i, number)| {
+                let label = format!("{:04}", number);
+
+                let station = GroundStation::default()
+                    .with_site_label(&label)
+                    .with_site_name(&label)
+                    .with_unique_id(i as u16 + 1);
+
+                (number, StationHandle::from(station))
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        let mut record = Record::default();
+
+        let mut insert = |epoch: Epoch, station: &StationHandle, observable, observation| {
+            record
+                .measurements
+                .entry(Key {
+                    epoch,
+                    flag: EpochFlag::OK,
+                })
+                .or_default()
+                .add_observation(station.clone(), observable, observation);
+        };
+
+        for (number, station) in stations.iter() {
+            let station_records = records
+                .iter()
+                .filter(|record| record.station == *number)
+                .sorted_by_key(|record| record.epoch)
+                .collect::<Vec<_>>();
+
+            // split into arcs on restart, the interval being constant over an arc
+            let arcs = station_records.iter().fold(
+                Vec::<Vec<&LegacyRecord>>::new(),
+                |mut arcs, record| {
+                    match arcs.last_mut() {
+                        Some(arc)
+                            if !record.restart
+                                && arc[0].count_interval == record.count_interval =>
+                        {
+                            arc.push(record)
+                        },
+                        _ => arcs.push(vec![record]),
+                    }
+                    arcs
+                },
+            );
+
+            for arc in arcs.iter() {
+                let interval = arc[0].count_interval;
+
+                let s1 = arc
+                    .iter()
+                    .map(|record| {
+                        (
+                            record.epoch.to_time_scale(TimeScale::TAI),
+                            record.range_rate,
+                        )
+                    })
+                    .collect::<Vec<_>>();
+
+                let u2 = arc
+                    .iter()
+                    .map(|record| {
+                        (
+                            record.epoch.to_time_scale(TimeScale::TAI),
+                            record.range_rate - (alpha - 1.0) * record.iono_correction,
+                        )
+                    })
+                    .collect::<Vec<_>>();
+
+                for (frequency, samples) in [(Frequency::DORIS1, s1), (Frequency::DORIS2, u2)] {
+                    for (epoch, phase, new_arc) in integrate_range_rate(&samples, Some(interval)) {
+                        let mut observation = Observation::default().with_value(phase);
+
+                        if new_arc {
+                            observation = observation.with_phase_flag(PhaseFlag::Discontinuity);
+                        }
+
+                        insert(
+                            epoch,
+                            station,
+                            Observable::UnambiguousPhaseRange(frequency),
+                            observation,
+                        );
+                    }
+                }
+            }
+
+            for record in station_records.iter() {
+                let epoch = record.epoch.to_time_scale(TimeScale::TAI);
+
+                for (observable, value) in [
+                    (Observable::Pressure, record.pressure),
+                    (Observable::Temperature, record.temperature),
+                    (Observable::HumidityRate, record.humidity),
+                ] {
+                    if let Some(value) = value {
+                        insert(
+                            epoch,
+                            station,
+                            observable,
+                            Observation::default().with_value(value),
+                        );
+                    }
+                }
+            }
+        }
+
+        header.ground_stations = stations
+            .into_values()
+            .map(|station| station.station().clone())
+            .collect();
+
+        header.observables = record
+            .measurements
+            .values()
+            .flat_map(|measurements| measurements.observables())
+            .unique()
+            .sorted()
+            .collect();
+
+        header.time_of_first_observation = record.measurements.keys().next().map(|k| k.epoch);
+        header.time_of_last_observation = record.measurements.keys().last().map(|k| k.epoch);
+
+        DORIS::new(header, record)
+    }
+
+    /// Parses legacy DORIS 2.2 Doppler data from [Read]able interface,
+    /// and converts it to [DORIS]. See [Self::from_legacy_records].
+    /// Blank lines are tolerated.
+    ///
+    /// ```
+    /// use doris_rs::prelude::*;
+    /// use std::io::BufReader;
+    ///
+    /// let content = "\
+    /// 980430139198120   10000000 101   -1000000      0      010132 153 55100
+    /// 980430139198120   20000000 101   -2000000    100      010132 153 55100";
+    ///
+    /// let doris = DORIS::parse_legacy(&mut BufReader::new(content.as_bytes()))
+    ///     .unwrap();
+    ///
+    /// assert_eq!(doris.header.satellite, "1998-043A");
+    /// assert_eq!(doris.header.ground_stations[0].label, "0101");
+    ///
+    /// // historic data may now be exported as RINEX
+    /// doris.to_file(std::env::temp_dir().join("legacy.rnx"))
+    ///     .unwrap();
+    /// ```
+    pub fn parse_legacy<R: Read>(reader: &mut BufReader<R>) -> Result<Self, ParsingError> {
+        let mut records = Vec::new();
+
+        for line in reader.lines() {
+            let line = line?;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            records.push(LegacyRecord::from_str(&line)?);
+        }
+
+        Ok(Self::from_legacy_records(&records))
+    }
+
+    /// Parses legacy DORIS 2.2 Doppler data from local file.
+    /// See [Self::from_legacy_records] for more information.
+    pub fn from_legacy_file<P: AsRef<Path>>(path: P) -> Result<Self, ParsingError> {
+        let fd = File::open(path)?;
+        let mut reader = BufReader::new(fd);
+        Self::parse_legacy(&mut reader)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::legacy::LegacyRecord;
+    use crate::prelude::{Duration, Epoch, Frequency, Observable, PhaseFlag, TimeScale, DORIS};
+
+    use std::io::BufReader;
+    use std::str::FromStr;
+
+    #[test]
+    fn legacy_record_parsing() {
+        let line = "980430139198120   10000000 101-1234567800   1000   -20010132 153 55100";
+
+        let record = LegacyRecord::from_str(line).unwrap();
+
+        let cospar = record.satellite.clone().unwrap();
+        assert_eq!(cospar.to_string(), "1998-043A");
+
+        assert_eq!(record.measurement_type, 39);
+        assert_eq!(record.time_scale, TimeScale::TAI);
+
+        assert_eq!(
+            record.epoch,
+            Epoch::from_gregorian(1998, 4, 30, 0, 0, 10, 0, TimeScale::TAI)
+        );
+
+        assert_eq!(record.station, 101);
+        assert!((record.range_rate + 1234.5678).abs() < 1.0E-9);
+        assert!((record.iono_correction - 1.0E-3).abs() < 1.0E-12);
+        assert!((record.tropo_correction + 2.0E-4).abs() < 1.0E-12);
+        assert!((record.pressure.unwrap() - 1013.2).abs() < 1.0E-9);
+        assert!((record.temperature.unwrap() - 15.3).abs() < 1.0E-9);
+        assert_eq!(record.humidity, Some(55.0));
+        assert_eq!(record.count_interval, Duration::from_seconds(10.0));
+        assert!(!record.restart);
+
+        // blank meteo, restart
+        let line = "980430139198120   10000000 101-1234567800   1000   -200            101";
+        let record = LegacyRecord::from_str(line).unwrap();
+        assert!(record.pressure.is_none());
+        assert!(record.humidity.is_none());
+        assert!(record.restart);
+
+        assert!(LegacyRecord::from_str("980430139198120   10000000").is_err());
+    }
+
+    #[test]
+    fn legacy_to_doris() {
+        let content = "\
+980430139198120   10000000 101   -1000000      0      010132 153 55100
+980430139198120   20000000 101   -2000000    100      010132 153 55100
+
+980430139198120   60000000 101    1000000      0      010132 153 55100
+980430139198120   60000000 102    3000000      0      0      100   100
+";
+
+        let doris = DORIS::parse_legacy(&mut BufReader::new(content.as_bytes())).unwrap();
+
+        assert_eq!(doris.header.satellite, "1998-043A");
+        assert_eq!(doris.header.ground_stations.len(), 2);
+        assert_eq!(doris.header.ground_stations[0].label, "0101");
+        assert_eq!(doris.header.ground_stations[1].label, "0102");
+
+        let l1 = Observable::UnambiguousPhaseRange(Frequency::DORIS1);
+        let l2 = Observable::UnambiguousPhaseRange(Frequency::DORIS2);
+
+        assert_eq!(
+            doris.header.observables,
+            vec![
+                l1,
+                l2,
+                Observable::Pressure,
+                Observable::Temperature,
+                Observable::HumidityRate
+            ]
+        );
+
+        let t0 = Epoch::from_gregorian(1998, 4, 30, 0, 0, 0, 0, TimeScale::TAI);
+
+        let series = |code: u16, observable: Observable| {
+            doris
+                .record
+                .measurements
+                .iter()
+                .flat_map(|(key, measurements)| {
+                    measurements
+                        .observations
+                        .iter()
+                        .filter(move |(k, _)| k.station.code == code && k.observable == observable)
+                        .map(move |(_, observation)| (key.epoch, *observation))
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let phases = series(1, l1);
+        assert_eq!(phases.len(), 5);
+
+        for ((epoch, observation), (t, expected, new_arc)) in phases.iter().zip([
+            (0.0, 0.0, true),
+            (10.0, -10.0, false),
+            (20.0, -30.0, false),
+            (50.0, 0.0, true),
+            (60.0, 10.0, false),
+        ]) {
+            assert_eq!(*epoch, t0 + Duration::from_seconds(t));
+            assert!((observation.value - expected).abs() < 1.0E-9);
+
+            assert_eq!(
+                observation.phase_flag == Some(PhaseFlag::Discontinuity),
+                new_arc
+            );
+        }
+
+        // iono free combination of both phases = corrected range rate
+        let alpha = (2036.25_f64 / 401.25).powi(2);
+        let phases_2 = series(1, l2);
+
+        let iono_free =
+            |i: usize| (alpha * phases[i].1.value - phases_2[i].1.value) / (alpha - 1.0);
+
+        let range_rate = (iono_free(2) - iono_free(1)) / 10.0;
+        assert!((range_rate - (-2.0 + 1.0E-4)).abs() < 1.0E-9);
+
+        assert_eq!(series(1, Observable::Pressure).len(), 3);
+        assert_eq!(series(2, Observable::Pressure).len(), 0);
+        assert_eq!(series(2, Observable::HumidityRate).len(), 0);
+        assert_eq!(series(2, Observable::Temperature).len(), 1);
+    }
+}
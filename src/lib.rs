@@ -23,17 +23,54 @@ extern crate serde;
 extern crate gnss_rs as gnss;
 extern crate num;
 
+pub mod beacon;
+pub mod ccsds;
 pub mod constants;
+pub mod csv;
+pub mod discontinuity;
 pub mod error;
 pub mod frequency;
 pub mod header;
+pub mod legacy;
+pub mod mask;
 pub mod matcher;
+pub mod meteo;
+pub mod multipath;
 pub mod observable;
+pub mod orbit;
+pub mod pass;
+pub mod positioning;
 pub mod production;
 pub mod record;
+pub mod residuals;
+pub mod screening;
+pub mod sinex;
 pub mod station;
+pub mod troposphere;
+
+#[cfg(feature = "arrow")]
+#[cfg_attr(docsrs, doc(cfg(feature = "arrow")))]
+pub mod arrow;
+
+#[cfg(feature = "polars")]
+#[cfg_attr(docsrs, doc(cfg(feature = "polars")))]
+pub mod polars;
+
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+pub mod json;
+
+#[cfg(feature = "sqlite")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sqlite")))]
+pub mod sqlite;
+
+#[cfg(feature = "serde")]
+mod entries;
 
 mod epoch;
+mod lsq;
+mod rinex;
+mod statistics;
 
 #[cfg(test)]
 mod tests;
@@ -47,6 +84,9 @@ use std::{
 
 use itertools::Itertools;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 #[cfg(feature = "flate2")]
 use flate2::{read::GzDecoder, write::GzEncoder, Compression as GzCompression};
 
@@ -67,19 +107,40 @@ pub type Comments = Vec<String>;
 pub mod prelude {
     // export
     pub use crate::{
+        beacon::{BeaconOffset, BeaconOffsetEstimator},
+        ccsds::{Tdm, TdmDataType, TdmIntegrationRef, TdmMetadata, TdmObservation, TdmSegment},
+        csv::{CsvEpochFormat, CsvLayout, CsvOptions, CsvStation},
+        discontinuity::{DiscontinuityDetector, PhaseBreak},
         error::{FormattingError, ParsingError},
         frequency::Frequency,
         header::{Antenna, Header, Receiver, Version},
+        legacy::{LegacyRecord, LegacyReport},
+        mask::{GeometricMask, MaskAction, MaskCriteria, MaskCriterion, MaskReport, MaskedLink},
         matcher::Matcher,
+        meteo::{MeteoSample, MeteoSeries},
+        multipath::{MultipathAnalysis, MultipathStats, PassMultipath},
         observable::Observable,
+        orbit::{Geometry, Orbit, OrbitState, SP3},
+        pass::Pass,
+        positioning::{PositionSolution, PositioningSolver},
         production::ProductionAttributes,
         record::{
-            ClockOffset, EpochFlag, Key, Measurements, Observation, ObservationKey, Record, SNR,
+            ClockOffset, ColumnarRecord, EpochFlag, Key, Measurements, Observation, ObservationKey,
+            PhaseFlag, Record, StationIndex, SNR,
         },
-        station::GroundStation,
+        residuals::{
+            PassResiduals, RangeRateModel, RangeRateResiduals, RangeRateSample, ResidualStats,
+        },
+        screening::{OutlierScreening, Rejection, RobustEstimator, ScreeningReport, ScreeningTest},
+        sinex::{Sinex, SinexSolution},
+        station::{GroundStation, StationCoordinates, StationHandle},
+        troposphere::{MappingFunction, TroposphereModel, ZenithDelay},
         Comments, DORIS,
     };
 
+    #[cfg(feature = "sqlite")]
+    pub use crate::sqlite::SqliteArchive;
+
     pub use gnss::prelude::{Constellation, DOMESTrackingPoint, COSPAR, DOMES, SV};
 
     pub use hifitime::{Duration, Epoch, Polynomial, TimeScale, TimeSeries};
@@ -109,6 +170,7 @@ pub(crate) fn fmt_comment(content: &str) -> String {
 }
 
 #[derive(Clone, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 /// [DORIS] is composed of a [Header] and a [Record] section.
 /// ```
 /// use std::str::FromStr;
@@ -0,0 +1,109 @@
+//! Least squares helpers, shared by our estimators
+
+/// Cholesky decomposition (N = L.Lᵀ) of this symmetric positive definite matrix.
+/// Returns None if the matrix is not positive definite (singular problem).
+fn cholesky(n: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let size = n.len();
+    let mut l = vec![vec![0.0; size]; size];
+
+    for i in 0..size {
+        for j in 0..=i {
+            let sum = (0..j).map(|k| l[i][k] * l[j][k]).sum::<f64>();
+
+            if i == j {
+                let diagonal = n[i][i] - sum;
+
+                if diagonal <= 0.0 || !diagonal.is_finite() {
+                    return None;
+                }
+
+                l[i][j] = diagonal.sqrt();
+            } else {
+                l[i][j] = (n[i][j] - sum) / l[j][j];
+            }
+        }
+    }
+
+    Some(l)
+}
+
+/// Solves L.Lᵀ.x = b, from the Cholesky factor
+fn cholesky_solve(l: &[Vec<f64>], b: &[f64]) -> Vec<f64> {
+    let size = l.len();
+
+    let mut y = vec![0.0; size];
+
+    for i in 0..size {
+        let sum = (0..i).map(|k| l[i][k] * y[k]).sum::<f64>();
+        y[i] = (b[i] - sum) / l[i][i];
+    }
+
+    let mut x = vec![0.0; size];
+
+    for i in (0..size).rev() {
+        let sum = (i + 1..size).map(|k| l[k][i] * x[k]).sum::<f64>();
+        x[i] = (y[i] - sum) / l[i][i];
+    }
+
+    x
+}
+
+/// Solves the normal equations N.x = b, and returns (x, N⁻¹).
+/// Returns None if N is singular.
+pub(crate) fn solve_normal_equations(
+    n: &[Vec<f64>],
+    b: &[f64],
+) -> Option<(Vec<f64>, Vec<Vec<f64>>)> {
+    let l = cholesky(n)?;
+
+    let x = cholesky_solve(&l, b);
+
+    let size = n.len();
+    let mut inverse = vec![vec![0.0; size]; size];
+
+    for j in 0..size {
+        let mut unit = vec![0.0; size];
+        unit[j] = 1.0;
+
+        for (i, value) in cholesky_solve(&l, &unit).into_iter().enumerate() {
+            inverse[i][j] = value;
+        }
+    }
+
+    Some((x, inverse))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn normal_equations() {
+        let n = vec![
+            vec![4.0, 12.0, -16.0],
+            vec![12.0, 37.0, -43.0],
+            vec![-16.0, -43.0, 98.0],
+        ];
+
+        let (x, inverse) = solve_normal_equations(&n, &[0.0, 6.0, 39.0]).unwrap();
+
+        for (x, expected) in x.iter().zip([1.0, 1.0, 1.0]) {
+            assert!((x - expected).abs() < 1.0E-9);
+        }
+
+        for (i, row) in n.iter().enumerate() {
+            for j in 0..3 {
+                let value = row
+                    .iter()
+                    .zip(inverse.iter())
+                    .map(|(n, inverse)| n * inverse[j])
+                    .sum::<f64>();
+
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!((value - expected).abs() < 1.0E-9);
+            }
+        }
+
+        assert!(solve_normal_equations(&[vec![1.0, 1.0], vec![1.0, 1.0]], &[1.0, 1.0]).is_none());
+    }
+}
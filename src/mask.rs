@@ -0,0 +1,451 @@
+//! Geometric (elevation, azimuth and range) masking of observations
+use std::collections::{BTreeMap, HashSet};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::{
+    Epoch, Geometry, GroundStation, Observable, Orbit, Record, StationHandle, DORIS, SNR, SP3,
+};
+
+/// [MaskCriteria] defines the acceptable [Geometry] of a link.
+/// Each criterion is disabled when set to None.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MaskCriteria {
+    /// Minimal elevation angle, in degrees
+    pub min_elevation_deg: Option<f64>,
+
+    /// Accepted azimuth sector (start, end), in degrees, described clockwise.
+    /// The sector may wrap around north, for example (300, 60).
+    pub azimuth_sector_deg: Option<(f64, f64)>,
+
+    /// Maximal range, in meters
+    pub max_range_m: Option<f64>,
+}
+
+impl MaskCriteria {
+    /// Copies and returns [MaskCriteria] with updated elevation cut-off, in degrees
+    pub fn with_min_elevation(&self, elevation_deg: f64) -> Self {
+        let mut s = *self;
+        s.min_elevation_deg = Some(elevation_deg);
+        s
+    }
+
+    /// Copies and returns [MaskCriteria] with updated azimuth sector (start, end),
+    /// in degrees, described clockwise.
+    pub fn with_azimuth_sector(&self, start_deg: f64, end_deg: f64) -> Self {
+        let mut s = *self;
+        s.azimuth_sector_deg = Some((start_deg.rem_euclid(360.0), end_deg.rem_euclid(360.0)));
+        s
+    }
+
+    /// Copies and returns [MaskCriteria] with updated range limit, in meters
+    pub fn with_max_range(&self, range_m: f64) -> Self {
+        let mut s = *self;
+        s.max_range_m = Some(range_m);
+        s
+    }
+
+    /// Returns the first [MaskCriterion] that this [Geometry] does not satisfy, if any
+    pub fn violation(&self, geometry: &Geometry) -> Option<MaskCriterion> {
+        if let Some(min_elevation) = self.min_elevation_deg {
+            if geometry.elevation_deg < min_elevation {
+                return Some(MaskCriterion::Elevation);
+            }
+        }
+
+        if let Some((start, end)) = self.azimuth_sector_deg {
+            let azimuth = geometry.azimuth_deg.rem_euclid(360.0);
+
+            let inside = if start <= end {
+                azimuth >= start && azimuth <= end
+            } else {
+                azimuth >= start || azimuth <= end
+            };
+
+            if !inside {
+                return Some(MaskCriterion::Azimuth);
+            }
+        }
+
+        if let Some(max_range) = self.max_range_m {
+            if geometry.range_m > max_range {
+                return Some(MaskCriterion::Range);
+            }
+        }
+
+        None
+    }
+}
+
+/// [MaskCriterion] describes why an observation was masked
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MaskCriterion {
+    /// Below elevation cut-off
+    Elevation,
+
+    /// Outside azimuth sector
+    Azimuth,
+
+    /// Beyond range limit
+    Range,
+}
+
+/// [MaskAction] describes what happens to the observations that do not pass
+/// the [GeometricMask]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MaskAction {
+    /// Observations are removed from the [Record]
+    #[default]
+    Remove,
+
+    /// Observations are preserved, and the frequency dependent observations
+    /// ([Observable::PseudoRange], [Observable::UnambiguousPhaseRange] and [Observable::Power])
+    /// are marked with [SNR::DbHz0]. Meteo observations are not modified.
+    Mark,
+}
+
+/// [MaskedLink] describes the observations of one [GroundStation], at one [Epoch],
+/// that were masked by the [GeometricMask].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MaskedLink {
+    /// [GroundStation] being observed
+    pub station: GroundStation,
+
+    /// [Epoch] of observation
+    pub epoch: Epoch,
+
+    /// [Geometry] of the link
+    pub geometry: Geometry,
+
+    /// [MaskCriterion] that masked this link
+    pub criterion: MaskCriterion,
+}
+
+/// [MaskReport] lists all links masked by the [GeometricMask], in chronological order.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MaskReport {
+    /// Masked links
+    pub masked: Vec<MaskedLink>,
+}
+
+impl MaskReport {
+    /// Total number of masked links
+    pub fn len(&self) -> usize {
+        self.masked.len()
+    }
+
+    /// Returns true if no link was masked
+    pub fn is_empty(&self) -> bool {
+        self.masked.is_empty()
+    }
+
+    /// Returns true if the observations of this [GroundStation] at this [Epoch] were masked
+    pub fn is_masked(&self, station: &GroundStation, epoch: Epoch) -> bool {
+        self.masked
+            .iter()
+            .any(|link| link.epoch == epoch && link.station == *station)
+    }
+}
+
+/// [GeometricMask] rejects the observations whose link [Geometry] does not
+/// satisfy the [MaskCriteria]. Site specific [MaskCriteria] may be defined,
+/// to account for the local horizon of each beacon. Rejected observations are
+/// either removed or marked, depending on the [MaskAction].
+/// Observations for which the [Geometry] is unknown (for example, station
+/// coordinates not described) are preserved.
+///
+/// ```no_run
+/// use doris_rs::prelude::*;
+///
+/// let sinex = Sinex::from_gzip_file("data/SNX/dpod2020_031.snx.gz")
+///     .unwrap();
+///
+/// let sp3 = SP3::from_gzip_file("data/SP3/ssacs220.b18164.e18165.DG_.sp3.001.gz")
+///     .unwrap();
+///
+/// // coordinates propagated to the beginning of the day
+/// let t0 = Epoch::from_gregorian_utc_at_midnight(2018, 6, 13);
+///
+/// let mut doris = DORIS::from_gzip_file("data/DOR/V3/cs2rx18164.gz")
+///     .unwrap()
+///     .with_station_coordinates(&sinex, t0);
+///
+/// let mask = GeometricMask::new(MaskCriteria::default().with_min_elevation(10.0))
+///     .with_station_criteria(
+///         "TLSB",
+///         MaskCriteria::default()
+///             .with_min_elevation(15.0)
+///             .with_azimuth_sector(90.0, 270.0),
+///     );
+///
+/// let report = doris.geometric_mask_mut(&mask, &sp3)
+///     .unwrap();
+///
+/// for link in report.masked.iter() {
+///     println!("{} {}: {:?}", link.epoch, link.station.label, link.criterion);
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GeometricMask {
+    /// [MaskCriteria] applied to all stations
+    pub criteria: MaskCriteria,
+
+    /// Site specific [MaskCriteria], indexed by station label
+    pub stations: BTreeMap<String, MaskCriteria>,
+
+    /// [MaskAction] applied to the rejected observations
+    pub action: MaskAction,
+}
+
+impl GeometricMask {
+    /// Builds a new [GeometricMask] applying these [MaskCriteria] to all stations
+    pub fn new(criteria: MaskCriteria) -> Self {
+        Self {
+            criteria,
+            stations: Default::default(),
+            action: Default::default(),
+        }
+    }
+
+    /// Copies and returns [GeometricMask] with updated [MaskAction]
+    pub fn with_action(&self, action: MaskAction) -> Self {
+        let mut s = self.clone();
+        s.action = action;
+        s
+    }
+
+    /// Copies and returns [GeometricMask] with site specific [MaskCriteria],
+    /// that replace the general [MaskCriteria] for this station label.
+    pub fn with_station_criteria(&self, label: &str, criteria: MaskCriteria) -> Self {
+        let mut s = self.clone();
+        s.stations.insert(label.to_uppercase(), criteria);
+        s
+    }
+
+    /// Returns the [MaskCriteria] that apply to this [GroundStation]
+    pub fn station_criteria(&self, station: &GroundStation) -> &MaskCriteria {
+        self.stations
+            .get(&station.label.to_uppercase())
+            .unwrap_or(&self.criteria)
+    }
+
+    /// Evaluates this [GeometricMask] on the link [Geometry] of each ([Epoch], [GroundStation]),
+    /// and returns the [MaskReport], see [Record::observation_geometry].
+    pub fn evaluate(&self, geometry: &BTreeMap<(Epoch, StationHandle), Geometry>) -> MaskReport {
+        let masked = geometry
+            .iter()
+            .filter_map(|((epoch, station), geometry)| {
+                let criterion = self.station_criteria(station).violation(geometry)?;
+
+                Some(MaskedLink {
+                    station: station.station().clone(),
+                    epoch: *epoch,
+                    geometry: *geometry,
+                    criterion,
+                })
+            })
+            .collect();
+
+        MaskReport { masked }
+    }
+
+    /// Reports the observations of this [Record] that do not pass this [GeometricMask],
+    /// using this [Orbit]. The [Record] is not modified: use [Self::mask_mut]
+    /// to remove or mark the rejected observations.
+    pub fn mask(&self, record: &Record, orbit: &Orbit) -> MaskReport {
+        self.evaluate(&record.observation_geometry(orbit))
+    }
+
+    /// Removes (or marks, depending on the [MaskAction]) the observations of this [Record]
+    /// that do not pass this [GeometricMask] (in place), using this [Orbit].
+    /// Returns the [MaskReport].
+    pub fn mask_mut(&self, record: &mut Record, orbit: &Orbit) -> MaskReport {
+        let report = self.mask(record, orbit);
+
+        let masked = report
+            .masked
+            .iter()
+            .map(|link| (StationHandle::from(&link.station), link.epoch))
+            .collect::<HashSet<_>>();
+
+        match self.action {
+            MaskAction::Remove => {
+                record.measurements.retain(|key, measurements| {
+                    measurements.observations.retain(|obs_key, _| {
+                        !masked.contains(&(obs_key.station.clone(), key.epoch))
+                    });
+
+                    !measurements.observations.is_empty()
+                });
+            },
+            MaskAction::Mark => {
+                for (key, measurements) in record.measurements.iter_mut() {
+                    for (obs_key, observation) in measurements.observations.iter_mut() {
+                        let frequency_dependent = matches!(
+                            obs_key.observable,
+                            Observable::PseudoRange(_)
+                                | Observable::UnambiguousPhaseRange(_)
+                                | Observable::Power(_)
+                        );
+
+                        if frequency_dependent
+                            && masked.contains(&(obs_key.station.clone(), key.epoch))
+                        {
+                            observation.snr = Some(SNR::DbHz0);
+                        }
+                    }
+                }
+            },
+        }
+
+        report
+    }
+}
+
+impl Record {
+    /// Copies and returns [Record] where observations that do not pass
+    /// this [GeometricMask] have been removed (or marked). See [GeometricMask::mask_mut].
+    pub fn with_geometric_mask(&self, mask: &GeometricMask, orbit: &Orbit) -> Self {
+        let mut s = self.clone();
+        s.geometric_mask_mut(mask, orbit);
+        s
+    }
+
+    /// Removes (or marks) the observations that do not pass this [GeometricMask] (in place),
+    /// and returns the [MaskReport]. See [GeometricMask::mask_mut].
+    pub fn geometric_mask_mut(&mut self, mask: &GeometricMask, orbit: &Orbit) -> MaskReport {
+        mask.mask_mut(self, orbit)
+    }
+}
+
+impl DORIS {
+    /// Copies and returns [DORIS] where observations that do not pass
+    /// this [GeometricMask] have been removed (or marked), using the [Orbit] of this
+    /// satellite (see [SP3::header_orbit]).
+    /// Returns None when this satellite is not described by the [SP3] file.
+    pub fn with_geometric_mask(&self, mask: &GeometricMask, sp3: &SP3) -> Option<Self> {
+        let mut s = self.clone();
+        s.geometric_mask_mut(mask, sp3)?;
+        Some(s)
+    }
+
+    /// Removes (or marks) the observations that do not pass this [GeometricMask] (in place),
+    /// using the [Orbit] of this satellite (see [SP3::header_orbit]),
+    /// and returns the [MaskReport].
+    /// Returns None when this satellite is not described by the [SP3] file.
+    pub fn geometric_mask_mut(&mut self, mask: &GeometricMask, sp3: &SP3) -> Option<MaskReport> {
+        let orbit = sp3.header_orbit(&self.header)?;
+        Some(self.record.geometric_mask_mut(mask, orbit))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::{Duration, Frequency, Header, OrbitState, StationCoordinates};
+    use crate::tests::toolkit::{synthetic_record, synthetic_t0};
+
+    #[test]
+    fn geometric_mask() {
+        let geometry = |elevation_deg: f64, azimuth_deg: f64, range_m: f64| Geometry {
+            range_m,
+            elevation_deg,
+            azimuth_deg,
+            range_rate_m_s: 0.0,
+        };
+
+        let criteria = MaskCriteria::default()
+            .with_min_elevation(10.0)
+            .with_azimuth_sector(300.0, 60.0)
+            .with_max_range(3.0E6);
+
+        assert_eq!(criteria.violation(&geometry(20.0, 10.0, 1.0E6)), None);
+        assert_eq!(criteria.violation(&geometry(20.0, 330.0, 1.0E6)), None);
+
+        assert_eq!(
+            criteria.violation(&geometry(5.0, 10.0, 1.0E6)),
+            Some(MaskCriterion::Elevation)
+        );
+        assert_eq!(
+            criteria.violation(&geometry(20.0, 180.0, 1.0E6)),
+            Some(MaskCriterion::Azimuth)
+        );
+        assert_eq!(
+            criteria.violation(&geometry(20.0, 10.0, 4.0E6)),
+            Some(MaskCriterion::Range)
+        );
+
+        let t0 = synthetic_t0();
+
+        // satellite at zenith of the equatorial station
+        let orbit = Orbit {
+            satellite: "L27".to_string(),
+            states: [0.0, 100.0]
+                .into_iter()
+                .map(|t| OrbitState {
+                    epoch: t0 + Duration::from_seconds(t),
+                    position_m: (7.0E6, 0.0, 0.0),
+                    velocity_m_s: Some((0.0, 0.0, 0.0)),
+                })
+                .collect(),
+        };
+
+        let coordinates = StationCoordinates {
+            epoch: t0,
+            position_m: (6378137.0, 0.0, 0.0),
+            velocity_m_yr: (0.0, 0.0, 0.0),
+        };
+
+        let station = GroundStation::default()
+            .with_site_label("TLSB")
+            .with_unique_id(13)
+            .with_coordinates(coordinates);
+
+        let samples = [(
+            0.0,
+            vec![(Observable::PseudoRange(Frequency::DORIS1), 1.0E6)],
+        )];
+
+        let record = synthetic_record(&station, &samples);
+
+        let mask = GeometricMask::new(MaskCriteria::default().with_min_elevation(10.0));
+        assert!(mask.mask(&record, &orbit).is_empty());
+
+        let mask =
+            mask.with_station_criteria("tlsb", MaskCriteria::default().with_max_range(1.0E5));
+
+        let masked = record.with_geometric_mask(&mask, &orbit);
+        assert!(masked.measurements.is_empty());
+
+        let report = mask.mask(&record, &orbit);
+        assert_eq!(report.len(), 1);
+        assert!(report.is_masked(&station, t0));
+        assert_eq!(report.masked[0].criterion, MaskCriterion::Range);
+
+        let marked = record.with_geometric_mask(&mask.with_action(MaskAction::Mark), &orbit);
+        assert_eq!(marked.measurements.len(), 1);
+
+        let observations = marked
+            .measurements
+            .values()
+            .flat_map(|measurements| measurements.observations.values())
+            .collect::<Vec<_>>();
+
+        assert_eq!(observations.len(), 1);
+        assert_eq!(observations[0].snr, Some(SNR::DbHz0));
+        assert_eq!(observations[0].value, 1.0E6);
+
+        // satellite not described by the SP3 file
+        let mut doris = DORIS::new(Header::default(), record);
+
+        assert!(doris.with_geometric_mask(&mask, &SP3::default()).is_none());
+        assert!(doris.geometric_mask_mut(&mask, &SP3::default()).is_none());
+    }
+}
@@ -4,8 +4,12 @@ use crate::prelude::DOMES;
 #[cfg(doc)]
 use crate::prelude::{GroundStation, Record, DORIS};
 
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
 /// [Matcher] is used to easily identify [GroundStation]s from a [DORIS] [Record].
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub enum Matcher<'a> {
     /// Search by station ID#
     /// ```
@@ -0,0 +1,254 @@
+//! Ground station meteorological observations
+use std::collections::BTreeMap;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::{Epoch, GroundStation, Matcher, Observable, Record, StationHandle, DORIS};
+
+/// Minimal physical pressure, in hPa
+const MIN_PRESSURE_HPA: f64 = 500.0;
+
+/// Maximal physical pressure, in hPa
+const MAX_PRESSURE_HPA: f64 = 1100.0;
+
+/// Minimal physical temperature, in °C
+const MIN_TEMPERATURE_CELSIUS: f64 = -90.0;
+
+/// Maximal physical temperature, in °C
+const MAX_TEMPERATURE_CELSIUS: f64 = 60.0;
+
+/// [MeteoSample] gathers the meteorological observations of a [GroundStation],
+/// at the epoch of spaceborn observation.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MeteoSample {
+    /// [Epoch] of observation
+    pub epoch: Epoch,
+
+    /// Pressure, in hPa
+    pub pressure: f64,
+
+    /// Dry temperature, in °C
+    pub temperature: f64,
+
+    /// Relative humidity, as saturation percentage
+    pub humidity: f64,
+}
+
+impl MeteoSample {
+    /// Returns saturation water vapour pressure, in hPa
+    /// (Magnus-Tetens formula).
+    pub fn saturation_vapour_pressure(&self) -> f64 {
+        6.1078 * (17.27 * self.temperature / (self.temperature + 237.3)).exp()
+    }
+
+    /// Returns (partial) water vapour pressure, in hPa
+    pub fn water_vapour_pressure(&self) -> f64 {
+        self.humidity / 100.0 * self.saturation_vapour_pressure()
+    }
+
+    /// Returns true if all values lie within their physical range:
+    /// - 500 to 1100 hPa pressure
+    /// - -90 to 60 °C temperature
+    /// - 0 to 100 % relative humidity
+    pub fn is_physical(&self) -> bool {
+        (MIN_PRESSURE_HPA..=MAX_PRESSURE_HPA).contains(&self.pressure)
+            && (MIN_TEMPERATURE_CELSIUS..=MAX_TEMPERATURE_CELSIUS).contains(&self.temperature)
+            && (0.0..=100.0).contains(&self.humidity)
+    }
+}
+
+/// [MeteoSeries] is the meteorological time series of one [GroundStation]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MeteoSeries {
+    /// [GroundStation] where the observations were made
+    pub station: GroundStation,
+
+    /// [MeteoSample]s, in chronological order
+    pub samples: Vec<MeteoSample>,
+}
+
+impl MeteoSeries {
+    /// Returns true if this [MeteoSeries] does not contain any sample
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Returns a linearly interpolated [MeteoSample] at desired [Epoch].
+    /// Returns None if [Epoch] lies outside this [MeteoSeries].
+    pub fn interpolate(&self, epoch: Epoch) -> Option<MeteoSample> {
+        let index = self.samples.partition_point(|sample| sample.epoch < epoch);
+
+        let after = self.samples.get(index)?;
+
+        if after.epoch == epoch {
+            return Some(*after);
+        }
+
+        let before = self.samples.get(index.checked_sub(1)?)?;
+
+        let dt = (after.epoch - before.epoch).to_seconds();
+        let ratio = (epoch - before.epoch).to_seconds() / dt;
+
+        let lerp = |a: f64, b: f64| a + (b - a) * ratio;
+
+        Some(MeteoSample {
+            epoch,
+            pressure: lerp(before.pressure, after.pressure),
+            temperature: lerp(before.temperature, after.temperature),
+            humidity: lerp(before.humidity, after.humidity),
+        })
+    }
+
+    /// Returns [Iterator] over non physical [MeteoSample]s.
+    /// See [MeteoSample::is_physical].
+    pub fn non_physical_iter(&self) -> Box<dyn Iterator<Item = &MeteoSample> + '_> {
+        Box::new(self.samples.iter().filter(|sample| !sample.is_physical()))
+    }
+
+    /// Retains physical [MeteoSample]s only (in place).
+    /// See [MeteoSample::is_physical].
+    pub fn retain_physical_mut(&mut self) {
+        self.samples.retain(|sample| sample.is_physical());
+    }
+}
+
+impl Record {
+    /// Returns the [MeteoSeries] of each [GroundStation].
+    /// Samples are only formed when pressure, temperature and humidity
+    /// were all observed.
+    pub fn meteo_series(&self) -> Vec<MeteoSeries> {
+        let mut series = BTreeMap::<StationHandle, BTreeMap<Epoch, [Option<f64>; 3]>>::new();
+
+        for (key, measurements) in self.measurements.iter() {
+            for (obs_key, observation) in measurements.observations.iter() {
+                let index = match obs_key.observable {
+                    Observable::Pressure => 0,
+                    Observable::Temperature => 1,
+                    Observable::HumidityRate => 2,
+                    _ => continue,
+                };
+
+                series
+                    .entry(obs_key.station.clone())
+                    .or_default()
+                    .entry(key.epoch)
+                    .or_default()[index] = Some(observation.value);
+            }
+        }
+
+        series
+            .into_iter()
+            .map(|(station, samples)| MeteoSeries {
+                station: station.station().clone(),
+                samples: samples
+                    .into_iter()
+                    .filter_map(|(epoch, values)| {
+                        Some(MeteoSample {
+                            epoch,
+                            pressure: values[0]?,
+                            temperature: values[1]?,
+                            humidity: values[2]?,
+                        })
+                    })
+                    .collect(),
+            })
+            .filter(|series| !series.is_empty())
+            .collect()
+    }
+
+    /// Returns the [MeteoSeries] of the matching [GroundStation], if any.
+    pub fn station_meteo(&self, matcher: &Matcher) -> Option<MeteoSeries> {
+        self.meteo_series()
+            .into_iter()
+            .find(|series| series.station.matches(matcher))
+    }
+}
+
+impl DORIS {
+    /// Returns the [MeteoSeries] of the matching [GroundStation], if any.
+    ///
+    /// ```
+    /// use doris_rs::prelude::*;
+    ///
+    /// let doris = DORIS::from_gzip_file("data/DOR/V3/cs2rx18164.gz")
+    ///     .unwrap();
+    ///
+    /// let toulouse = doris.station_meteo(&Matcher::Label("TLSB"))
+    ///     .unwrap();
+    ///
+    /// for sample in toulouse.samples.iter() {
+    ///     if sample.is_physical() {
+    ///         let e_hpa = sample.water_vapour_pressure();
+    ///     }
+    /// }
+    ///
+    /// // interpolate at any epoch
+    /// let t = toulouse.samples[0].epoch + Duration::from_seconds(1.0);
+    /// let sample = toulouse.interpolate(t);
+    /// ```
+    pub fn station_meteo(&self, matcher: &Matcher) -> Option<MeteoSeries> {
+        self.record.station_meteo(matcher)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::{Duration, Frequency};
+    use crate::tests::toolkit::{synthetic_record, synthetic_t0};
+
+    #[test]
+    fn station_meteo() {
+        let station = GroundStation::default()
+            .with_site_label("TLSB")
+            .with_unique_id(13);
+
+        let samples = (0..3)
+            .map(|i| {
+                let t = i as f64 * 10.0;
+                (
+                    t,
+                    vec![
+                        (Observable::PseudoRange(Frequency::DORIS1), 1.0E6),
+                        (Observable::Pressure, 1000.0 + t),
+                        (Observable::Temperature, 20.0),
+                        (Observable::HumidityRate, if i == 2 { 120.0 } else { 50.0 }),
+                    ],
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let record = synthetic_record(&station, &samples);
+
+        assert!(record.station_meteo(&Matcher::Label("GR4B")).is_none());
+
+        let mut series = record.station_meteo(&Matcher::Label("tlsb")).unwrap();
+        assert_eq!(series.samples.len(), 3);
+
+        let t0 = synthetic_t0();
+
+        let interpolated = series
+            .interpolate(t0 + Duration::from_seconds(5.0))
+            .unwrap();
+
+        assert_eq!(interpolated.pressure, 1005.0);
+        assert_eq!(interpolated.temperature, 20.0);
+        assert_eq!(interpolated.humidity, 50.0);
+
+        assert!((interpolated.water_vapour_pressure() - 11.69).abs() < 1.0E-2);
+
+        assert!(series
+            .interpolate(t0 - Duration::from_seconds(1.0))
+            .is_none());
+        assert!(series
+            .interpolate(t0 + Duration::from_seconds(21.0))
+            .is_none());
+
+        assert_eq!(series.non_physical_iter().count(), 1);
+        series.retain_physical_mut();
+        assert_eq!(series.samples.len(), 2);
+    }
+}
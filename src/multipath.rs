@@ -0,0 +1,299 @@
+//! Code minus carrier multipath and code noise analysis
+use std::collections::BTreeMap;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    prelude::{Duration, Epoch, Frequency, GroundStation, Observable, Pass, Record, StationHandle},
+    statistics::{mean, rms},
+};
+
+/// Code minus carrier observations of one [GroundStation] [Pass],
+/// on one [Frequency]. The ionospheric term is removed using
+/// the dual frequency phase observations.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PassMultipath {
+    /// [Pass] being analyzed
+    pub pass: Pass,
+
+    /// [Frequency] of the code observation
+    pub frequency: Frequency,
+
+    /// Code minus carrier ([Epoch], value) serie, in meters,
+    /// with the pass average (phase ambiguity and hardware biases) removed.
+    pub samples: Vec<(Epoch, f64)>,
+}
+
+impl PassMultipath {
+    /// Returns the multipath RMS, in meters
+    pub fn multipath_rms(&self) -> f64 {
+        let values = self.samples.iter().map(|(_, v)| *v).collect::<Vec<_>>();
+        rms(&values).unwrap_or_default()
+    }
+
+    /// Returns code noise estimate, in meters, deduced from the
+    /// epoch to epoch differences, which cancel the slowly varying multipath.
+    pub fn code_noise(&self) -> f64 {
+        let differences = self.differences();
+        rms(&differences).unwrap_or_default() / 2.0_f64.sqrt()
+    }
+
+    fn differences(&self) -> Vec<f64> {
+        self.samples
+            .iter()
+            .zip(self.samples.iter().skip(1))
+            .map(|((_, v_1), (_, v_2))| v_2 - v_1)
+            .collect()
+    }
+}
+
+/// [MultipathStats] summarizes the code minus carrier analysis
+/// of several [PassMultipath]s.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MultipathStats {
+    /// Number of [Pass]es
+    pub passes: usize,
+
+    /// Number of samples
+    pub samples: usize,
+
+    /// Multipath RMS, in meters
+    pub multipath_rms: f64,
+
+    /// Code noise, in meters
+    pub code_noise: f64,
+}
+
+impl MultipathStats {
+    fn from_passes<'a>(passes: impl Iterator<Item = &'a PassMultipath>) -> Self {
+        let mut values = Vec::new();
+        let mut differences = Vec::new();
+        let mut num_passes = 0;
+
+        for pass in passes {
+            num_passes += 1;
+            values.extend(pass.samples.iter().map(|(_, v)| *v));
+            differences.extend(pass.differences());
+        }
+
+        Self {
+            passes: num_passes,
+            samples: values.len(),
+            multipath_rms: rms(&values).unwrap_or_default(),
+            code_noise: rms(&differences).unwrap_or_default() / 2.0_f64.sqrt(),
+        }
+    }
+}
+
+/// [MultipathAnalysis] computes the ionosphere free code minus carrier
+/// combination, per [GroundStation] [Pass] and [Frequency], using
+/// [Observable::PseudoRange] and [Observable::UnambiguousPhaseRange] observations.
+///
+/// ```
+/// use doris_rs::prelude::*;
+///
+/// let doris = DORIS::from_gzip_file("data/DOR/V3/cs2rx18164.gz")
+///     .unwrap();
+///
+/// let analysis = MultipathAnalysis::new(&doris.record, Duration::from_seconds(600.0));
+///
+/// for (station, frequencies) in analysis.station_summary() {
+///     for (frequency, stats) in frequencies {
+///         println!("{} ({}): MP={:.3}m noise={:.3}m",
+///             station.label, frequency, stats.multipath_rms, stats.code_noise);
+///     }
+/// }
+///
+/// // degraded beacons will stand out per generation
+/// let per_revision = analysis.beacon_revision_summary();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MultipathAnalysis {
+    /// [PassMultipath] for each [Pass] and [Frequency]
+    pub passes: Vec<PassMultipath>,
+}
+
+const MIN_PASS_SAMPLES: usize = 2;
+
+impl MultipathAnalysis {
+    /// Runs the code minus carrier analysis on this [Record].
+    /// A new [Pass] starts when a station has not been observed for longer than `max_gap`.
+    pub fn new(record: &Record, max_gap: Duration) -> Self {
+        let l1 = Frequency::DORIS1.frequency_hz();
+        let l2 = Frequency::DORIS2.frequency_hz();
+        let alpha = (l1 / l2).powi(2);
+
+        let series = record.station_observable_series();
+        let mut passes = Vec::new();
+
+        for pass in record.station_passes(max_gap) {
+            let serie = |observable: Observable| {
+                series
+                    .get(&(StationHandle::from(&pass.station), observable))
+                    .map(|serie| {
+                        serie
+                            .iter()
+                            .filter(|(t, _)| pass.contains(*t))
+                            .copied()
+                            .collect::<BTreeMap<_, _>>()
+                    })
+                    .unwrap_or_default()
+            };
+
+            let phase_1 = serie(Observable::UnambiguousPhaseRange(Frequency::DORIS1));
+            let phase_2 = serie(Observable::UnambiguousPhaseRange(Frequency::DORIS2));
+
+            for (frequency, phase, iono_factor) in [
+                (Frequency::DORIS1, &phase_1, 2.0 / (alpha - 1.0)),
+                (Frequency::DORIS2, &phase_2, 2.0 * alpha / (alpha - 1.0)),
+            ] {
+                let code = serie(Observable::PseudoRange(frequency));
+
+                let mut samples = code
+                    .iter()
+                    .filter_map(|(t, code)| {
+                        let (l_1, l_2) = (phase_1.get(t)?, phase_2.get(t)?);
+                        let phase = phase.get(t)?;
+                        Some((*t, code - phase - iono_factor * (l_1 - l_2)))
+                    })
+                    .collect::<Vec<_>>();
+
+                if samples.len() < MIN_PASS_SAMPLES {
+                    continue;
+                }
+
+                let values = samples.iter().map(|(_, v)| *v).collect::<Vec<_>>();
+                let average = mean(&values).unwrap_or_default();
+
+                for (_, value) in samples.iter_mut() {
+                    *value -= average;
+                }
+
+                passes.push(PassMultipath {
+                    pass: pass.clone(),
+                    frequency,
+                    samples,
+                });
+            }
+        }
+
+        Self { passes }
+    }
+
+    /// Summarizes this analysis per [GroundStation] (beacon) and [Frequency]
+    pub fn station_summary(&self) -> BTreeMap<GroundStation, BTreeMap<Frequency, MultipathStats>> {
+        let mut summary = BTreeMap::<GroundStation, BTreeMap<Frequency, MultipathStats>>::new();
+
+        for pass in self.passes.iter() {
+            summary
+                .entry(pass.pass.station.clone())
+                .or_default()
+                .entry(pass.frequency)
+                .or_insert_with(|| {
+                    MultipathStats::from_passes(self.passes.iter().filter(|p| {
+                        p.pass.station == pass.pass.station && p.frequency == pass.frequency
+                    }))
+                });
+        }
+
+        summary
+    }
+
+    /// Summarizes this analysis per DORIS beacon revision (generation) and [Frequency]
+    pub fn beacon_revision_summary(&self) -> BTreeMap<u8, BTreeMap<Frequency, MultipathStats>> {
+        let mut summary = BTreeMap::<u8, BTreeMap<Frequency, MultipathStats>>::new();
+
+        for pass in self.passes.iter() {
+            let revision = pass.pass.station.beacon_revision;
+
+            summary
+                .entry(revision)
+                .or_default()
+                .entry(pass.frequency)
+                .or_insert_with(|| {
+                    MultipathStats::from_passes(self.passes.iter().filter(|p| {
+                        p.pass.station.beacon_revision == revision && p.frequency == pass.frequency
+                    }))
+                });
+        }
+
+        summary
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tests::toolkit::{add_synthetic_samples, synthetic_record};
+
+    #[test]
+    fn code_minus_carrier() {
+        // nominal DORIS carriers: 2036.25 MHz and 401.25 MHz
+        let alpha = (2036.25_f64 / 401.25).powi(2);
+        assert!((alpha - 25.7533).abs() < 1.0E-4);
+
+        let l1 = Observable::UnambiguousPhaseRange(Frequency::DORIS1);
+        let l2 = Observable::UnambiguousPhaseRange(Frequency::DORIS2);
+        let c1 = Observable::PseudoRange(Frequency::DORIS1);
+        let c2 = Observable::PseudoRange(Frequency::DORIS2);
+
+        let samples = |multipath: f64| {
+            (0..20)
+                .map(|i| {
+                    let t = i as f64 * 10.0;
+                    let range = 1.0E6 + 5.0E3 * t;
+                    let iono = 2.0 + 0.01 * t;
+                    let mp = multipath * (-1.0_f64).powi(i);
+
+                    (
+                        t,
+                        vec![
+                            (l1, range - iono + 10.0),
+                            (l2, range - alpha * iono - 20.0),
+                            (c1, range + iono + mp),
+                            (c2, range + alpha * iono),
+                        ],
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let toulouse = GroundStation::default()
+            .with_site_label("TLSB")
+            .with_unique_id(13)
+            .with_beacon_revision(3);
+
+        let grasse = GroundStation::default()
+            .with_site_label("GR4B")
+            .with_unique_id(12)
+            .with_beacon_revision(2);
+
+        let mut record = synthetic_record(&toulouse, &samples(0.5));
+        add_synthetic_samples(&mut record, &grasse, &samples(0.0));
+
+        let analysis = MultipathAnalysis::new(&record, Duration::from_seconds(600.0));
+        assert_eq!(analysis.passes.len(), 4);
+
+        let summary = analysis.station_summary();
+
+        let stats = summary[&toulouse][&Frequency::DORIS1];
+        assert_eq!(stats.passes, 1);
+        assert_eq!(stats.samples, 20);
+        assert!((stats.multipath_rms - 0.5).abs() < 1.0E-6);
+        assert!((stats.code_noise - 2.0_f64.sqrt() * 0.5).abs() < 1.0E-6);
+
+        for frequency in [Frequency::DORIS1, Frequency::DORIS2] {
+            let stats = summary[&grasse][&frequency];
+            assert!(stats.multipath_rms < 1.0E-6);
+        }
+
+        let summary = analysis.beacon_revision_summary();
+        assert_eq!(summary.len(), 2);
+        assert!(summary[&2][&Frequency::DORIS1].multipath_rms < 1.0E-6);
+        assert!((summary[&3][&Frequency::DORIS1].multipath_rms - 0.5).abs() < 1.0E-6);
+    }
+}
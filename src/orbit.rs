@@ -0,0 +1,637 @@
+//! SP3 precise orbits and satellite to ground station geometry
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    io::{BufRead, BufReader, Read},
+    path::Path,
+};
+
+#[cfg(feature = "flate2")]
+use flate2::read::GzDecoder;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(doc)]
+use crate::prelude::GroundStation;
+
+use crate::{
+    constants::SPEED_OF_LIGHT_M_S,
+    prelude::{
+        Duration, Epoch, Header, ParsingError, Record, StationCoordinates, StationHandle,
+        TimeScale, DORIS,
+    },
+};
+
+/// Earth rotation rate, in rad/s
+const EARTH_ROTATION_RAD_S: f64 = 7.2921151467E-5;
+
+/// Number of samples used in the Lagrange interpolation (order 9)
+const INTERPOLATION_SAMPLES: usize = 10;
+
+/// [OrbitState] is the satellite state, in the terrestrial (ECEF) frame
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OrbitState {
+    /// [Epoch] of this state
+    pub epoch: Epoch,
+
+    /// ECEF (x, y, z) position, in meters
+    pub position_m: (f64, f64, f64),
+
+    /// ECEF (x, y, z) velocity, in m/s, when known
+    pub velocity_m_s: Option<(f64, f64, f64)>,
+}
+
+/// [Orbit] of one satellite, as a chronological list of [OrbitState]s
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Orbit {
+    /// SP3 satellite identifier
+    pub satellite: String,
+
+    /// [OrbitState]s, in chronological order
+    pub states: Vec<OrbitState>,
+}
+
+/// Lagrange polynomial evaluation at `t`
+fn lagrange(x: &[f64], y: &[f64], t: f64) -> f64 {
+    let mut value = 0.0;
+
+    for (j, (x_j, y_j)) in x.iter().zip(y.iter()).enumerate() {
+        let mut l_j = 1.0;
+
+        for (m, x_m) in x.iter().enumerate() {
+            if m != j {
+                l_j *= (t - x_m) / (x_j - x_m);
+            }
+        }
+
+        value += y_j * l_j;
+    }
+
+    value
+}
+
+impl Orbit {
+    /// Interpolates the [OrbitState] at desired [Epoch], using Lagrange interpolation
+    /// (order 9). Returns None if [Epoch] lies outside this [Orbit].
+    /// When velocities are not described, they are deduced from the position polynomial.
+    pub fn interpolate(&self, epoch: Epoch) -> Option<OrbitState> {
+        let first = self.states.first()?;
+        let last = self.states.last()?;
+
+        if epoch < first.epoch || epoch > last.epoch || self.states.len() < 2 {
+            return None;
+        }
+
+        let index = self.states.partition_point(|state| state.epoch < epoch);
+
+        let size = INTERPOLATION_SAMPLES.min(self.states.len());
+        let start = index.saturating_sub(size / 2).min(self.states.len() - size);
+
+        let window = &self.states[start..start + size];
+
+        let x = window
+            .iter()
+            .map(|state| (state.epoch - first.epoch).to_seconds())
+            .collect::<Vec<_>>();
+
+        let t = (epoch - first.epoch).to_seconds();
+
+        let interpolate = |t: f64, coordinate: fn(&OrbitState) -> Option<f64>| {
+            let y = window.iter().filter_map(coordinate).collect::<Vec<_>>();
+            if y.len() == x.len() {
+                Some(lagrange(&x, &y, t))
+            } else {
+                None
+            }
+        };
+
+        let position = |t: f64| {
+            Some((
+                interpolate(t, |state| Some(state.position_m.0))?,
+                interpolate(t, |state| Some(state.position_m.1))?,
+                interpolate(t, |state| Some(state.position_m.2))?,
+            ))
+        };
+
+        let position_m = position(t)?;
+
+        let velocity = match (
+            interpolate(t, |state| state.velocity_m_s.map(|v| v.0)),
+            interpolate(t, |state| state.velocity_m_s.map(|v| v.1)),
+            interpolate(t, |state| state.velocity_m_s.map(|v| v.2)),
+        ) {
+            (Some(vx), Some(vy), Some(vz)) => (vx, vy, vz),
+            _ => {
+                let (before, after) = (position(t - 0.5)?, position(t + 0.5)?);
+                (after.0 - before.0, after.1 - before.1, after.2 - before.2)
+            },
+        };
+
+        Some(OrbitState {
+            epoch,
+            position_m,
+            velocity_m_s: Some(velocity),
+        })
+    }
+
+    /// Returns the [Geometry] between this satellite and [StationCoordinates],
+    /// at desired reception [Epoch]. See [Geometry::new].
+    pub fn geometry(&self, epoch: Epoch, station: &StationCoordinates) -> Option<Geometry> {
+        let state = self.interpolate(epoch)?;
+        Some(Geometry::new(station, &state))
+    }
+}
+
+/// [Geometry] of a satellite to ground station link
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Geometry {
+    /// Geometric range, in meters
+    pub range_m: f64,
+
+    /// Elevation angle of the satellite, in degrees
+    pub elevation_deg: f64,
+
+    /// Azimuth angle of the satellite (clockwise from north), in degrees
+    pub azimuth_deg: f64,
+
+    /// Expected range rate, in m/s (positive when receding)
+    pub range_rate_m_s: f64,
+}
+
+impl Geometry {
+    /// Computes the [Geometry] between [StationCoordinates] (beacon)
+    /// and satellite [OrbitState] (receiver).
+    /// The light time of the uplink signal, including the Earth rotation
+    /// during propagation, is accounted for.
+    pub fn new(station: &StationCoordinates, state: &OrbitState) -> Self {
+        let (x_s, y_s, z_s) = state.position_m;
+        let (vx_s, vy_s, vz_s) = state.velocity_m_s.unwrap_or_default();
+
+        let (x_r, y_r, z_r) = station.position_m;
+
+        let mut rho = (x_s - x_r, y_s - y_r, z_s - z_r);
+        let mut range_m = (rho.0.powi(2) + rho.1.powi(2) + rho.2.powi(2)).sqrt();
+
+        for _ in 0..2 {
+            let theta = EARTH_ROTATION_RAD_S * range_m / SPEED_OF_LIGHT_M_S;
+            let (sin_t, cos_t) = theta.sin_cos();
+
+            // station position at emission, in the frame of reception
+            let x_e = cos_t * x_r + sin_t * y_r;
+            let y_e = -sin_t * x_r + cos_t * y_r;
+
+            rho = (x_s - x_e, y_s - y_e, z_s - z_r);
+            range_m = (rho.0.powi(2) + rho.1.powi(2) + rho.2.powi(2)).sqrt();
+        }
+
+        let range_rate_m_s = (rho.0 * vx_s + rho.1 * vy_s + rho.2 * vz_s) / range_m;
+
+        let (latitude, longitude, _) = station.geodetic();
+        let (sin_lat, cos_lat) = latitude.to_radians().sin_cos();
+        let (sin_lon, cos_lon) = longitude.to_radians().sin_cos();
+
+        let east = -sin_lon * rho.0 + cos_lon * rho.1;
+        let north = -sin_lat * cos_lon * rho.0 - sin_lat * sin_lon * rho.1 + cos_lat * rho.2;
+        let up = cos_lat * cos_lon * rho.0 + cos_lat * sin_lon * rho.1 + sin_lat * rho.2;
+
+        let elevation_deg = (up / range_m).asin().to_degrees();
+        let azimuth_deg = east.atan2(north).to_degrees().rem_euclid(360.0);
+
+        Self {
+            range_m,
+            elevation_deg,
+            azimuth_deg,
+            range_rate_m_s,
+        }
+    }
+}
+
+/// [SP3] precise orbit file (revision c or d).
+/// Clock estimates are not interpreted.
+///
+/// ```no_run
+/// use doris_rs::prelude::*;
+///
+/// let sinex = Sinex::from_gzip_file("data/SNX/dpod2020_031.snx.gz")
+///     .unwrap();
+///
+/// let sp3 = SP3::from_gzip_file("data/SP3/ssacs220.b18164.e18165.DG_.sp3.001.gz")
+///     .unwrap();
+///
+/// // coordinates propagated to the beginning of the day
+/// let t0 = Epoch::from_gregorian_utc_at_midnight(2018, 6, 13);
+///
+/// let doris = DORIS::from_gzip_file("data/DOR/V3/cs2rx18164.gz")
+///     .unwrap()
+///     .with_station_coordinates(&sinex, t0);
+///
+/// // the DORIS satellite is identified from the file header
+/// let geometry = doris.observation_geometry(&sp3)
+///     .unwrap();
+///
+/// for ((epoch, station), geometry) in geometry.iter() {
+///     println!("{} {}: elev={:.1}°", epoch, station.label, geometry.elevation_deg);
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SP3 {
+    /// [TimeScale] in which the [OrbitState]s are expressed
+    pub time_scale: TimeScale,
+
+    /// Satellite identifiers, as declared in the header
+    pub satellites: Vec<String>,
+
+    /// Comments found in this file
+    pub comments: Vec<String>,
+
+    /// [Orbit] of each satellite
+    pub orbits: HashMap<String, Orbit>,
+}
+
+/// Parses the SP3 time system descriptor
+fn parse_time_scale(content: &str) -> TimeScale {
+    match content.trim() {
+        "TAI" => TimeScale::TAI,
+        "UTC" => TimeScale::UTC,
+        "GAL" => TimeScale::GST,
+        "BDT" => TimeScale::BDT,
+        "QZS" => TimeScale::QZSST,
+        _ => TimeScale::GPST,
+    }
+}
+
+/// Parses the SP3 "*  YYYY MM DD HH MM SS.SSSSSSSS" epoch descriptor
+fn parse_sp3_epoch(content: &str, time_scale: TimeScale) -> Result<Epoch, ParsingError> {
+    let items = content.split_whitespace().collect::<Vec<_>>();
+
+    if items.len() < 6 {
+        return Err(ParsingError::SP3Format);
+    }
+
+    let mut values = [0_u32; 5];
+
+    for (value, item) in values.iter_mut().zip(items.iter()) {
+        *value = item.parse::<u32>().map_err(|_| ParsingError::SP3Format)?;
+    }
+
+    let seconds = items[5]
+        .parse::<f64>()
+        .map_err(|_| ParsingError::SP3Format)?;
+
+    let epoch = Epoch::maybe_from_gregorian(
+        values[0] as i32,
+        values[1] as u8,
+        values[2] as u8,
+        values[3] as u8,
+        values[4] as u8,
+        seconds.floor() as u8,
+        0,
+        time_scale,
+    )?;
+
+    // the rounded fraction may carry over to the next second
+    let nanos = (seconds.fract() * 1.0E9).round() as i64;
+
+    Ok(epoch + Duration::from_truncated_nanoseconds(nanos))
+}
+
+/// Parses the 3 coordinates of an SP3 P/V record
+fn parse_sp3_vector(content: &str, scaling: f64) -> Result<(f64, f64, f64), ParsingError> {
+    let mut items = content.split_whitespace().map(|item| {
+        item.parse::<f64>()
+            .map(|value| value * scaling)
+            .map_err(|_| ParsingError::SP3Format)
+    });
+
+    match (items.next(), items.next(), items.next()) {
+        (Some(x), Some(y), Some(z)) => Ok((x?, y?, z?)),
+        _ => Err(ParsingError::SP3Format),
+    }
+}
+
+impl SP3 {
+    /// Parses [SP3] content by consuming [BufReader] (efficient buffered reader).
+    pub fn parse<R: Read>(reader: &mut BufReader<R>) -> Result<Self, ParsingError> {
+        let mut sp3 = Self::default();
+
+        let mut time_system_parsed = false;
+        let mut epoch = Option::<Epoch>::None;
+
+        for (nth, line) in reader.lines().enumerate() {
+            let line = line?;
+
+            if nth == 0 {
+                if !line.starts_with("#c") && !line.starts_with("#d") {
+                    return Err(ParsingError::SP3Format);
+                }
+                continue;
+            }
+
+            if line.starts_with("EOF") {
+                break;
+            }
+
+            if let Some(comment) = line.strip_prefix("/*") {
+                sp3.comments.push(comment.trim().to_string());
+            } else if line.starts_with("+ ") {
+                let ids = line.get(9..).unwrap_or_default();
+
+                for id in ids.as_bytes().chunks(3) {
+                    let id = String::from_utf8_lossy(id).trim().to_string();
+                    if id.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                        sp3.satellites.push(id);
+                    }
+                }
+            } else if line.starts_with("%c") {
+                if !time_system_parsed {
+                    sp3.time_scale = parse_time_scale(line.get(9..12).unwrap_or_default());
+                    time_system_parsed = true;
+                }
+            } else if let Some(content) = line.strip_prefix('*') {
+                epoch = Some(parse_sp3_epoch(content, sp3.time_scale)?);
+            } else if line.starts_with('P') || line.starts_with('V') {
+                let epoch = epoch.ok_or(ParsingError::SP3Format)?;
+
+                if line.len() < 4 {
+                    return Err(ParsingError::SP3Format);
+                }
+
+                let satellite = line[1..4].trim().to_string();
+
+                let orbit = sp3.orbits.entry(satellite.clone()).or_insert(Orbit {
+                    satellite,
+                    states: Vec::new(),
+                });
+
+                if line.starts_with('P') {
+                    // km
+                    let position_m = parse_sp3_vector(&line[4..], 1.0E3)?;
+
+                    orbit.states.push(OrbitState {
+                        epoch,
+                        position_m,
+                        velocity_m_s: None,
+                    });
+                } else {
+                    // dm/s
+                    let velocity = parse_sp3_vector(&line[4..], 1.0E-1)?;
+
+                    if let Some(state) =
+                        orbit.states.last_mut().filter(|state| state.epoch == epoch)
+                    {
+                        state.velocity_m_s = Some(velocity);
+                    }
+                }
+            }
+        }
+
+        Ok(sp3)
+    }
+
+    /// Parses [SP3] from local readable file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ParsingError> {
+        let fd = File::open(path)?;
+        let mut reader = BufReader::new(fd);
+        Self::parse(&mut reader)
+    }
+
+    /// Parses [SP3] from local gzip compressed file.
+    #[cfg(feature = "flate2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "flate2")))]
+    pub fn from_gzip_file<P: AsRef<Path>>(path: P) -> Result<Self, ParsingError> {
+        let fd = File::open(path)?;
+        let reader = GzDecoder::new(fd);
+        let mut reader = BufReader::new(reader);
+        Self::parse(&mut reader)
+    }
+
+    /// Returns the [Orbit] of this satellite identifier
+    pub fn orbit(&self, satellite: &str) -> Option<&Orbit> {
+        self.orbits.get(satellite)
+    }
+
+    /// Identifies the [Orbit] of the DORIS satellite described by this [Header].
+    /// Single satellite files apply directly, otherwise the satellite is identified
+    /// from the comments that mention its name or COSPAR number.
+    pub fn header_orbit(&self, header: &Header) -> Option<&Orbit> {
+        if self.satellites.len() == 1 {
+            return self.orbit(&self.satellites[0]);
+        }
+
+        let name = header.satellite.to_uppercase();
+        let cospar = header.cospar.as_ref().map(|cospar| cospar.to_string());
+
+        self.comments
+            .iter()
+            .filter(|comment| {
+                let comment = comment.to_uppercase();
+                (!name.is_empty() && comment.contains(&name))
+                    || cospar
+                        .as_ref()
+                        .is_some_and(|cospar| comment.contains(cospar))
+            })
+            .find_map(|comment| {
+                comment
+                    .split_whitespace()
+                    .find(|item| self.satellites.iter().any(|sat| sat == item))
+                    .and_then(|satellite| self.orbit(satellite))
+            })
+    }
+}
+
+impl Record {
+    /// Returns the [Geometry] of each ([Epoch], [GroundStation]) observation of this [Record],
+    /// using this [Orbit]. Stations must be described by their [StationCoordinates].
+    /// The orbit is interpolated at the clock corrected [Epoch], see
+    /// [crate::prelude::Measurements::clock_corrected_epoch].
+    pub fn observation_geometry(
+        &self,
+        orbit: &Orbit,
+    ) -> BTreeMap<(Epoch, StationHandle), Geometry> {
+        let mut geometry = BTreeMap::new();
+
+        for (key, measurements) in self.measurements.iter() {
+            let epoch = measurements.clock_corrected_epoch(key.epoch);
+
+            let Some(state) = orbit.interpolate(epoch) else {
+                continue;
+            };
+
+            for obs_key in measurements.observations.keys() {
+                if geometry.contains_key(&(key.epoch, obs_key.station.clone())) {
+                    continue;
+                }
+
+                if let Some(coordinates) = &obs_key.station.coordinates {
+                    geometry.insert(
+                        (key.epoch, obs_key.station.clone()),
+                        Geometry::new(coordinates, &state),
+                    );
+                }
+            }
+        }
+
+        geometry
+    }
+}
+
+impl DORIS {
+    /// Returns the [Geometry] of each ([Epoch], [GroundStation]) observation,
+    /// using the [Orbit] of this DORIS satellite, see [SP3::header_orbit].
+    /// Returns None if this satellite is not described by the [SP3] file.
+    pub fn observation_geometry(
+        &self,
+        sp3: &SP3,
+    ) -> Option<BTreeMap<(Epoch, StationHandle), Geometry>> {
+        let orbit = sp3.header_orbit(&self.header)?;
+        Some(self.record.observation_geometry(orbit))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::{Duration, Frequency, GroundStation, Observable};
+    use crate::tests::toolkit::{add_synthetic_samples, synthetic_record, synthetic_t0};
+
+    /// Circular equatorial orbit, in the ECEF frame
+    fn circular_orbit(t: f64) -> (f64, f64, f64) {
+        let (radius, rate) = (7.0E6, 1.0E-3);
+        (radius * (rate * t).cos(), radius * (rate * t).sin(), 0.0)
+    }
+
+    #[test]
+    fn sp3_epoch_parsing() {
+        let epoch = parse_sp3_epoch("2018  6 13  0  0  0.50000000", TimeScale::TAI).unwrap();
+        assert_eq!(epoch, synthetic_t0() + Duration::from_seconds(0.5));
+
+        let epoch = parse_sp3_epoch("2018  6 13  0  0 59.9999999996", TimeScale::TAI).unwrap();
+        assert_eq!(epoch, synthetic_t0() + Duration::from_seconds(60.0));
+
+        assert!(parse_sp3_epoch("2018  2 30  0  0  0.00000000", TimeScale::TAI).is_err());
+        assert!(parse_sp3_epoch("2018  6 13  0 61  0.00000000", TimeScale::TAI).is_err());
+    }
+
+    #[test]
+    fn sp3_orbit_geometry() {
+        let mut content = String::from(
+            "#dP2018  6 13  0  0  0.00000000      10 ORBIT IGS14 HLM  IDS
+## 2005 259200.00000000    60.00000000 58282 0.0000000000000
++    2   L27L39  0  0  0  0  0  0  0  0  0  0  0  0  0  0  0
+%c L  cc TAI ccc cccc cccc cccc cccc ccccc ccccc ccccc ccccc
+/* CRYOSAT-2 L27
+/* JASON-3 L39
+",
+        );
+
+        for i in 0..10 {
+            let t = i as f64 * 60.0;
+            let (x, y, z) = circular_orbit(t);
+
+            content.push_str(&format!("*  2018  6 13  0 {:2}  0.00000000\n", i));
+
+            content.push_str(&format!(
+                "PL27{:14.6}{:14.6}{:14.6} 999999.999999\n",
+                x / 1.0E3,
+                y / 1.0E3,
+                z / 1.0E3
+            ));
+
+            content.push_str("PL39      0.000000      0.000000   7000.000000 999999.999999\n");
+        }
+
+        content.push_str("EOF\n");
+
+        let mut reader = BufReader::new(content.as_bytes());
+        let sp3 = SP3::parse(&mut reader).unwrap();
+
+        assert_eq!(sp3.time_scale, TimeScale::TAI);
+        assert_eq!(sp3.satellites, vec!["L27".to_string(), "L39".to_string()]);
+
+        let header = Header::default();
+        assert!(sp3.header_orbit(&header).is_none());
+
+        let mut header = header;
+        header.satellite = "CRYOSAT-2".to_string();
+
+        let orbit = sp3.header_orbit(&header).unwrap();
+        assert_eq!(orbit.satellite, "L27");
+        assert_eq!(orbit.states.len(), 10);
+
+        let t0 = synthetic_t0();
+
+        assert!(orbit
+            .interpolate(t0 - Duration::from_seconds(1.0))
+            .is_none());
+
+        let state = orbit
+            .interpolate(t0 + Duration::from_seconds(150.0))
+            .unwrap();
+        let (x, y, _) = circular_orbit(150.0);
+
+        assert!((state.position_m.0 - x).abs() < 1.0E-3);
+        assert!((state.position_m.1 - y).abs() < 1.0E-3);
+
+        let (vx, vy, _) = state.velocity_m_s.unwrap();
+        assert!((vx - (-7.0E3 * (0.15_f64).sin())).abs() < 1.0E-3);
+        assert!((vy - 7.0E3 * (0.15_f64).cos()).abs() < 1.0E-3);
+
+        // station right below the satellite, on the equator
+        let below = StationCoordinates {
+            epoch: t0,
+            position_m: (6378137.0, 0.0, 0.0),
+            velocity_m_yr: (0.0, 0.0, 0.0),
+        };
+
+        let geometry = orbit.geometry(t0, &below).unwrap();
+        assert!((geometry.range_m - 621863.0).abs() < 1.0);
+        assert!(geometry.elevation_deg > 89.9);
+        assert!(geometry.range_rate_m_s.abs() < 0.1);
+
+        // station west of the satellite: looking east
+        let (sin_lon, cos_lon) = (-10.0_f64).to_radians().sin_cos();
+
+        let west = StationCoordinates {
+            epoch: t0,
+            position_m: (6378137.0 * cos_lon, 6378137.0 * sin_lon, 0.0),
+            velocity_m_yr: (0.0, 0.0, 0.0),
+        };
+
+        let station = GroundStation::default()
+            .with_site_label("WEST")
+            .with_unique_id(1)
+            .with_coordinates(west);
+
+        let unknown = GroundStation::default()
+            .with_site_label("UNKN")
+            .with_unique_id(2);
+
+        let samples = [0.0, 30.0]
+            .into_iter()
+            .map(|t| (t, vec![(Observable::PseudoRange(Frequency::DORIS1), 1.0E6)]))
+            .collect::<Vec<_>>();
+
+        let mut record = synthetic_record(&station, &samples);
+        add_synthetic_samples(&mut record, &unknown, &samples);
+
+        let geometry = record.observation_geometry(orbit);
+        assert_eq!(geometry.len(), 2);
+
+        let west = geometry[&(t0, StationHandle::from(&station))];
+        assert!((west.azimuth_deg - 90.0).abs() < 1.0E-3);
+        assert!(west.elevation_deg > 0.0 && west.elevation_deg < 90.0);
+
+        // satellite moving away from this station
+        let later = geometry[&(
+            t0 + Duration::from_seconds(30.0),
+            StationHandle::from(station),
+        )];
+        assert!(later.range_m > west.range_m);
+        assert!(later.range_rate_m_s > 0.0);
+    }
+}
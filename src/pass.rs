@@ -0,0 +1,190 @@
+//! DORIS ground station passes
+use std::collections::HashMap;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::{Duration, Epoch, GroundStation, Record, StationHandle, DORIS};
+
+/// [Pass] describes one continuous visibility period of a [GroundStation],
+/// as seen from the DORIS satellite.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Pass {
+    /// [GroundStation] being observed
+    pub station: GroundStation,
+
+    /// [Epoch] of first observation
+    pub start: Epoch,
+
+    /// [Epoch] of last observation
+    pub end: Epoch,
+}
+
+impl Pass {
+    /// Total [Duration] of this [Pass]
+    pub fn duration(&self) -> Duration {
+        self.end - self.start
+    }
+
+    /// Returns true if [Epoch] lies within this [Pass]
+    pub fn contains(&self, epoch: Epoch) -> bool {
+        epoch >= self.start && epoch <= self.end
+    }
+
+    /// Splits this [Pass] at given [Epoch], which becomes the start
+    /// of the second [Pass]. The first [Pass] ends on the last observation
+    /// of this [GroundStation] in the [Record], strictly prior to [Epoch],
+    /// so each [Epoch] belongs to a single [Pass].
+    /// Returns None if [Epoch] does not strictly lie within.
+    pub fn split_at(&self, record: &Record, epoch: Epoch) -> Option<(Self, Self)> {
+        if epoch <= self.start || epoch > self.end {
+            return None;
+        }
+
+        let end = record
+            .measurements
+            .iter()
+            .filter(|(key, _)| key.epoch >= self.start && key.epoch < epoch)
+            .filter(|(_, measurements)| {
+                measurements
+                    .observations
+                    .keys()
+                    .any(|obs_key| *obs_key.station == self.station)
+            })
+            .map(|(key, _)| key.epoch)
+            .max()?;
+
+        Some((
+            Self {
+                station: self.station.clone(),
+                start: self.start,
+                end,
+            },
+            Self {
+                station: self.station.clone(),
+                start: epoch,
+                end: self.end,
+            },
+        ))
+    }
+}
+
+impl Record {
+    /// Returns all [Epoch]s at which each [GroundStation] was observed,
+    /// in chronological order.
+    pub(crate) fn station_epochs(&self) -> HashMap<StationHandle, Vec<Epoch>> {
+        let mut epochs = HashMap::<StationHandle, Vec<Epoch>>::new();
+
+        for (key, measurements) in self.measurements.iter() {
+            for obs_key in measurements.observations.keys() {
+                let station_epochs = epochs.entry(obs_key.station.clone()).or_default();
+
+                if station_epochs.last() != Some(&key.epoch) {
+                    station_epochs.push(key.epoch);
+                }
+            }
+        }
+
+        epochs
+    }
+
+    /// Segments this [Record] into [GroundStation] [Pass]es.
+    /// A new [Pass] starts each time a station has not been observed
+    /// for longer than `max_gap`. [Pass]es are sorted chronologically.
+    pub fn station_passes(&self, max_gap: Duration) -> Vec<Pass> {
+        let mut passes = Vec::new();
+
+        for (station, epochs) in self.station_epochs() {
+            let mut start = epochs[0];
+            let mut end = epochs[0];
+
+            for epoch in epochs.iter().skip(1) {
+                if *epoch - end > max_gap {
+                    passes.push(Pass {
+                        station: station.station().clone(),
+                        start,
+                        end,
+                    });
+
+                    start = *epoch;
+                }
+
+                end = *epoch;
+            }
+
+            passes.push(Pass {
+                station: station.station().clone(),
+                start,
+                end,
+            });
+        }
+
+        passes.sort_by(|a, b| (a.start, &a.station).cmp(&(b.start, &b.station)));
+        passes
+    }
+}
+
+impl DORIS {
+    /// Segments this [DORIS] [Record] into [GroundStation] [Pass]es.
+    /// See [Record::station_passes] for more information.
+    ///
+    /// ```
+    /// use doris_rs::prelude::*;
+    ///
+    /// let doris = DORIS::from_gzip_file("data/DOR/V3/cs2rx18164.gz")
+    ///     .unwrap();
+    ///
+    /// // a station is no longer visible after 10' without observations
+    /// let max_gap = Duration::from_seconds(600.0);
+    ///
+    /// for pass in doris.station_passes(max_gap) {
+    ///     assert!(pass.end >= pass.start);
+    /// }
+    /// ```
+    pub fn station_passes(&self, max_gap: Duration) -> Vec<Pass> {
+        self.record.station_passes(max_gap)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prelude::{Duration, Frequency, GroundStation, Observable};
+    use crate::tests::toolkit::synthetic_record;
+
+    #[test]
+    fn station_passes() {
+        let station = GroundStation::default()
+            .with_site_label("TLSB")
+            .with_unique_id(13);
+
+        let l1 = Observable::UnambiguousPhaseRange(Frequency::DORIS1);
+
+        // two passes: [0; 90] and [3600; 3660]
+        let samples = (0..10)
+            .map(|i| (i as f64 * 10.0, vec![(l1, 0.0)]))
+            .chain((0..7).map(|i| (3600.0 + i as f64 * 10.0, vec![(l1, 0.0)])))
+            .collect::<Vec<_>>();
+
+        let record = synthetic_record(&station, &samples);
+
+        let passes = record.station_passes(Duration::from_seconds(600.0));
+        assert_eq!(passes.len(), 2);
+
+        assert_eq!(passes[0].duration(), Duration::from_seconds(90.0));
+        assert_eq!(passes[1].duration(), Duration::from_seconds(60.0));
+
+        let split = passes[1].start + Duration::from_seconds(30.0);
+
+        let (first, second) = passes[1].split_at(&record, split).unwrap();
+
+        // first pass ends on the previous sample
+        assert_eq!(first.duration(), Duration::from_seconds(20.0));
+        assert_eq!(second.duration(), Duration::from_seconds(30.0));
+
+        assert!(!first.contains(split));
+        assert!(second.contains(split));
+
+        assert!(passes[0].split_at(&record, passes[0].start).is_none());
+    }
+}
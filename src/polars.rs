@@ -0,0 +1,526 @@
+//! Polars DataFrame support (requires the `polars` feature).
+use std::{collections::HashMap, str::FromStr};
+
+use itertools::Itertools;
+
+use ::polars::prelude::{
+    Column, DataFrame, DataType, Float64Type, Int64Type, PolarsNumericType, UInt16Type, UInt8Type,
+};
+
+use crate::{
+    prelude::{
+        ClockOffset, FormattingError, Header, Key, Measurements, Observable, Observation,
+        ParsingError, Record, TimeScale, DORIS, SNR,
+    },
+    record::columns::{
+        duration_from_seconds, epoch_flag, epoch_flag_value, epoch_from_tai_nanoseconds,
+        phase_flag, phase_flag_value, snr_value, tai_nanoseconds, wide_observables,
+        StationResolver, CLOCK_EXTRAPOLATED_COLUMN, CLOCK_OFFSET_COLUMN, EPOCH_COLUMN, FLAG_COLUMN,
+        OBSERVABLE_COLUMN, PHASE_FLAG_COLUMN, PHASE_FLAG_SUFFIX, SNR_COLUMN, SNR_SUFFIX,
+        STATION_CODE_COLUMN, STATION_DOMES_COLUMN, STATION_LABEL_COLUMN, VALUE_COLUMN,
+    },
+};
+
+/// Columns of the wide layout that do not describe an [Observable]
+const WIDE_COLUMNS: [&str; 7] = [
+    EPOCH_COLUMN,
+    FLAG_COLUMN,
+    STATION_CODE_COLUMN,
+    STATION_LABEL_COLUMN,
+    STATION_DOMES_COLUMN,
+    CLOCK_OFFSET_COLUMN,
+    CLOCK_EXTRAPOLATED_COLUMN,
+];
+
+/// Returns the values of this numerical column, cast to the expected type
+fn numeric_column<T: PolarsNumericType>(
+    dataframe: &DataFrame,
+    name: &str,
+) -> Result<Vec<Option<T::Native>>, ParsingError> {
+    let column = dataframe.column(name)?.cast(&T::get_dtype())?;
+    let values = column.as_materialized_series().unpack::<T>()?;
+    Ok(values.into_iter().collect())
+}
+
+/// Returns the values of this boolean column
+fn boolean_column(dataframe: &DataFrame, name: &str) -> Result<Vec<Option<bool>>, ParsingError> {
+    let column = dataframe.column(name)?.cast(&DataType::Boolean)?;
+    Ok(column.bool()?.into_iter().collect())
+}
+
+/// Returns the values of this string column
+fn string_column<'a>(
+    dataframe: &'a DataFrame,
+    name: &str,
+) -> Result<Vec<Option<&'a str>>, ParsingError> {
+    Ok(dataframe.column(name)?.str()?.into_iter().collect())
+}
+
+/// Returns the values of this optional numerical column, when it exists
+fn optional_numeric_column<T: PolarsNumericType>(
+    dataframe: &DataFrame,
+    name: &str,
+) -> Result<Option<Vec<Option<T::Native>>>, ParsingError> {
+    if dataframe.get_column_index(name).is_some() {
+        Ok(Some(numeric_column::<T>(dataframe, name)?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Columns shared by the long and wide layouts: one value per row
+#[derive(Default)]
+struct KeyColumns {
+    epochs: Vec<i64>,
+    flags: Vec<u8>,
+    codes: Vec<Option<u16>>,
+    labels: Vec<Option<String>>,
+    domes: Vec<Option<String>>,
+    clock_offsets: Vec<Option<f64>>,
+    extrapolated: Vec<Option<bool>>,
+}
+
+impl KeyColumns {
+    /// Appends one row, for this [Key] and [Measurements]
+    fn push(&mut self, key: &Key, measurements: &Measurements, station: Option<(u16, &str, &str)>) {
+        let clock_offset = measurements.satellite_clock_offset;
+
+        self.epochs.push(tai_nanoseconds(key.epoch));
+        self.flags.push(epoch_flag_value(key.flag));
+        self.codes.push(station.map(|(code, _, _)| code));
+        self.labels
+            .push(station.map(|(_, label, _)| label.to_string()));
+        self.domes
+            .push(station.map(|(_, _, domes)| domes.to_string()));
+        self.clock_offsets
+            .push(clock_offset.map(|clock| clock.offset.to_seconds()));
+        self.extrapolated
+            .push(clock_offset.map(|clock| clock.extrapolated));
+    }
+
+    /// Converts to [Column]s: epoch, flag, station (code, label, DOMES)
+    /// and the satellite clock columns.
+    fn into_columns(self) -> (Vec<Column>, Vec<Column>) {
+        let key = vec![
+            Column::new(EPOCH_COLUMN.into(), self.epochs),
+            Column::new(FLAG_COLUMN.into(), self.flags),
+            Column::new(STATION_CODE_COLUMN.into(), self.codes),
+            Column::new(STATION_LABEL_COLUMN.into(), self.labels),
+            Column::new(STATION_DOMES_COLUMN.into(), self.domes),
+        ];
+
+        let clock = vec![
+            Column::new(CLOCK_OFFSET_COLUMN.into(), self.clock_offsets),
+            Column::new(CLOCK_EXTRAPOLATED_COLUMN.into(), self.extrapolated),
+        ];
+
+        (key, clock)
+    }
+}
+
+impl DORIS {
+    /// Converts this [DORIS] record to Polars [DataFrame], in long layout:
+    /// one row per observation, with the same columns as the Arrow
+    /// representation (`epoch`, `flag`, `station_code`, `station_label`, `station_domes`,
+    /// `observable`, `value`, `snr`, `phase_flag`, `clock_offset`, `clock_extrapolated`).
+    /// Epochs are expressed as TAI nanoseconds (since J1900) and flags as RINEX values.
+    /// Epochs that do not contain any observation (like events) are described by a row
+    /// without station and observable. The satellite [ClockOffset] is repeated on each
+    /// row of the epoch. Use [Record::from_dataframe] to convert back.
+    /// Requires the `polars` feature.
+    ///
+    /// ```
+    /// use doris_rs::prelude::*;
+    ///
+    /// let doris = DORIS::from_gzip_file("data/DOR/V3/cs2rx18164.gz")
+    ///     .unwrap();
+    ///
+    /// let dataframe = doris.to_dataframe()
+    ///     .unwrap();
+    ///
+    /// let parsed = Record::from_dataframe(&dataframe, &doris.header)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(parsed, doris.record);
+    /// ```
+    pub fn to_dataframe(&self) -> Result<DataFrame, FormattingError> {
+        let mut keys = KeyColumns::default();
+        let mut observables = Vec::<Option<String>>::new();
+        let mut values = Vec::<Option<f64>>::new();
+        let mut snr = Vec::<Option<u8>>::new();
+        let mut phase_flags = Vec::<Option<u8>>::new();
+
+        for (key, measurements) in self.record.measurements.iter() {
+            if measurements.observations.is_empty() {
+                keys.push(key, measurements, None);
+                observables.push(None);
+                values.push(None);
+                snr.push(None);
+                phase_flags.push(None);
+            }
+
+            for (obs_key, observation) in measurements.observations.iter() {
+                let station = &obs_key.station;
+                let domes = station.domes.to_string();
+
+                keys.push(
+                    key,
+                    measurements,
+                    Some((station.code, &station.label, &domes)),
+                );
+
+                observables.push(Some(format!("{:x}", obs_key.observable)));
+                values.push(Some(observation.value));
+                snr.push(observation.snr.map(snr_value));
+                phase_flags.push(observation.phase_flag.map(phase_flag_value));
+            }
+        }
+
+        let (mut columns, clock) = keys.into_columns();
+
+        columns.push(Column::new(OBSERVABLE_COLUMN.into(), observables));
+        columns.push(Column::new(VALUE_COLUMN.into(), values));
+        columns.push(Column::new(SNR_COLUMN.into(), snr));
+        columns.push(Column::new(PHASE_FLAG_COLUMN.into(), phase_flags));
+        columns.extend(clock);
+
+        Ok(DataFrame::new(columns)?)
+    }
+
+    /// Converts this [DORIS] record to Polars [DataFrame], in wide layout:
+    /// one row per epoch and [GroundStation](crate::prelude::GroundStation),
+    /// and one column per [Observable], named after its RINEX code (for example `L1`).
+    /// Each [Observable] column is completed by its `<code>_snr` and `<code>_phase_flag`
+    /// columns. [Observable]s follow the [Header] order, followed by any other [Observable]
+    /// found in the record. Other columns are the same as [Self::to_dataframe].
+    /// Use [Record::from_dataframe] to convert back.
+    /// Requires the `polars` feature.
+    ///
+    /// ```
+    /// use doris_rs::prelude::*;
+    ///
+    /// let doris = DORIS::from_gzip_file("data/DOR/V3/cs2rx18164.gz")
+    ///     .unwrap();
+    ///
+    /// let dataframe = doris.to_wide_dataframe()
+    ///     .unwrap();
+    ///
+    /// // one column per observable
+    /// let l1 = dataframe.column("L1")
+    ///     .unwrap();
+    /// ```
+    pub fn to_wide_dataframe(&self) -> Result<DataFrame, FormattingError> {
+        let observables = wide_observables(&self.header, &self.record);
+
+        let positions = observables
+            .iter()
+            .enumerate()
+            .map(|(position, observable)| (*observable, position))
+            .collect::<HashMap<_, _>>();
+
+        let mut keys = KeyColumns::default();
+        let mut values = vec![Vec::<Option<f64>>::new(); observables.len()];
+        let mut snr = vec![Vec::<Option<u8>>::new(); observables.len()];
+        let mut phase_flags = vec![Vec::<Option<u8>>::new(); observables.len()];
+
+        for (key, measurements) in self.record.measurements.iter() {
+            if measurements.observations.is_empty() {
+                keys.push(key, measurements, None);
+
+                for position in 0..observables.len() {
+                    values[position].push(None);
+                    snr[position].push(None);
+                    phase_flags[position].push(None);
+                }
+            }
+
+            // observations are sorted by station
+            for (station, observations) in &measurements
+                .observations
+                .iter()
+                .chunk_by(|(obs_key, _)| &obs_key.station)
+            {
+                let domes = station.domes.to_string();
+
+                keys.push(
+                    key,
+                    measurements,
+                    Some((station.code, &station.label, &domes)),
+                );
+
+                for position in 0..observables.len() {
+                    values[position].push(None);
+                    snr[position].push(None);
+                    phase_flags[position].push(None);
+                }
+
+                for (obs_key, observation) in observations {
+                    let position = positions[&obs_key.observable];
+
+                    *values[position].last_mut().unwrap() = Some(observation.value);
+                    *snr[position].last_mut().unwrap() = observation.snr.map(snr_value);
+                    *phase_flags[position].last_mut().unwrap() =
+                        observation.phase_flag.map(phase_flag_value);
+                }
+            }
+        }
+
+        let (mut columns, clock) = keys.into_columns();
+
+        for (((observable, values), snr), phase_flags) in
+            observables.iter().zip(values).zip(snr).zip(phase_flags)
+        {
+            let code = format!("{:x}", observable);
+
+            columns.push(Column::new(code.as_str().into(), values));
+            columns.push(Column::new(format!("{}{}", code, SNR_SUFFIX).into(), snr));
+            columns.push(Column::new(
+                format!("{}{}", code, PHASE_FLAG_SUFFIX).into(),
+                phase_flags,
+            ));
+        }
+
+        columns.extend(clock);
+
+        Ok(DataFrame::new(columns)?)
+    }
+}
+
+impl Record {
+    /// Converts Polars [DataFrame] to [Record]. This is the mirror operation of
+    /// [DORIS::to_dataframe] and [DORIS::to_wide_dataframe]: the layout is identified
+    /// by the presence of the `observable` column. In the wide layout, columns
+    /// that do not describe an [Observable] are ignored, as well as
+    /// missing `<code>_snr` and `<code>_phase_flag` columns.
+    /// [GroundStation](crate::prelude::GroundStation)s are described by the [Header]
+    /// when possible. Epochs are expressed in [TimeScale::TAI].
+    /// Requires the `polars` feature.
+    pub fn from_dataframe(dataframe: &DataFrame, header: &Header) -> Result<Self, ParsingError> {
+        let epochs = numeric_column::<Int64Type>(dataframe, EPOCH_COLUMN)?;
+        let flags = numeric_column::<UInt8Type>(dataframe, FLAG_COLUMN)?;
+        let codes = numeric_column::<UInt16Type>(dataframe, STATION_CODE_COLUMN)?;
+        let labels = string_column(dataframe, STATION_LABEL_COLUMN)?;
+        let domes = string_column(dataframe, STATION_DOMES_COLUMN)?;
+        let clock_offsets = numeric_column::<Float64Type>(dataframe, CLOCK_OFFSET_COLUMN)?;
+        let extrapolated = boolean_column(dataframe, CLOCK_EXTRAPOLATED_COLUMN)?;
+
+        // observation columns: observable, value, SNR and phase flag
+        let mut series = Vec::<(
+            Option<Vec<Option<&str>>>,
+            Option<Observable>,
+            Vec<Option<f64>>,
+            Option<Vec<Option<u8>>>,
+            Option<Vec<Option<u8>>>,
+        )>::new();
+
+        if dataframe.get_column_index(OBSERVABLE_COLUMN).is_some() {
+            series.push((
+                Some(string_column(dataframe, OBSERVABLE_COLUMN)?),
+                None,
+                numeric_column::<Float64Type>(dataframe, VALUE_COLUMN)?,
+                optional_numeric_column::<UInt8Type>(dataframe, SNR_COLUMN)?,
+                optional_numeric_column::<UInt8Type>(dataframe, PHASE_FLAG_COLUMN)?,
+            ));
+        } else {
+            for name in dataframe.get_column_names_str() {
+                if WIDE_COLUMNS.contains(&name)
+                    || name.ends_with(SNR_SUFFIX)
+                    || name.ends_with(PHASE_FLAG_SUFFIX)
+                {
+                    continue;
+                }
+
+                let Ok(observable) = Observable::from_str(name) else {
+                    continue;
+                };
+
+                series.push((
+                    None,
+                    Some(observable),
+                    numeric_column::<Float64Type>(dataframe, name)?,
+                    optional_numeric_column::<UInt8Type>(
+                        dataframe,
+                        &format!("{}{}", name, SNR_SUFFIX),
+                    )?,
+                    optional_numeric_column::<UInt8Type>(
+                        dataframe,
+                        &format!("{}{}", name, PHASE_FLAG_SUFFIX),
+                    )?,
+                ));
+            }
+        }
+
+        let mut record = Record::default();
+        let mut stations = StationResolver::new(&header.ground_stations);
+
+        for row in 0..dataframe.height() {
+            let (Some(epoch), Some(flag)) = (epochs[row], flags[row]) else {
+                return Err(ParsingError::DataFrameLayout);
+            };
+
+            let key = Key {
+                epoch: epoch_from_tai_nanoseconds(epoch, TimeScale::TAI),
+                flag: epoch_flag(flag)?,
+            };
+
+            let measurements = record.measurements.entry(key).or_default();
+
+            if let Some(offset) = clock_offsets[row] {
+                measurements.satellite_clock_offset = Some(ClockOffset {
+                    offset: duration_from_seconds(offset),
+                    extrapolated: extrapolated[row].unwrap_or_default(),
+                });
+            }
+
+            let Some(code) = codes[row] else {
+                continue;
+            };
+
+            let station = stations.resolve(Some(code), labels[row], domes[row])?;
+
+            for (codes, observable, values, snr, phase_flags) in series.iter() {
+                let observable = match (codes, observable) {
+                    (Some(codes), _) => match codes[row] {
+                        Some(code) => Observable::from_str(code)?,
+                        None => continue,
+                    },
+                    (None, Some(observable)) => *observable,
+                    (None, None) => continue,
+                };
+
+                let Some(value) = values[row] else {
+                    if codes.is_some() {
+                        return Err(ParsingError::DataFrameLayout);
+                    }
+                    continue;
+                };
+
+                let mut observation = Observation::default().with_value(value);
+
+                if let Some(snr) = snr.as_ref().and_then(|snr| snr[row]) {
+                    observation = observation.with_snr(SNR::from(snr));
+                }
+
+                if let Some(flag) = phase_flags.as_ref().and_then(|flags| flags[row]) {
+                    observation = observation.with_phase_flag(phase_flag(flag)?);
+                }
+
+                measurements.add_observation(station.clone(), observable, observation);
+            }
+        }
+
+        Ok(record)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prelude::{
+        ClockOffset, Duration, EpochFlag, Frequency, GroundStation, Key, Observable, PhaseFlag,
+        Record, DORIS, SNR,
+    };
+    use crate::tests::toolkit::{add_synthetic_samples, synthetic_record, synthetic_t0};
+
+    #[test]
+    fn polars_round_trip() {
+        let l1 = Observable::UnambiguousPhaseRange(Frequency::DORIS1);
+        let l2 = Observable::UnambiguousPhaseRange(Frequency::DORIS2);
+
+        let toulouse = GroundStation::default()
+            .with_site_label("TLSB")
+            .with_site_name("TOULOUSE")
+            .with_unique_id(1);
+
+        // not described in the header
+        let grasse = GroundStation::default()
+            .with_site_label("GR4B")
+            .with_unique_id(2);
+
+        let mut record = synthetic_record(
+            &toulouse,
+            &[
+                (0.0, vec![(l1, 1.0), (l2, 2.0)]),
+                (10.0, vec![(l1, 3.0), (Observable::Temperature, 20.5)]),
+            ],
+        );
+
+        add_synthetic_samples(&mut record, &grasse, &[(10.0, vec![(l2, 4.0)])]);
+
+        let t0 = synthetic_t0();
+
+        let measurements = record.measurements.values_mut().next().unwrap();
+
+        measurements.satellite_clock_offset = Some(ClockOffset::from_extrapolated_offset(
+            Duration::from_seconds(-4.25),
+        ));
+
+        for observation in measurements.observations.values_mut() {
+            *observation = observation
+                .with_snr(SNR::DbHz42_47)
+                .with_phase_flag(PhaseFlag::Discontinuity);
+        }
+
+        record.measurements.insert(
+            Key {
+                epoch: t0 + Duration::from_seconds(5.0),
+                flag: EpochFlag::ExternalEvent,
+            },
+            Default::default(),
+        );
+
+        let mut doris = DORIS::default();
+        doris.header.observables = vec![l1, l2];
+        doris.header.ground_stations = vec![toulouse.clone()];
+        doris.record = record;
+
+        // long layout
+        let dataframe = doris.to_dataframe().unwrap();
+
+        assert_eq!(dataframe.height(), 6);
+        assert_eq!(dataframe.width(), 11);
+
+        let parsed = Record::from_dataframe(&dataframe, &doris.header).unwrap();
+        assert_eq!(parsed, doris.record);
+
+        // stations are described by the header
+        let (obs_key, _) = parsed
+            .measurements
+            .values()
+            .next()
+            .unwrap()
+            .observations
+            .iter()
+            .next()
+            .unwrap();
+
+        assert_eq!(obs_key.station.station(), &toulouse);
+
+        // wide layout, in Key order: (t0, TLSB), (t0+10s, TLSB), (t0+10s, GR4B), (t0+5s)
+        let dataframe = doris.to_wide_dataframe().unwrap();
+
+        assert_eq!(dataframe.height(), 4);
+        assert_eq!(dataframe.width(), 7 + 3 * 3);
+
+        let names = dataframe.get_column_names_str();
+        assert_eq!(names[5], "L1");
+        assert_eq!(names[6], "L1_snr");
+        assert_eq!(names[8], "L2");
+        assert_eq!(names[11], "T");
+
+        let temperatures = dataframe
+            .column("T")
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_iter()
+            .collect::<Vec<_>>();
+
+        assert_eq!(temperatures, vec![None, Some(20.5), None, None]);
+
+        let parsed = Record::from_dataframe(&dataframe, &doris.header).unwrap();
+        assert_eq!(parsed, doris.record);
+
+        assert!(DORIS::default().to_dataframe().unwrap().is_empty());
+        assert!(DORIS::default().to_wide_dataframe().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,542 @@
+//! Least squares ground station positioning, from DORIS range rate observations
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    lsq::solve_normal_equations,
+    prelude::{
+        GroundStation, Matcher, Orbit, Pass, PassResiduals, RangeRateModel, RangeRateResiduals,
+        RangeRateSample, Record, StationCoordinates, DORIS, SP3,
+    },
+    residuals::{CountInterval, RangeRateObservations},
+};
+
+/// Number of position parameters
+const POSITION_PARAMETERS: usize = 3;
+
+/// [PositionSolution] is the outcome of the [PositioningSolver]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PositionSolution {
+    /// [GroundStation] described by the solved [StationCoordinates]
+    pub station: GroundStation,
+
+    /// Solved [StationCoordinates]
+    pub coordinates: StationCoordinates,
+
+    /// Range rate bias (beacon frequency offset) of each [Pass], in m/s
+    pub pass_biases: Vec<(Pass, f64)>,
+
+    /// Zenith wet delay correction, in meters, when estimated
+    pub zenith_wet_delay_m: Option<f64>,
+
+    /// Covariance matrix of the parameters. The parameters are
+    /// the (x, y, z) ECEF position in meters, followed by the zenith wet delay (when estimated),
+    /// followed by each [Pass] bias.
+    pub covariance: Vec<Vec<f64>>,
+
+    /// A posteriori standard deviation of unit weight, in m/s
+    pub sigma0: f64,
+
+    /// Post fit [RangeRateResiduals]
+    pub residuals: RangeRateResiduals,
+
+    /// Number of iterations
+    pub iterations: usize,
+
+    /// True if the solution converged
+    pub converged: bool,
+}
+
+impl PositionSolution {
+    /// Returns the (x, y, z) ECEF position standard deviation, in meters
+    pub fn position_std_dev_m(&self) -> (f64, f64, f64) {
+        (
+            self.covariance[0][0].sqrt(),
+            self.covariance[1][1].sqrt(),
+            self.covariance[2][2].sqrt(),
+        )
+    }
+}
+
+/// [PositioningSolver] solves the position of one [GroundStation], by iterative least squares,
+/// from its range rate observations and the satellite [Orbit].
+/// A range rate bias is estimated for each [Pass] (residual beacon frequency offset),
+/// and possibly one zenith wet tropospheric delay.
+/// Observations are modeled with the [RangeRateModel].
+///
+/// ```no_run
+/// use doris_rs::prelude::*;
+///
+/// let sp3 = SP3::from_gzip_file("data/SP3/ssacs220.b18164.e18165.DG_.sp3.001.gz")
+///     .unwrap();
+///
+/// let doris = DORIS::from_gzip_file("data/DOR/V3/cs2rx18164.gz")
+///     .unwrap();
+///
+/// // rough a priori position of a new beacon
+/// let apriori = StationCoordinates {
+///     epoch: Epoch::from_gregorian_utc_at_midnight(2018, 6, 13),
+///     position_m: (4627800.0, 119600.0, 4373000.0),
+///     velocity_m_yr: (0.0, 0.0, 0.0),
+/// };
+///
+/// let solver = PositioningSolver::default()
+///     .with_min_elevation(10.0)
+///     .with_troposphere(true);
+///
+/// let solution = doris.station_position(&sp3, &solver, &Matcher::Label("TLSB"), apriori)
+///     .unwrap();
+///
+/// let (sigma_x, sigma_y, sigma_z) = solution.position_std_dev_m();
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PositioningSolver {
+    /// [RangeRateModel] used to model the observations
+    pub model: RangeRateModel,
+
+    /// Maximal number of iterations
+    pub max_iterations: usize,
+
+    /// Convergence criterion: norm of the position correction, in meters
+    pub convergence_m: f64,
+
+    /// Minimal elevation angle of the observations, in degrees
+    pub min_elevation_deg: f64,
+
+    /// Minimal number of observations, for a [Pass] to be used
+    pub min_pass_samples: usize,
+
+    /// Estimate the zenith wet tropospheric delay
+    pub troposphere: bool,
+}
+
+impl Default for PositioningSolver {
+    fn default() -> Self {
+        Self {
+            model: Default::default(),
+            max_iterations: 10,
+            convergence_m: 1.0E-3,
+            min_elevation_deg: 10.0,
+            min_pass_samples: 5,
+            troposphere: false,
+        }
+    }
+}
+
+/// Linearized observation equation
+struct Equation {
+    /// Pass index
+    pass: usize,
+
+    /// Design matrix row (position and troposphere partials)
+    partials: [f64; POSITION_PARAMETERS + 1],
+
+    /// Prefit residual
+    residual: f64,
+
+    /// Modeled sample
+    sample: RangeRateSample,
+}
+
+impl PositioningSolver {
+    /// Copies and returns [PositioningSolver] with updated [RangeRateModel]
+    pub fn with_model(&self, model: RangeRateModel) -> Self {
+        let mut s = *self;
+        s.model = model;
+        s
+    }
+
+    /// Copies and returns [PositioningSolver] with updated maximal number of iterations
+    pub fn with_max_iterations(&self, max_iterations: usize) -> Self {
+        let mut s = *self;
+        s.max_iterations = max_iterations;
+        s
+    }
+
+    /// Copies and returns [PositioningSolver] with updated convergence criterion, in meters
+    pub fn with_convergence(&self, convergence_m: f64) -> Self {
+        let mut s = *self;
+        s.convergence_m = convergence_m;
+        s
+    }
+
+    /// Copies and returns [PositioningSolver] with updated elevation cut-off, in degrees
+    pub fn with_min_elevation(&self, elevation_deg: f64) -> Self {
+        let mut s = *self;
+        s.min_elevation_deg = elevation_deg;
+        s
+    }
+
+    /// Copies and returns [PositioningSolver] with updated minimal number of samples per pass
+    pub fn with_min_pass_samples(&self, min_samples: usize) -> Self {
+        let mut s = *self;
+        s.min_pass_samples = min_samples;
+        s
+    }
+
+    /// Copies and returns [PositioningSolver] with zenith wet delay estimation enabled or not
+    pub fn with_troposphere(&self, troposphere: bool) -> Self {
+        let mut s = *self;
+        s.troposphere = troposphere;
+        s
+    }
+
+    /// Returns the count intervals of this [Pass], that pass the elevation cut-off
+    fn intervals(
+        &self,
+        observations: &RangeRateObservations,
+        orbit: &Orbit,
+        pass: &Pass,
+        coordinates: &StationCoordinates,
+    ) -> Vec<CountInterval> {
+        self.model
+            .count_intervals(observations, orbit, pass, coordinates)
+            .into_iter()
+            .filter(|interval| {
+                interval.geometry_1.elevation_deg >= self.min_elevation_deg
+                    && interval.geometry_2.elevation_deg >= self.min_elevation_deg
+            })
+            .collect()
+    }
+
+    /// Linearizes the observation equations, at current parameters
+    fn linearize(
+        &self,
+        observations: &RangeRateObservations,
+        orbit: &Orbit,
+        passes: &[Pass],
+        coordinates: &StationCoordinates,
+        biases: &[f64],
+        zenith_wet_delay_m: f64,
+    ) -> Vec<Equation> {
+        let (x, y, z) = coordinates.position_m;
+
+        let line_of_sight = |position: (f64, f64, f64)| {
+            let rho = (position.0 - x, position.1 - y, position.2 - z);
+            let norm = (rho.0.powi(2) + rho.1.powi(2) + rho.2.powi(2)).sqrt();
+            [rho.0 / norm, rho.1 / norm, rho.2 / norm]
+        };
+
+        let mut equations = Vec::new();
+
+        for (index, pass) in passes.iter().enumerate() {
+            for interval in self.intervals(observations, orbit, pass, coordinates) {
+                let dt = interval.sample.interval.to_seconds();
+
+                let u_1 = line_of_sight(interval.state_1.position_m);
+                let u_2 = line_of_sight(interval.state_2.position_m);
+
+                let (_, m_w1) = self
+                    .model
+                    .mapping
+                    .factors(interval.geometry_1.elevation_deg);
+
+                let (_, m_w2) = self
+                    .model
+                    .mapping
+                    .factors(interval.geometry_2.elevation_deg);
+
+                let troposphere_partial = (m_w2 - m_w1) / dt;
+
+                let mut sample = interval.sample;
+                sample.computed += biases[index] + zenith_wet_delay_m * troposphere_partial;
+
+                equations.push(Equation {
+                    pass: index,
+                    partials: [
+                        -(u_2[0] - u_1[0]) / dt,
+                        -(u_2[1] - u_1[1]) / dt,
+                        -(u_2[2] - u_1[2]) / dt,
+                        troposphere_partial,
+                    ],
+                    residual: sample.residual(),
+                    sample,
+                });
+            }
+        }
+
+        equations
+    }
+
+    /// Solves the position of this [GroundStation], from the observations of this [Record],
+    /// this satellite [Orbit] and a priori [StationCoordinates].
+    /// Returns None when the problem is not solvable (not enough observations,
+    /// or singular geometry).
+    pub fn solve(
+        &self,
+        record: &Record,
+        orbit: &Orbit,
+        station: &GroundStation,
+        apriori: StationCoordinates,
+    ) -> Option<PositionSolution> {
+        let observations = self.model.observations(record);
+
+        let passes = observations
+            .passes
+            .iter()
+            .filter(|pass| pass.station == *station)
+            .filter(|pass| {
+                self.intervals(&observations, orbit, pass, &apriori).len() >= self.min_pass_samples
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let troposphere_parameters = if self.troposphere { 1 } else { 0 };
+        let bias_offset = POSITION_PARAMETERS + troposphere_parameters;
+        let unknowns = bias_offset + passes.len();
+
+        let mut coordinates = apriori;
+        let mut biases = vec![0.0; passes.len()];
+        let mut zenith_wet_delay_m = 0.0;
+
+        let mut iterations = 0;
+        let mut converged = false;
+
+        loop {
+            let equations = self.linearize(
+                &observations,
+                orbit,
+                &passes,
+                &coordinates,
+                &biases,
+                zenith_wet_delay_m,
+            );
+
+            if passes.is_empty() || equations.len() <= unknowns {
+                return None;
+            }
+
+            let mut normal = vec![vec![0.0; unknowns]; unknowns];
+            let mut b = vec![0.0; unknowns];
+
+            for equation in equations.iter() {
+                let mut row = vec![0.0; unknowns];
+
+                row[..POSITION_PARAMETERS]
+                    .copy_from_slice(&equation.partials[..POSITION_PARAMETERS]);
+
+                if self.troposphere {
+                    row[POSITION_PARAMETERS] = equation.partials[POSITION_PARAMETERS];
+                }
+
+                row[bias_offset + equation.pass] = 1.0;
+
+                for i in 0..unknowns {
+                    b[i] += row[i] * equation.residual;
+
+                    for j in 0..unknowns {
+                        normal[i][j] += row[i] * row[j];
+                    }
+                }
+            }
+
+            let (dx, inverse) = solve_normal_equations(&normal, &b)?;
+
+            if converged || iterations == self.max_iterations {
+                let sum_squares = equations
+                    .iter()
+                    .map(|equation| equation.residual.powi(2))
+                    .sum::<f64>();
+
+                let sigma0 = (sum_squares / (equations.len() - unknowns) as f64).sqrt();
+
+                let covariance = inverse
+                    .iter()
+                    .map(|row| row.iter().map(|value| value * sigma0.powi(2)).collect())
+                    .collect();
+
+                let residuals = RangeRateResiduals {
+                    passes: passes
+                        .iter()
+                        .enumerate()
+                        .map(|(index, pass)| PassResiduals {
+                            pass: pass.clone(),
+                            samples: equations
+                                .iter()
+                                .filter(|equation| equation.pass == index)
+                                .map(|equation| equation.sample)
+                                .collect(),
+                        })
+                        .collect(),
+                };
+
+                return Some(PositionSolution {
+                    station: station.with_coordinates(coordinates),
+                    coordinates,
+                    pass_biases: passes.into_iter().zip(biases).collect(),
+                    zenith_wet_delay_m: if self.troposphere {
+                        Some(zenith_wet_delay_m)
+                    } else {
+                        None
+                    },
+                    covariance,
+                    sigma0,
+                    residuals,
+                    iterations,
+                    converged,
+                });
+            }
+
+            let (x, y, z) = coordinates.position_m;
+            coordinates.position_m = (x + dx[0], y + dx[1], z + dx[2]);
+
+            if self.troposphere {
+                zenith_wet_delay_m += dx[POSITION_PARAMETERS];
+            }
+
+            for (bias, correction) in biases.iter_mut().zip(dx[bias_offset..].iter()) {
+                *bias += correction;
+            }
+
+            iterations += 1;
+
+            let norm = (dx[0].powi(2) + dx[1].powi(2) + dx[2].powi(2)).sqrt();
+            converged = norm < self.convergence_m;
+        }
+    }
+}
+
+impl DORIS {
+    /// Solves the position of the matching [GroundStation], using the [Orbit] of this
+    /// satellite (see [SP3::header_orbit]) and a priori [StationCoordinates].
+    /// See [PositioningSolver::solve].
+    pub fn station_position(
+        &self,
+        sp3: &SP3,
+        solver: &PositioningSolver,
+        matcher: &Matcher,
+        apriori: StationCoordinates,
+    ) -> Option<PositionSolution> {
+        let orbit = sp3.header_orbit(&self.header)?;
+
+        let station = self
+            .header
+            .ground_stations
+            .iter()
+            .find(|station| station.matches(matcher))?;
+
+        solver.solve(&self.record, orbit, station, apriori)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::{Duration, Frequency, Geometry, Observable, OrbitState};
+    use crate::tests::toolkit::{synthetic_record, synthetic_t0};
+
+    #[test]
+    fn range_rate_positioning() {
+        let t0 = synthetic_t0();
+
+        // inclined circular orbit, with its node drifting westward
+        // (Earth rotation image) so that successive passes differ
+        let orbit_position = |t: f64| {
+            let (radius, rate, node_rate) = (7.2E6, 1.0E-3, -3.0E-4);
+            let inclination = 60.0_f64.to_radians();
+
+            let (sin_u, cos_u) = (rate * t).sin_cos();
+            let (sin_n, cos_n) = (node_rate * t).sin_cos();
+            let (sin_i, cos_i) = inclination.sin_cos();
+
+            (
+                radius * (cos_n * cos_u - sin_n * sin_u * cos_i),
+                radius * (sin_n * cos_u + cos_n * sin_u * cos_i),
+                radius * sin_u * sin_i,
+            )
+        };
+
+        let orbit = Orbit {
+            satellite: "L27".to_string(),
+            states: (0..1000)
+                .map(|i| {
+                    let t = i as f64 * 30.0;
+                    OrbitState {
+                        epoch: t0 + Duration::from_seconds(t),
+                        position_m: orbit_position(t),
+                        velocity_m_s: None,
+                    }
+                })
+                .collect(),
+        };
+
+        // Toulouse beacon (TLSB)
+        let truth = StationCoordinates {
+            epoch: t0,
+            position_m: (4627851.9, 119640.1, 4372993.6),
+            velocity_m_yr: (0.0, 0.0, 0.0),
+        };
+
+        let station = GroundStation::default()
+            .with_site_label("TLSB")
+            .with_unique_id(13);
+
+        let l1 = Observable::UnambiguousPhaseRange(Frequency::DORIS1);
+        let l2 = Observable::UnambiguousPhaseRange(Frequency::DORIS2);
+
+        // visible samples, with a range rate bias per pass
+        let mut samples = Vec::new();
+
+        for i in 0..2900 {
+            let t = 10.0 * i as f64;
+            let state = orbit.interpolate(t0 + Duration::from_seconds(t)).unwrap();
+            let geometry = Geometry::new(&truth, &state);
+
+            if geometry.elevation_deg > 5.0 {
+                let bias = 0.05 * (1.0 + (t / 6000.0).floor());
+                let phase = geometry.range_m + bias * t;
+                samples.push((t, vec![(l1, phase), (l2, phase)]));
+            }
+        }
+
+        let record = synthetic_record(&station, &samples);
+
+        let apriori = StationCoordinates {
+            position_m: (4627851.9 + 100.0, 119640.1 - 50.0, 4372993.6 + 80.0),
+            ..truth
+        };
+
+        let model = RangeRateModel::default()
+            .with_troposphere(false)
+            .with_relativity(false)
+            .with_beacon_frequency(false)
+            .with_clock(false);
+
+        let solver = PositioningSolver::default().with_model(model);
+
+        let solution = solver.solve(&record, &orbit, &station, apriori).unwrap();
+
+        assert!(solution.converged);
+        assert!(solution.pass_biases.len() > 1);
+        assert!(solution.zenith_wet_delay_m.is_none());
+
+        let (x, y, z) = solution.coordinates.position_m;
+        assert!((x - truth.position_m.0).abs() < 1.0E-2);
+        assert!((y - truth.position_m.1).abs() < 1.0E-2);
+        assert!((z - truth.position_m.2).abs() < 1.0E-2);
+
+        // WGS84 reference (Bowring): 43.56069°N 1.48089°E 207.27m
+        let (latitude, longitude, height) = solution.coordinates.geodetic();
+        assert!((latitude - 43.56069).abs() < 1.0E-5);
+        assert!((longitude - 1.48089).abs() < 1.0E-5);
+        assert!((height - 207.27).abs() < 1.0E-2);
+
+        for (_, bias) in solution.pass_biases.iter() {
+            assert!(*bias > 0.04);
+        }
+
+        assert!(solution.sigma0 < 1.0E-3);
+        assert_eq!(solution.covariance.len(), 3 + solution.pass_biases.len());
+
+        assert_eq!(solution.station.coordinates, Some(solution.coordinates));
+
+        // unknown station
+        let unknown = GroundStation::default()
+            .with_site_label("GR4B")
+            .with_unique_id(12);
+
+        assert!(solver.solve(&record, &orbit, &unknown, apriori).is_none());
+    }
+}
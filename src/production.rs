@@ -1,8 +1,12 @@
 use crate::error::ParsingError;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 /// This structure is attached to DORIS file that were named
 /// according to the standard convention.
 #[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ProductionAttributes {
     /// 3 letter satellite name
     pub satellite: String,
@@ -0,0 +1,510 @@
+use std::{collections::HashMap, ops::Range};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::{
+    ClockOffset, Comments, Epoch, EpochFlag, Key, Measurements, Observable, Observation,
+    ObservationKey, PhaseFlag, Record, StationHandle, SNR,
+};
+
+#[cfg(feature = "serde")]
+use crate::prelude::ParsingError;
+
+/// [ColumnarRecord] is the columnar representation of the [Record]:
+/// each observation is one row, described by contiguous arrays
+/// (epoch, flag, station index, observable, value, SNR and phase flag).
+/// Rows follow the [Record] order: [Key] order, then [ObservationKey] order.
+///
+/// [GroundStation](crate::prelude::GroundStation)s are described once, in the station table,
+/// and rows refer to them by index, see [Self::stations]. The epoch table
+/// describes each [Key], its satellite [ClockOffset] and the rows it contains,
+/// so the conversion to and from [Record] is lossless,
+/// including epochs that do not contain any observation.
+///
+/// ```
+/// use doris_rs::prelude::*;
+///
+/// let doris = DORIS::from_gzip_file("data/DOR/V3/cs2rx18164.gz")
+///     .unwrap();
+///
+/// let columnar = doris.record.to_columnar();
+///
+/// // mean value of all temperature observations
+/// let (sum, count) = columnar.observables()
+///     .iter()
+///     .zip(columnar.values())
+///     .filter(|(observable, _)| **observable == Observable::Temperature)
+///     .fold((0.0, 0), |(sum, count), (_, value)| (sum + value, count + 1));
+///
+/// // lossless conversion
+/// assert_eq!(columnar.to_record(), doris.record);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "ColumnarRecordData"))]
+pub struct ColumnarRecord {
+    /// Comments found "as is" during record parsing
+    pub comments: Comments,
+
+    /// Station table
+    stations: Vec<StationHandle>,
+
+    /// [Key] of each epoch
+    keys: Vec<Key>,
+
+    /// Satellite [ClockOffset] of each epoch
+    clock_offsets: Vec<Option<ClockOffset>>,
+
+    /// First row of each epoch, terminated by the number of rows
+    epoch_offsets: Vec<usize>,
+
+    /// [Epoch] of each row
+    epochs: Vec<Epoch>,
+
+    /// [EpochFlag] of each row
+    flags: Vec<EpochFlag>,
+
+    /// Index of the station, in the station table, of each row
+    station_indices: Vec<u32>,
+
+    /// [Observable] of each row
+    observables: Vec<Observable>,
+
+    /// Observed value of each row, unit is [Observable] dependent
+    values: Vec<f64>,
+
+    /// [SNR] of each row
+    snr: Vec<Option<SNR>>,
+
+    /// [PhaseFlag] of each row
+    phase_flags: Vec<Option<PhaseFlag>>,
+}
+
+/// Deserialized [ColumnarRecord], prior validation
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct ColumnarRecordData {
+    comments: Comments,
+    stations: Vec<StationHandle>,
+    keys: Vec<Key>,
+    clock_offsets: Vec<Option<ClockOffset>>,
+    epoch_offsets: Vec<usize>,
+    epochs: Vec<Epoch>,
+    flags: Vec<EpochFlag>,
+    station_indices: Vec<u32>,
+    observables: Vec<Observable>,
+    values: Vec<f64>,
+    snr: Vec<Option<SNR>>,
+    phase_flags: Vec<Option<PhaseFlag>>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<ColumnarRecordData> for ColumnarRecord {
+    type Error = ParsingError;
+
+    fn try_from(data: ColumnarRecordData) -> Result<Self, Self::Error> {
+        let columnar = Self {
+            comments: data.comments,
+            stations: data.stations,
+            keys: data.keys,
+            clock_offsets: data.clock_offsets,
+            epoch_offsets: data.epoch_offsets,
+            epochs: data.epochs,
+            flags: data.flags,
+            station_indices: data.station_indices,
+            observables: data.observables,
+            values: data.values,
+            snr: data.snr,
+            phase_flags: data.phase_flags,
+        };
+
+        columnar.validate()?;
+        Ok(columnar)
+    }
+}
+
+impl ColumnarRecord {
+    /// Verifies the consistency of the tables and columns:
+    /// equal column lengths, station indices within the station table,
+    /// and epoch table covering all rows in order.
+    #[cfg(feature = "serde")]
+    fn validate(&self) -> Result<(), ParsingError> {
+        let len = self.values.len();
+
+        let columns = [
+            self.epochs.len(),
+            self.flags.len(),
+            self.station_indices.len(),
+            self.observables.len(),
+            self.snr.len(),
+            self.phase_flags.len(),
+        ];
+
+        if columns.iter().any(|column| *column != len)
+            || self.clock_offsets.len() != self.keys.len()
+            || self.epoch_offsets.len() != self.keys.len() + 1
+            || self.epoch_offsets.first() != Some(&0)
+            || self.epoch_offsets.last() != Some(&len)
+            || self.epoch_offsets.windows(2).any(|pair| pair[0] > pair[1])
+        {
+            return Err(ParsingError::ColumnarRecord);
+        }
+
+        let stations = self.stations.len();
+
+        if self
+            .station_indices
+            .iter()
+            .any(|index| *index as usize >= stations)
+        {
+            return Err(ParsingError::ColumnarRecord);
+        }
+
+        for (nth, key) in self.keys.iter().enumerate() {
+            for row in self.epoch_rows(nth) {
+                if self.epochs[row] != key.epoch || self.flags[row] != key.flag {
+                    return Err(ParsingError::ColumnarRecord);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns number of rows (observations)
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns true if this [ColumnarRecord] does not contain any observation
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns the station table. Rows refer to [StationHandle]s by index,
+    /// see [Self::station_indices].
+    pub fn stations(&self) -> &[StationHandle] {
+        &self.stations
+    }
+
+    /// Returns the index of this [StationHandle] in the station table
+    pub fn station_index(&self, station: &StationHandle) -> Option<u32> {
+        self.stations
+            .iter()
+            .position(|handle| handle == station)
+            .map(|index| index as u32)
+    }
+
+    /// Returns the [Key] of each epoch
+    pub fn keys(&self) -> &[Key] {
+        &self.keys
+    }
+
+    /// Returns the satellite [ClockOffset] of each epoch
+    pub fn clock_offsets(&self) -> &[Option<ClockOffset>] {
+        &self.clock_offsets
+    }
+
+    /// Returns the rows of the nth epoch, see [Self::keys].
+    pub fn epoch_rows(&self, nth: usize) -> Range<usize> {
+        self.epoch_offsets[nth]..self.epoch_offsets[nth + 1]
+    }
+
+    /// Returns the [Epoch] column
+    pub fn epochs(&self) -> &[Epoch] {
+        &self.epochs
+    }
+
+    /// Returns the [EpochFlag] column
+    pub fn flags(&self) -> &[EpochFlag] {
+        &self.flags
+    }
+
+    /// Returns the station index column, see [Self::stations].
+    pub fn station_indices(&self) -> &[u32] {
+        &self.station_indices
+    }
+
+    /// Returns the [Observable] column
+    pub fn observables(&self) -> &[Observable] {
+        &self.observables
+    }
+
+    /// Returns the value column, unit is [Observable] dependent
+    pub fn values(&self) -> &[f64] {
+        &self.values
+    }
+
+    /// Returns the value column, as mutable slice
+    pub fn values_mut(&mut self) -> &mut [f64] {
+        &mut self.values
+    }
+
+    /// Returns the [SNR] column
+    pub fn snr(&self) -> &[Option<SNR>] {
+        &self.snr
+    }
+
+    /// Returns the [PhaseFlag] column
+    pub fn phase_flags(&self) -> &[Option<PhaseFlag>] {
+        &self.phase_flags
+    }
+
+    /// Returns the [StationHandle] of the nth row
+    pub fn station(&self, row: usize) -> &StationHandle {
+        &self.stations[self.station_indices[row] as usize]
+    }
+
+    /// Returns the [Observation] of the nth row
+    pub fn observation(&self, row: usize) -> Observation {
+        Observation {
+            snr: self.snr[row],
+            phase_flag: self.phase_flags[row],
+            value: self.values[row],
+        }
+    }
+
+    /// Converts this [ColumnarRecord] to [Record]
+    pub fn to_record(&self) -> Record {
+        let mut record = Record {
+            comments: self.comments.clone(),
+            ..Default::default()
+        };
+
+        for (nth, key) in self.keys.iter().enumerate() {
+            let mut measurements = Measurements {
+                satellite_clock_offset: self.clock_offsets[nth],
+                ..Default::default()
+            };
+
+            for row in self.epoch_rows(nth) {
+                measurements.observations.insert(
+                    ObservationKey {
+                        station: self.station(row).clone(),
+                        observable: self.observables[row],
+                    },
+                    self.observation(row),
+                );
+            }
+
+            record.measurements.insert(key.clone(), measurements);
+        }
+
+        record
+    }
+}
+
+impl From<&Record> for ColumnarRecord {
+    fn from(record: &Record) -> Self {
+        let len = record
+            .measurements
+            .values()
+            .map(|measurements| measurements.observations.len())
+            .sum::<usize>();
+
+        let num_epochs = record.measurements.len();
+
+        let mut columnar = Self {
+            comments: record.comments.clone(),
+            keys: Vec::with_capacity(num_epochs),
+            clock_offsets: Vec::with_capacity(num_epochs),
+            epoch_offsets: Vec::with_capacity(num_epochs + 1),
+            epochs: Vec::with_capacity(len),
+            flags: Vec::with_capacity(len),
+            station_indices: Vec::with_capacity(len),
+            observables: Vec::with_capacity(len),
+            values: Vec::with_capacity(len),
+            snr: Vec::with_capacity(len),
+            phase_flags: Vec::with_capacity(len),
+            ..Default::default()
+        };
+
+        let mut station_table = HashMap::<StationHandle, u32>::new();
+
+        for (key, measurements) in record.measurements.iter() {
+            columnar.keys.push(key.clone());
+            columnar
+                .clock_offsets
+                .push(measurements.satellite_clock_offset);
+            columnar.epoch_offsets.push(columnar.values.len());
+
+            for (obs_key, observation) in measurements.observations.iter() {
+                let index = *station_table
+                    .entry(obs_key.station.clone())
+                    .or_insert_with(|| {
+                        columnar.stations.push(obs_key.station.clone());
+                        (columnar.stations.len() - 1) as u32
+                    });
+
+                columnar.epochs.push(key.epoch);
+                columnar.flags.push(key.flag);
+                columnar.station_indices.push(index);
+                columnar.observables.push(obs_key.observable);
+                columnar.values.push(observation.value);
+                columnar.snr.push(observation.snr);
+                columnar.phase_flags.push(observation.phase_flag);
+            }
+        }
+
+        columnar.epoch_offsets.push(columnar.values.len());
+        columnar
+    }
+}
+
+impl From<Record> for ColumnarRecord {
+    fn from(record: Record) -> Self {
+        Self::from(&record)
+    }
+}
+
+impl From<&ColumnarRecord> for Record {
+    fn from(columnar: &ColumnarRecord) -> Self {
+        columnar.to_record()
+    }
+}
+
+impl From<ColumnarRecord> for Record {
+    fn from(columnar: ColumnarRecord) -> Self {
+        columnar.to_record()
+    }
+}
+
+impl Record {
+    /// Converts this [Record] to its [ColumnarRecord] representation,
+    /// suited for analysis workloads.
+    pub fn to_columnar(&self) -> ColumnarRecord {
+        ColumnarRecord::from(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prelude::{
+        ClockOffset, Duration, EpochFlag, Frequency, GroundStation, Key, Observable, PhaseFlag,
+        Record, SNR,
+    };
+    use crate::tests::toolkit::{add_synthetic_samples, synthetic_record, synthetic_t0};
+
+    #[test]
+    fn columnar_record() {
+        let l1 = Observable::UnambiguousPhaseRange(Frequency::DORIS1);
+
+        let toulouse = GroundStation::default()
+            .with_site_label("TLSB")
+            .with_unique_id(1);
+
+        let grasse = GroundStation::default()
+            .with_site_label("GR4B")
+            .with_unique_id(2);
+
+        let mut record = synthetic_record(
+            &toulouse,
+            &[
+                (0.0, vec![(l1, 1.0), (Observable::Temperature, 20.0)]),
+                (10.0, vec![(l1, 2.0)]),
+            ],
+        );
+
+        add_synthetic_samples(&mut record, &grasse, &[(10.0, vec![(l1, 3.0)])]);
+
+        let t0 = synthetic_t0();
+
+        let measurements = record.measurements.values_mut().next().unwrap();
+
+        measurements.satellite_clock_offset = Some(ClockOffset::from_extrapolated_offset(
+            Duration::from_seconds(1.0E-3),
+        ));
+
+        for observation in measurements.observations.values_mut() {
+            *observation = observation
+                .with_snr(SNR::DbHz30_35)
+                .with_phase_flag(PhaseFlag::Discontinuity);
+        }
+
+        // event without observation
+        record.measurements.insert(
+            Key {
+                epoch: t0 + Duration::from_seconds(5.0),
+                flag: EpochFlag::ExternalEvent,
+            },
+            Default::default(),
+        );
+
+        record.comments.push("comment".to_string());
+
+        let columnar = record.to_columnar();
+
+        assert_eq!(columnar.len(), 4);
+        assert_eq!(columnar.keys().len(), 3);
+        assert_eq!(columnar.stations().len(), 2);
+
+        assert_eq!(columnar.values(), &[1.0, 20.0, 2.0, 3.0]);
+        assert_eq!(columnar.station_indices(), &[0, 0, 0, 1]);
+        assert_eq!(columnar.station(3).station(), &grasse);
+        assert_eq!(columnar.epochs()[3], t0 + Duration::from_seconds(10.0));
+        assert_eq!(
+            columnar.observables(),
+            &[l1, Observable::Temperature, l1, l1]
+        );
+
+        assert_eq!(columnar.snr()[0], Some(SNR::DbHz30_35));
+        assert_eq!(columnar.snr()[2], None);
+        assert_eq!(columnar.phase_flags()[1], Some(PhaseFlag::Discontinuity));
+
+        assert_eq!(columnar.epoch_rows(0), 0..2);
+        assert_eq!(columnar.epoch_rows(1), 2..4);
+        assert_eq!(columnar.epoch_rows(2), 4..4);
+
+        assert!(columnar.clock_offsets()[0].unwrap().extrapolated);
+        assert_eq!(columnar.flags()[0], EpochFlag::OK);
+
+        let station = columnar.stations()[1].clone();
+        assert_eq!(columnar.station_index(&station), Some(1));
+
+        // lossless
+        assert_eq!(columnar.to_record(), record);
+        assert_eq!(Record::from(columnar), record);
+
+        assert!(Record::default().to_columnar().is_empty());
+        assert_eq!(
+            Record::default().to_columnar().to_record(),
+            Record::default()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn columnar_record_deserialization() {
+        use crate::prelude::ColumnarRecord;
+
+        let l1 = Observable::UnambiguousPhaseRange(Frequency::DORIS1);
+
+        let station = GroundStation::default()
+            .with_site_label("TLSB")
+            .with_unique_id(1);
+
+        let record = synthetic_record(&station, &[(0.0, vec![(l1, 1.0)]), (10.0, vec![(l1, 2.0)])]);
+
+        let columnar = record.to_columnar();
+        let json = serde_json::to_value(&columnar).unwrap();
+
+        let parsed = serde_json::from_value::<ColumnarRecord>(json.clone()).unwrap();
+        assert_eq!(parsed, columnar);
+
+        // station index out of the station table
+        let mut invalid = json.clone();
+        invalid["station_indices"][1] = 3.into();
+        assert!(serde_json::from_value::<ColumnarRecord>(invalid).is_err());
+
+        // truncated column
+        let mut invalid = json.clone();
+        invalid["values"].as_array_mut().unwrap().pop();
+        assert!(serde_json::from_value::<ColumnarRecord>(invalid).is_err());
+
+        // epoch table not covering all rows
+        let mut invalid = json;
+        invalid["epoch_offsets"][2] = 1.into();
+        assert!(serde_json::from_value::<ColumnarRecord>(invalid).is_err());
+    }
+}
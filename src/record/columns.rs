@@ -0,0 +1,193 @@
+//! Column names of the tabular representations of the [Record]
+//! (Arrow, Polars, CSV), one row per observation.
+use std::{
+    collections::{BTreeSet, HashMap},
+    str::FromStr,
+};
+
+use crate::prelude::{
+    Duration, EpochFlag, GroundStation, Header, Observable, ParsingError, PhaseFlag, Record,
+    StationHandle, SNR,
+};
+
+#[cfg(any(feature = "arrow", feature = "polars", feature = "sqlite"))]
+use crate::prelude::{Epoch, TimeScale};
+
+/// Observation [Epoch](crate::prelude::Epoch), as TAI nanoseconds (since J1900)
+pub const EPOCH_COLUMN: &str = "epoch";
+
+/// [EpochFlag], as standard RINEX value
+pub const FLAG_COLUMN: &str = "flag";
+
+/// [GroundStation] unique code
+pub const STATION_CODE_COLUMN: &str = "station_code";
+
+/// [GroundStation] label
+pub const STATION_LABEL_COLUMN: &str = "station_label";
+
+/// [GroundStation] DOMES site number
+pub const STATION_DOMES_COLUMN: &str = "station_domes";
+
+/// [Observable], as standard RINEX code
+pub const OBSERVABLE_COLUMN: &str = "observable";
+
+/// Observed value, unit is [Observable] dependent
+pub const VALUE_COLUMN: &str = "value";
+
+/// [SNR], as standard RINEX value
+pub const SNR_COLUMN: &str = "snr";
+
+/// [PhaseFlag], as standard RINEX value
+pub const PHASE_FLAG_COLUMN: &str = "phase_flag";
+
+/// Satellite [ClockOffset](crate::prelude::ClockOffset), in seconds
+pub const CLOCK_OFFSET_COLUMN: &str = "clock_offset";
+
+/// True when the satellite [ClockOffset](crate::prelude::ClockOffset) is extrapolated
+pub const CLOCK_EXTRAPOLATED_COLUMN: &str = "clock_extrapolated";
+
+/// Column name suffix of the [SNR] columns, in the wide layout
+/// (one column per [Observable])
+pub const SNR_SUFFIX: &str = "_snr";
+
+/// Column name suffix of the [PhaseFlag] columns, in the wide layout
+/// (one column per [Observable])
+pub const PHASE_FLAG_SUFFIX: &str = "_phase_flag";
+
+/// Returns this [Epoch] as TAI nanoseconds (since J1900)
+#[cfg(any(feature = "arrow", feature = "polars", feature = "sqlite"))]
+pub(crate) fn tai_nanoseconds(epoch: Epoch) -> i64 {
+    epoch.to_tai_duration().total_nanoseconds() as i64
+}
+
+/// Returns [Epoch] from TAI nanoseconds (since J1900), expressed in this [TimeScale]
+#[cfg(any(feature = "arrow", feature = "polars", feature = "sqlite"))]
+pub(crate) fn epoch_from_tai_nanoseconds(nanoseconds: i64, time_scale: TimeScale) -> Epoch {
+    Epoch::from_tai_duration(Duration::from_total_nanoseconds(nanoseconds as i128))
+        .to_time_scale(time_scale)
+}
+
+/// Returns [Duration] from seconds, rounded to the nanosecond,
+/// so the (seconds) columns are lossless
+pub(crate) fn duration_from_seconds(seconds: f64) -> Duration {
+    Duration::from_total_nanoseconds((seconds * 1.0E9).round() as i128)
+}
+
+/// Returns the [Observable] columns of the wide layout: [Header] [Observable]s,
+/// followed by any other [Observable] found in the [Record]
+pub(crate) fn wide_observables(header: &Header, record: &Record) -> Vec<Observable> {
+    let mut observables = header.observables.clone();
+
+    let others = record
+        .measurements
+        .values()
+        .flat_map(|measurements| measurements.observations.keys())
+        .map(|obs_key| obs_key.observable)
+        .filter(|observable| !observables.contains(observable))
+        .collect::<BTreeSet<_>>();
+
+    observables.extend(others);
+    observables
+}
+
+/// Returns the standard RINEX value of this [EpochFlag]
+pub(crate) fn epoch_flag_value(flag: EpochFlag) -> u8 {
+    flag.to_string().parse::<u8>().unwrap_or_default()
+}
+
+/// Returns [EpochFlag] from its standard RINEX value
+pub(crate) fn epoch_flag(value: u8) -> Result<EpochFlag, ParsingError> {
+    EpochFlag::from_str(&value.to_string())
+}
+
+/// Returns the standard RINEX value of this [SNR]
+pub(crate) fn snr_value(snr: SNR) -> u8 {
+    format!("{:x}", snr).parse::<u8>().unwrap_or_default()
+}
+
+/// Returns the standard RINEX value of this [PhaseFlag]
+pub(crate) fn phase_flag_value(flag: PhaseFlag) -> u8 {
+    flag.to_string().parse::<u8>().unwrap_or_default()
+}
+
+/// Returns [PhaseFlag] from its standard RINEX value
+pub(crate) fn phase_flag(value: u8) -> Result<PhaseFlag, ParsingError> {
+    PhaseFlag::from_str(&value.to_string())
+}
+
+/// [StationResolver] identifies the [GroundStation]s described by the station columns:
+/// any of (code, label, DOMES), depending on the format. Stations are described by the
+/// known [GroundStation]s (file header) when possible. Other stations are created,
+/// and given a new unique code when it is not described. Stations are interned.
+pub(crate) struct StationResolver<'a> {
+    /// Known [GroundStation]s
+    stations: &'a [GroundStation],
+
+    /// Resolved [StationHandle]s
+    resolved: HashMap<(Option<u16>, Option<String>, Option<String>), StationHandle>,
+
+    /// Next unique code
+    next_code: u16,
+}
+
+impl<'a> StationResolver<'a> {
+    /// Builds a new [StationResolver], from the known [GroundStation]s
+    pub fn new(stations: &'a [GroundStation]) -> Self {
+        Self {
+            stations,
+            resolved: Default::default(),
+            next_code: stations
+                .iter()
+                .map(|station| station.code)
+                .max()
+                .unwrap_or(0)
+                + 1,
+        }
+    }
+
+    /// Returns the [StationHandle] described by these columns,
+    /// None when the column is not available.
+    pub fn resolve(
+        &mut self,
+        code: Option<u16>,
+        label: Option<&str>,
+        domes: Option<&str>,
+    ) -> Result<StationHandle, ParsingError> {
+        let key = (code, label.map(String::from), domes.map(String::from));
+
+        if let Some(station) = self.resolved.get(&key) {
+            return Ok(station.clone());
+        }
+
+        let known = self.stations.iter().find(|station| {
+            code.is_none_or(|code| station.code == code)
+                && label.is_none_or(|label| station.label == label)
+                && domes.is_none_or(|domes| station.domes.to_string() == domes)
+        });
+
+        let station = match known {
+            Some(station) => StationHandle::from(station),
+            None => {
+                let code = code.unwrap_or_else(|| {
+                    self.next_code += 1;
+                    self.next_code - 1
+                });
+
+                let mut station = GroundStation::default().with_unique_id(code);
+
+                if let Some(label) = label {
+                    station = station.with_site_label(label);
+                }
+
+                if let Some(domes) = domes {
+                    station = station.with_domes_str(domes)?;
+                }
+
+                StationHandle::from(station)
+            },
+        };
+
+        self.resolved.insert(key, station.clone());
+        Ok(station)
+    }
+}
@@ -58,3 +58,57 @@ impl std::fmt::Display for EpochFlag {
         }
     }
 }
+
+/// [PhaseFlag] is attached to phase observations
+/// and describes the phase continuity.
+#[derive(Copy, Default, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PhaseFlag {
+    /// Phase tracking is continuous (sane)
+    #[default]
+    OK,
+
+    /// Phase discontinuity (jump or reset) since previous observation
+    Discontinuity,
+}
+
+impl std::str::FromStr for PhaseFlag {
+    type Err = ParsingError;
+
+    /// Parses [PhaseFlag] from standard values.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "0" => Ok(Self::OK),
+            "1" => Ok(Self::Discontinuity),
+            _ => Err(ParsingError::ObservationFlag),
+        }
+    }
+}
+
+impl std::fmt::Display for PhaseFlag {
+    /// Formats [PhaseFlag] according to standards.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::OK => "0".fmt(f),
+            Self::Discontinuity => "1".fmt(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn phase_flag_parsing() {
+        for (desc, expected) in [("0", PhaseFlag::OK), ("1", PhaseFlag::Discontinuity)] {
+            let parsed = PhaseFlag::from_str(desc).unwrap();
+            assert_eq!(parsed, expected);
+            assert_eq!(parsed.to_string(), desc);
+        }
+
+        assert!(PhaseFlag::from_str("2").is_err());
+        assert_eq!(PhaseFlag::default(), PhaseFlag::OK);
+    }
+}
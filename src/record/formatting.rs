@@ -75,7 +75,13 @@ impl Record {
                                 })
                                 .reduce(|k, _| k)
                             {
-                                write!(writer, "{:14.3}  ", observation.value)?;
+                                write!(writer, "{:14.3} ", observation.value)?;
+
+                                if let Some(flag) = observation.phase_flag {
+                                    write!(writer, "{}", flag)?;
+                                } else {
+                                    write!(writer, " ")?;
+                                }
                             } else {
                                 write!(writer, "                  ")?;
                             }
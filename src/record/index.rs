@@ -0,0 +1,337 @@
+use itertools::Itertools;
+use std::{collections::BTreeMap, ops::Bound};
+
+use crate::prelude::{Epoch, EpochFlag, GroundStation, Observable, Observation, Record};
+
+/// Chronological series of one [Observable], indexed by ([Epoch], [EpochFlag]).
+type Series<'a> = BTreeMap<(Epoch, EpochFlag), &'a Observation>;
+
+/// [StationIndex] is a station-first view of the [Record]: observations
+/// are indexed by [GroundStation], then [Observable], then [Epoch].
+/// It borrows the [Record] observations and is obtained with [Record::station_index].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StationIndex<'a> {
+    /// Observations per [GroundStation] and [Observable]
+    stations: BTreeMap<&'a GroundStation, BTreeMap<Observable, Series<'a>>>,
+}
+
+impl<'a> StationIndex<'a> {
+    /// Builds the [StationIndex] of this [Record]
+    pub(crate) fn new(record: &'a Record) -> Self {
+        let mut stations = BTreeMap::<&'a GroundStation, BTreeMap<Observable, Series<'a>>>::new();
+
+        for (key, measurements) in record.measurements.iter() {
+            for (obs_key, observation) in measurements.observations.iter() {
+                stations
+                    .entry(&obs_key.station)
+                    .or_default()
+                    .entry(obs_key.observable)
+                    .or_default()
+                    .insert((key.epoch, key.flag), observation);
+            }
+        }
+
+        Self { stations }
+    }
+
+    /// Returns true if this [StationIndex] is empty
+    pub fn is_empty(&self) -> bool {
+        self.stations.is_empty()
+    }
+
+    /// Returns [GroundStation]s [Iterator], in [GroundStation] order
+    pub fn stations_iter(&self) -> Box<dyn Iterator<Item = &'a GroundStation> + '_> {
+        Box::new(self.stations.keys().copied())
+    }
+
+    /// Returns [Observable]s [Iterator] for this [GroundStation]
+    pub fn observables_iter(
+        &self,
+        station: &GroundStation,
+    ) -> Box<dyn Iterator<Item = Observable> + '_> {
+        match self.stations.get(station) {
+            Some(observables) => Box::new(observables.keys().copied()),
+            None => Box::new(std::iter::empty()),
+        }
+    }
+
+    /// Returns number of observations of this [GroundStation]
+    pub fn station_len(&self, station: &GroundStation) -> usize {
+        self.stations
+            .get(station)
+            .map(|observables| observables.values().map(|series| series.len()).sum())
+            .unwrap_or_default()
+    }
+
+    /// Returns chronological ([Epoch], [Observable], [Observation]) [Iterator]
+    /// for this [GroundStation]. [Observable]s sampled at the same [Epoch] are
+    /// returned in [Observable] order.
+    pub fn station_iter(
+        &self,
+        station: &GroundStation,
+    ) -> Box<dyn Iterator<Item = (Epoch, Observable, &'a Observation)> + '_> {
+        match self.stations.get(station) {
+            Some(observables) => Box::new(Self::merge(observables, None, None)),
+            None => Box::new(std::iter::empty()),
+        }
+    }
+
+    /// Returns chronological ([Epoch], [Observable], [Observation]) [Iterator]
+    /// for this [GroundStation], from `start` (included) until `end` (excluded).
+    /// When `end` is not specified, the [Iterator] runs until the end of the record.
+    pub fn station_range_iter(
+        &self,
+        station: &GroundStation,
+        start: Epoch,
+        end: Option<Epoch>,
+    ) -> Box<dyn Iterator<Item = (Epoch, Observable, &'a Observation)> + '_> {
+        match self.stations.get(station) {
+            Some(observables) => Box::new(Self::merge(observables, Some(start), end)),
+            None => Box::new(std::iter::empty()),
+        }
+    }
+
+    /// Returns chronological ([Epoch], [Observation]) [Iterator]
+    /// for this [GroundStation] and [Observable].
+    pub fn series_iter(
+        &self,
+        station: &GroundStation,
+        observable: Observable,
+    ) -> Box<dyn Iterator<Item = (Epoch, &'a Observation)> + '_> {
+        match self
+            .stations
+            .get(station)
+            .and_then(|observables| observables.get(&observable))
+        {
+            Some(series) => Box::new(Self::range(series, None, None)),
+            None => Box::new(std::iter::empty()),
+        }
+    }
+
+    /// Returns chronological ([Epoch], [Observation]) [Iterator]
+    /// for this [GroundStation] and [Observable], from `start` (included)
+    /// until `end` (excluded). When `end` is not specified,
+    /// the [Iterator] runs until the end of the record.
+    ///
+    /// ```
+    /// use doris_rs::prelude::*;
+    ///
+    /// let doris = DORIS::from_gzip_file("data/DOR/V3/cs2rx18164.gz")
+    ///     .unwrap();
+    ///
+    /// let index = doris.record.station_index();
+    ///
+    /// let station = doris.ground_station(Matcher::Label("TLSB"))
+    ///     .unwrap();
+    ///
+    /// let t0 = Epoch::from_gregorian_utc_at_midnight(2018, 6, 13);
+    /// let t1 = t0 + Duration::from_hours(1.0);
+    ///
+    /// let l1 = Observable::UnambiguousPhaseRange(Frequency::DORIS1);
+    ///
+    /// for (epoch, observation) in index.series_range_iter(&station, l1, t0, Some(t1)) {
+    ///     assert!(epoch >= t0 && epoch < t1);
+    /// }
+    /// ```
+    pub fn series_range_iter(
+        &self,
+        station: &GroundStation,
+        observable: Observable,
+        start: Epoch,
+        end: Option<Epoch>,
+    ) -> Box<dyn Iterator<Item = (Epoch, &'a Observation)> + '_> {
+        match self
+            .stations
+            .get(station)
+            .and_then(|observables| observables.get(&observable))
+        {
+            Some(series) => Box::new(Self::range(series, Some(start), end)),
+            None => Box::new(std::iter::empty()),
+        }
+    }
+
+    /// Returns the [Observation] of this [GroundStation] and [Observable], at this [Epoch]
+    pub fn observation(
+        &self,
+        station: &GroundStation,
+        observable: Observable,
+        epoch: Epoch,
+    ) -> Option<&'a Observation> {
+        self.series_range_iter(station, observable, epoch, None)
+            .take_while(|(t, _)| *t == epoch)
+            .map(|(_, observation)| observation)
+            .next()
+    }
+
+    /// Merges the ([Epoch], [Observation]) sub series of all [Observable]s, in [start, end[,
+    /// in chronological order.
+    fn merge<'s>(
+        observables: &'s BTreeMap<Observable, Series<'a>>,
+        start: Option<Epoch>,
+        end: Option<Epoch>,
+    ) -> impl Iterator<Item = (Epoch, Observable, &'a Observation)> + 's {
+        observables
+            .iter()
+            .map(move |(observable, series)| {
+                Self::range(series, start, end)
+                    .map(move |(epoch, observation)| (epoch, *observable, observation))
+            })
+            .kmerge_by(|a, b| (a.0, a.1) < (b.0, b.1))
+    }
+
+    /// Returns the ([Epoch], [Observation]) sub series, in [start, end[
+    fn range<'s>(
+        series: &'s Series<'a>,
+        start: Option<Epoch>,
+        end: Option<Epoch>,
+    ) -> impl Iterator<Item = (Epoch, &'a Observation)> + 's {
+        // EpochFlag::OK is the lowest flag
+        let lower = match start {
+            Some(start) => Bound::Included((start, EpochFlag::OK)),
+            None => Bound::Unbounded,
+        };
+
+        let upper = match (start, end) {
+            // empty range
+            (Some(start), Some(end)) if end <= start => Bound::Excluded((start, EpochFlag::OK)),
+            (_, Some(end)) => Bound::Excluded((end, EpochFlag::OK)),
+            (_, None) => Bound::Unbounded,
+        };
+
+        series
+            .range((lower, upper))
+            .map(|((epoch, _), observation)| (*epoch, *observation))
+    }
+}
+
+impl Record {
+    /// Returns the station-first [StationIndex] of this [Record],
+    /// to efficiently browse the observations station by station.
+    ///
+    /// ```
+    /// use doris_rs::prelude::*;
+    ///
+    /// let doris = DORIS::from_gzip_file("data/DOR/V3/cs2rx18164.gz")
+    ///     .unwrap();
+    ///
+    /// let index = doris.record.station_index();
+    ///
+    /// for station in index.stations_iter() {
+    ///     for (epoch, observable, observation) in index.station_iter(station) {
+    ///         // chronological observations of this station
+    ///     }
+    /// }
+    /// ```
+    pub fn station_index(&self) -> StationIndex<'_> {
+        StationIndex::new(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prelude::{Duration, Frequency, GroundStation, Observable};
+    use crate::tests::toolkit::{add_synthetic_samples, synthetic_record, synthetic_t0};
+
+    #[test]
+    fn station_index() {
+        let l1 = Observable::UnambiguousPhaseRange(Frequency::DORIS1);
+        let l2 = Observable::UnambiguousPhaseRange(Frequency::DORIS2);
+
+        let station_a = GroundStation::default()
+            .with_site_label("AAAA")
+            .with_unique_id(1);
+
+        let station_b = GroundStation::default()
+            .with_site_label("BBBB")
+            .with_unique_id(2);
+
+        let mut record = synthetic_record(
+            &station_a,
+            &[
+                (0.0, vec![(l1, 1.0), (l2, 2.0)]),
+                (10.0, vec![(l1, 3.0), (l2, 4.0)]),
+                (20.0, vec![(l1, 5.0)]),
+            ],
+        );
+
+        add_synthetic_samples(
+            &mut record,
+            &station_b,
+            &[(10.0, vec![(l2, 10.0)]), (30.0, vec![(l2, 11.0)])],
+        );
+
+        let index = record.station_index();
+        let t0 = synthetic_t0();
+
+        assert!(!index.is_empty());
+        assert_eq!(
+            index.stations_iter().collect::<Vec<_>>(),
+            vec![&station_a, &station_b]
+        );
+
+        assert_eq!(
+            index.observables_iter(&station_a).collect::<Vec<_>>(),
+            vec![l1, l2]
+        );
+
+        assert_eq!(
+            index.observables_iter(&station_b).collect::<Vec<_>>(),
+            vec![l2]
+        );
+
+        assert_eq!(index.station_len(&station_a), 5);
+        assert_eq!(index.station_len(&station_b), 2);
+
+        let series = index
+            .station_iter(&station_a)
+            .map(|(t, observable, observation)| {
+                ((t - t0).to_seconds(), observable, observation.value)
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            series,
+            vec![
+                (0.0, l1, 1.0),
+                (0.0, l2, 2.0),
+                (10.0, l1, 3.0),
+                (10.0, l2, 4.0),
+                (20.0, l1, 5.0),
+            ]
+        );
+
+        let start = t0 + Duration::from_seconds(10.0);
+        let end = t0 + Duration::from_seconds(20.0);
+
+        let series = index
+            .station_range_iter(&station_a, start, Some(end))
+            .map(|(_, observable, observation)| (observable, observation.value))
+            .collect::<Vec<_>>();
+
+        assert_eq!(series, vec![(l1, 3.0), (l2, 4.0)]);
+
+        let series = index
+            .series_range_iter(&station_a, l1, start, None)
+            .map(|(_, observation)| observation.value)
+            .collect::<Vec<_>>();
+
+        assert_eq!(series, vec![3.0, 5.0]);
+
+        assert_eq!(index.series_iter(&station_b, l1).count(), 0);
+        assert_eq!(
+            index
+                .series_range_iter(&station_a, l1, end, Some(start))
+                .count(),
+            0
+        );
+
+        assert_eq!(
+            index
+                .observation(&station_b, l2, t0 + Duration::from_seconds(30.0))
+                .map(|obs| obs.value),
+            Some(11.0)
+        );
+
+        assert!(index.observation(&station_b, l2, end).is_none());
+    }
+}
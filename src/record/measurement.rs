@@ -1,7 +1,7 @@
 #[cfg(doc)]
-use crate::prelude::{TimeScale, DORIS};
+use crate::prelude::{GroundStation, TimeScale, DORIS};
 
-use crate::prelude::{ClockOffset, GroundStation, Matcher, Observable, Observation};
+use crate::prelude::{ClockOffset, Epoch, Matcher, Observable, Observation, StationHandle};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -13,8 +13,8 @@ use std::collections::BTreeMap;
 #[derive(Clone, Debug, Default, PartialEq, PartialOrd, Eq, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ObservationKey {
-    /// [GroundStation] being observed
-    pub station: GroundStation,
+    /// [GroundStation] being observed, as compact [StationHandle]
+    pub station: StationHandle,
 
     /// [Observable] determines the physics and measurement unit
     pub observable: Observable,
@@ -28,14 +28,17 @@ pub struct Measurements {
     pub satellite_clock_offset: Option<ClockOffset>,
 
     /// Observations indexed [Observable]s, measurement unit varies.
+    #[cfg_attr(feature = "serde", serde(with = "crate::entries::flattened"))]
     pub observations: BTreeMap<ObservationKey, Observation>,
 }
 
 impl Measurements {
-    /// Add a new observation to this set of [Measurements]  
+    /// Add a new observation to this set of [Measurements].
+    /// The [GroundStation] is described by a [StationHandle], which is cheap to clone:
+    /// create one [StationHandle] per [GroundStation] and share it between observations.
     pub fn add_observation(
         &mut self,
-        station: GroundStation,
+        station: StationHandle,
         observable: Observable,
         observation: Observation,
     ) {
@@ -48,10 +51,11 @@ impl Measurements {
         );
     }
 
-    /// Updates this set of [Measurements] with a new observation
+    /// Updates this set of [Measurements] with a new observation,
+    /// see [Self::add_observation].
     pub fn with_observation(
         &self,
-        station: GroundStation,
+        station: StationHandle,
         observable: Observable,
         observation: Observation,
     ) -> Self {
@@ -96,4 +100,13 @@ impl Measurements {
         s.satellite_clock_offset = Some(clock_offset);
         s
     }
+
+    /// Returns the [Epoch] of these [Measurements] corrected for the satellite
+    /// [ClockOffset], when it is known. The sampling [Epoch] is returned otherwise.
+    pub fn clock_corrected_epoch(&self, epoch: Epoch) -> Epoch {
+        match self.satellite_clock_offset {
+            Some(clock_offset) => epoch - clock_offset.offset,
+            None => epoch,
+        }
+    }
 }
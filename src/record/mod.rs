@@ -1,6 +1,11 @@
 mod clock;
+mod columnar;
+
+pub mod columns;
+
 mod flag;
 mod formatting;
+mod index;
 mod key;
 mod measurement;
 mod observation;
@@ -8,18 +13,20 @@ mod parsing;
 mod snr;
 
 use itertools::Itertools;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::prelude::{Comments, Epoch, Matcher, Observable, StationHandle};
 
 #[cfg(doc)]
 use crate::prelude::GroundStation;
 
-use crate::prelude::{Comments, Epoch, Matcher, Observable};
-
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 pub use clock::ClockOffset;
-pub use flag::EpochFlag;
+pub use columnar::ColumnarRecord;
+pub use flag::{EpochFlag, PhaseFlag};
+pub use index::StationIndex;
 pub use key::Key;
 pub use measurement::{Measurements, ObservationKey};
 pub use observation::Observation;
@@ -34,6 +41,7 @@ pub struct Record {
 
     /// [GroundStation]s [Measurement]s, in chronolical order.
     /// Observations vary with the satellite orbit course.
+    #[cfg_attr(feature = "serde", serde(with = "crate::entries::flattened"))]
     pub measurements: BTreeMap<Key, Measurements>,
 }
 
@@ -63,4 +71,23 @@ impl Record {
                 .unique(),
         )
     }
+
+    /// Gathers all observations, per [GroundStation] and [Observable],
+    /// as chronological ([Epoch], value) series.
+    pub(crate) fn station_observable_series(
+        &self,
+    ) -> HashMap<(StationHandle, Observable), Vec<(Epoch, f64)>> {
+        let mut series = HashMap::<(StationHandle, Observable), Vec<(Epoch, f64)>>::new();
+
+        for (key, measurements) in self.measurements.iter() {
+            for (obs_key, observation) in measurements.observations.iter() {
+                series
+                    .entry((obs_key.station.clone(), obs_key.observable))
+                    .or_default()
+                    .push((key.epoch, observation.value));
+            }
+        }
+
+        series
+    }
 }
@@ -4,7 +4,7 @@ use crate::prelude::Observable;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use crate::prelude::SNR;
+use crate::prelude::{PhaseFlag, SNR};
 
 /// Signal [Observation].
 #[derive(Copy, Default, Clone, Debug, PartialEq, PartialOrd)]
@@ -13,8 +13,9 @@ pub struct Observation {
     /// [SNR] for all frequency measurements
     pub snr: Option<SNR>,
 
-    // /// Phase lock [Flag] for phase measurements specifically.
-    // pub phase_flag: Option<Flag>,
+    /// [PhaseFlag] for phase measurements specifically.
+    pub phase_flag: Option<PhaseFlag>,
+
     /// Measured value, unit is [Observable] dependent.
     pub value: f64,
 }
@@ -26,11 +27,11 @@ impl Observation {
         self
     }
 
-    // /// Defines DORIS phase measurement with associated [Flag]
-    // pub fn with_phase_flag(mut self, flag: Flag) -> Self {
-    //     self.phase_flag = Some(flag);
-    //     self
-    // }
+    /// Defines DORIS phase measurement with associated [PhaseFlag]
+    pub fn with_phase_flag(mut self, flag: PhaseFlag) -> Self {
+        self.phase_flag = Some(flag);
+        self
+    }
 
     /// Defines new DORIS measurement with desired value.
     /// Unit and meaning is dependent on attached [Observable].
@@ -39,26 +40,3 @@ impl Observation {
         self
     }
 }
-
-#[cfg(test)]
-mod test {
-    // use super::*;
-
-    // #[test]
-    // fn default_flag() {
-    //     assert_eq!(Flag::default(), Flag::Ok);
-    // }
-
-    // #[test]
-    // fn parsing() {
-    //     for (flag, expected) in [("0", Flag::Ok), ("1", Flag::PowerFailure)] {
-    //         let parsed = Flag::from_str(flag).unwrap();
-
-    //         assert_eq!(parsed, expected);
-
-    //         let formatted = parsed.to_string();
-
-    //         assert_eq!(formatted, flag);
-    //     }
-    // }
-}
@@ -4,8 +4,8 @@ use crate::{
     epoch::parse_in_timescale as parse_epoch_in_timescale,
     error::ParsingError,
     prelude::{
-        ClockOffset, Duration, Epoch, EpochFlag, GroundStation, Header, Key, Matcher, Measurements,
-        Observable, Observation, Record, TimeScale, SNR,
+        ClockOffset, Duration, Epoch, EpochFlag, Header, Key, Matcher, Measurements, Observable,
+        Observation, ObservationKey, PhaseFlag, Record, StationHandle, TimeScale, SNR,
     },
 };
 
@@ -40,6 +40,13 @@ impl Record {
         let observables = &header.observables;
         let nb_observables = observables.len();
 
+        // stations are interned once, and shared by all observations
+        let stations = header
+            .ground_stations
+            .iter()
+            .map(StationHandle::from)
+            .collect::<Vec<_>>();
+
         // Iterate and consume, one line at a time
         while let Ok(size) = reader.read_line(&mut line_buf) {
             if size == 0 {
@@ -70,7 +77,7 @@ impl Record {
                 let mut obs_ptr = 0;
                 let mut epoch = Epoch::default();
                 let flag = EpochFlag::default();
-                let mut station = Option::<&GroundStation>::None;
+                let mut station = Option::<&StationHandle>::None;
                 let mut clock_offset = Option::<ClockOffset>::None;
 
                 for (nth, line) in epoch_buf.lines().enumerate() {
@@ -120,8 +127,7 @@ impl Record {
                             let matcher = Matcher::ID(station_id);
 
                             // identification
-                            if let Some(matching) = header
-                                .ground_stations
+                            if let Some(matching) = stations
                                 .iter()
                                 .filter(|station| station.matches(&matcher))
                                 .reduce(|k, _| k)
@@ -208,22 +214,27 @@ impl Record {
 
                                 offset += 1;
 
-                                if offset + 1 < line_len {
-                                    // let slice = &line[offset..offset + 1];
-                                    // println!("slice \"{}\"", slice);
+                                if offset < line_len
+                                    && observables[obs_ptr].is_phase_range_observable()
+                                {
+                                    let slice = &line[offset..offset + 1];
+
+                                    if let Ok(flag) = slice.trim().parse::<PhaseFlag>() {
+                                        if let Some(measurements) =
+                                            record.measurements.get_mut(&key)
+                                        {
+                                            let obs_key = ObservationKey {
+                                                station: station.clone(),
+                                                observable: observables[obs_ptr],
+                                            };
 
-                                    // if let Ok(flag) = slice.trim().parse::<Flag>() {
-                                    //     if let Some(measurements) =
-                                    //         record.measurements.get_mut(&key)
-                                    //     {
-                                    //         if let Some(observation) = measurements
-                                    //             .observations
-                                    //             .get_mut(&observables[obs_ptr])
-                                    //         {
-                                    //             observation.phase_flag = Some(flag);
-                                    //         }
-                                    //     }
-                                    // }
+                                            if let Some(observation) =
+                                                measurements.observations.get_mut(&obs_key)
+                                            {
+                                                observation.phase_flag = Some(flag);
+                                            }
+                                        }
+                                    }
                                 }
 
                                 offset += 1;
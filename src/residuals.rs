@@ -0,0 +1,616 @@
+//! Range rate residuals (observed minus computed) against a reference orbit
+use std::collections::{BTreeMap, HashMap};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    constants::SPEED_OF_LIGHT_M_S,
+    prelude::{
+        Duration, Epoch, Frequency, Geometry, GroundStation, MappingFunction, Observable, Orbit,
+        OrbitState, Pass, PhaseFlag, Record, StationCoordinates, StationHandle, TroposphereModel,
+        DORIS, SP3,
+    },
+    statistics::{mean_std_dev, rms},
+};
+
+/// [RangeRateModel] describes the range rate model, and the terms it accounts for.
+/// The observed range rate is the ionosphere free phase difference over each
+/// count interval. The computed range rate is the geometric range difference
+/// over the same interval, corrected for:
+/// - the tropospheric delay, see [TroposphereModel]
+/// - the satellite clock drift, deduced from the satellite clock offsets
+/// - the nominal beacon frequency shift, see [GroundStation::relative_frequency_shift]
+/// - the relativistic clock effect of the satellite orbit eccentricity.
+///
+/// The frequency offsets appear as range rate biases c.δf/f.
+///
+/// ```no_run
+/// use doris_rs::prelude::*;
+///
+/// let sinex = Sinex::from_gzip_file("data/SNX/dpod2020_031.snx.gz")
+///     .unwrap();
+///
+/// let sp3 = SP3::from_gzip_file("data/SP3/ssacs220.b18164.e18165.DG_.sp3.001.gz")
+///     .unwrap();
+///
+/// // coordinates propagated to the beginning of the day
+/// let t0 = Epoch::from_gregorian_utc_at_midnight(2018, 6, 13);
+///
+/// let doris = DORIS::from_gzip_file("data/DOR/V3/cs2rx18164.gz")
+///     .unwrap()
+///     .with_station_coordinates(&sinex, t0);
+///
+/// let model = RangeRateModel::default()
+///     .with_mapping_function(MappingFunction::Chao);
+///
+/// let residuals = doris.range_rate_residuals(&sp3, &model)
+///     .unwrap();
+///
+/// for (station, stats) in residuals.station_summary() {
+///     println!("{}: mean={:.3}m/s rms={:.3}m/s", station.label, stats.mean, stats.rms);
+/// }
+///
+/// for pass in residuals.passes.iter() {
+///     let stats = pass.stats();
+/// }
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RangeRateModel {
+    /// Maximal data gap, within a [Pass]
+    pub max_gap: Duration,
+
+    /// Maximal count interval. Longer intervals are not used.
+    pub max_interval: Duration,
+
+    /// Account for the tropospheric delay
+    pub troposphere: bool,
+
+    /// [MappingFunction] of the tropospheric delay
+    pub mapping: MappingFunction,
+
+    /// Account for the satellite clock drift
+    pub clock: bool,
+
+    /// Account for the nominal beacon frequency shift
+    pub beacon_frequency: bool,
+
+    /// Account for the relativistic clock effect
+    pub relativity: bool,
+}
+
+impl Default for RangeRateModel {
+    fn default() -> Self {
+        Self {
+            max_gap: Duration::from_seconds(600.0),
+            max_interval: Duration::from_seconds(30.0),
+            troposphere: true,
+            mapping: Default::default(),
+            clock: true,
+            beacon_frequency: true,
+            relativity: true,
+        }
+    }
+}
+
+/// [RangeRateSample] is the range rate residual of one count interval
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RangeRateSample {
+    /// [Epoch] at the end of the count interval
+    pub epoch: Epoch,
+
+    /// Count interval
+    pub interval: Duration,
+
+    /// Observed range rate, in m/s
+    pub observed: f64,
+
+    /// Computed range rate, in m/s
+    pub computed: f64,
+
+    /// Elevation angle, in degrees, at the end of the count interval
+    pub elevation_deg: f64,
+}
+
+impl RangeRateSample {
+    /// Observed minus computed residual, in m/s
+    pub fn residual(&self) -> f64 {
+        self.observed - self.computed
+    }
+}
+
+/// [ResidualStats] summarizes a set of residuals, in m/s
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ResidualStats {
+    /// Number of samples
+    pub samples: usize,
+
+    /// Mean residual
+    pub mean: f64,
+
+    /// Standard deviation of the residuals
+    pub std_dev: f64,
+
+    /// Root mean square of the residuals
+    pub rms: f64,
+}
+
+impl ResidualStats {
+    fn from_residuals(residuals: &[f64]) -> Self {
+        let (mean, std_dev) = mean_std_dev(residuals).unwrap_or_default();
+
+        Self {
+            samples: residuals.len(),
+            mean,
+            std_dev,
+            rms: rms(residuals).unwrap_or_default(),
+        }
+    }
+}
+
+/// [PassResiduals] are the range rate residuals of one [Pass]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PassResiduals {
+    /// [Pass] being analyzed
+    pub pass: Pass,
+
+    /// [RangeRateSample]s, in chronological order
+    pub samples: Vec<RangeRateSample>,
+}
+
+impl PassResiduals {
+    /// Returns the [ResidualStats] of this [Pass]
+    pub fn stats(&self) -> ResidualStats {
+        let residuals = self
+            .samples
+            .iter()
+            .map(|sample| sample.residual())
+            .collect::<Vec<_>>();
+
+        ResidualStats::from_residuals(&residuals)
+    }
+}
+
+/// [RangeRateResiduals] gathers the [PassResiduals] of all stations
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RangeRateResiduals {
+    /// [PassResiduals], per station and in chronological order
+    pub passes: Vec<PassResiduals>,
+}
+
+impl RangeRateResiduals {
+    /// Summarizes the residuals per [GroundStation]
+    pub fn station_summary(&self) -> BTreeMap<GroundStation, ResidualStats> {
+        let mut residuals = BTreeMap::<GroundStation, Vec<f64>>::new();
+
+        for pass in self.passes.iter() {
+            residuals
+                .entry(pass.pass.station.clone())
+                .or_default()
+                .extend(pass.samples.iter().map(|sample| sample.residual()));
+        }
+
+        residuals
+            .into_iter()
+            .map(|(station, residuals)| (station, ResidualStats::from_residuals(&residuals)))
+            .collect()
+    }
+}
+
+/// Phase observations of one station, at one [Epoch]
+#[derive(Default)]
+struct PhaseSample {
+    l1: Option<f64>,
+    l2: Option<f64>,
+    discontinuity: bool,
+}
+
+/// Range rate observations of a [Record], ready to be modeled
+pub(crate) struct RangeRateObservations {
+    troposphere: TroposphereModel,
+    clock_offsets: HashMap<Epoch, Duration>,
+    phases: HashMap<StationHandle, BTreeMap<Epoch, PhaseSample>>,
+    pub(crate) passes: Vec<Pass>,
+}
+
+/// Modeled count interval
+pub(crate) struct CountInterval {
+    pub(crate) sample: RangeRateSample,
+    pub(crate) state_1: OrbitState,
+    pub(crate) state_2: OrbitState,
+    pub(crate) geometry_1: Geometry,
+    pub(crate) geometry_2: Geometry,
+}
+
+impl RangeRateModel {
+    /// Copies and returns [RangeRateModel] with updated maximal data gap
+    pub fn with_max_gap(&self, max_gap: Duration) -> Self {
+        let mut s = *self;
+        s.max_gap = max_gap;
+        s
+    }
+
+    /// Copies and returns [RangeRateModel] with updated maximal count interval
+    pub fn with_max_interval(&self, max_interval: Duration) -> Self {
+        let mut s = *self;
+        s.max_interval = max_interval;
+        s
+    }
+
+    /// Copies and returns [RangeRateModel] with troposphere term enabled or not
+    pub fn with_troposphere(&self, troposphere: bool) -> Self {
+        let mut s = *self;
+        s.troposphere = troposphere;
+        s
+    }
+
+    /// Copies and returns [RangeRateModel] with updated tropospheric [MappingFunction]
+    pub fn with_mapping_function(&self, mapping: MappingFunction) -> Self {
+        let mut s = *self;
+        s.mapping = mapping;
+        s
+    }
+
+    /// Copies and returns [RangeRateModel] with satellite clock term enabled or not
+    pub fn with_clock(&self, clock: bool) -> Self {
+        let mut s = *self;
+        s.clock = clock;
+        s
+    }
+
+    /// Copies and returns [RangeRateModel] with beacon frequency term enabled or not
+    pub fn with_beacon_frequency(&self, beacon_frequency: bool) -> Self {
+        let mut s = *self;
+        s.beacon_frequency = beacon_frequency;
+        s
+    }
+
+    /// Copies and returns [RangeRateModel] with relativistic term enabled or not
+    pub fn with_relativity(&self, relativity: bool) -> Self {
+        let mut s = *self;
+        s.relativity = relativity;
+        s
+    }
+
+    /// Relativistic clock correction of the satellite, expressed in meters
+    fn relativistic_range(state: &OrbitState) -> f64 {
+        let (x, y, z) = state.position_m;
+        let (vx, vy, vz) = state.velocity_m_s.unwrap_or_default();
+        -2.0 * (x * vx + y * vy + z * vz) / SPEED_OF_LIGHT_M_S
+    }
+
+    /// Gathers the range rate observations of this [Record]
+    pub(crate) fn observations(&self, record: &Record) -> RangeRateObservations {
+        let mut clock_offsets = HashMap::<Epoch, Duration>::new();
+        let mut phases = HashMap::<StationHandle, BTreeMap<Epoch, PhaseSample>>::new();
+
+        for (key, measurements) in record.measurements.iter() {
+            if let Some(clock_offset) = measurements.satellite_clock_offset {
+                clock_offsets.insert(key.epoch, clock_offset.offset);
+            }
+
+            for (obs_key, observation) in measurements.observations.iter() {
+                let sample = phases
+                    .entry(obs_key.station.clone())
+                    .or_default()
+                    .entry(key.epoch)
+                    .or_default();
+
+                match obs_key.observable {
+                    Observable::UnambiguousPhaseRange(Frequency::DORIS1) => {
+                        sample.l1 = Some(observation.value);
+                    },
+                    Observable::UnambiguousPhaseRange(Frequency::DORIS2) => {
+                        sample.l2 = Some(observation.value);
+                    },
+                    _ => continue,
+                }
+
+                if observation.phase_flag == Some(PhaseFlag::Discontinuity) {
+                    sample.discontinuity = true;
+                }
+            }
+        }
+
+        RangeRateObservations {
+            troposphere: TroposphereModel::new(record).with_mapping_function(self.mapping),
+            clock_offsets,
+            phases,
+            passes: record.station_passes(self.max_gap),
+        }
+    }
+
+    /// Models each count interval of this [Pass], for these [StationCoordinates]
+    pub(crate) fn count_intervals(
+        &self,
+        observations: &RangeRateObservations,
+        orbit: &Orbit,
+        pass: &Pass,
+        coordinates: &StationCoordinates,
+    ) -> Vec<CountInterval> {
+        let l1 = Frequency::DORIS1.frequency_hz();
+        let l2 = Frequency::DORIS2.frequency_hz();
+        let alpha = (l1 / l2).powi(2);
+
+        let Some(station_phases) = observations.phases.get(&StationHandle::from(&pass.station))
+        else {
+            return Vec::new();
+        };
+
+        let clock_offsets = &observations.clock_offsets;
+
+        let corrected = |epoch: Epoch| match clock_offsets.get(&epoch) {
+            Some(offset) => epoch - *offset,
+            None => epoch,
+        };
+
+        let (latitude_deg, _, height_m) = coordinates.geodetic();
+
+        // (epoch, iono free phase, discontinuity)
+        let iono_free = station_phases
+            .range(pass.start..=pass.end)
+            .filter_map(|(epoch, sample)| {
+                let (l_1, l_2) = (sample.l1?, sample.l2?);
+                Some((
+                    *epoch,
+                    (alpha * l_1 - l_2) / (alpha - 1.0),
+                    sample.discontinuity,
+                ))
+            })
+            .collect::<Vec<_>>();
+
+        let mut intervals = Vec::new();
+
+        for ((t_1, phase_1, _), (t_2, phase_2, discontinuity)) in
+            iono_free.iter().zip(iono_free.iter().skip(1))
+        {
+            let interval = *t_2 - *t_1;
+
+            if *discontinuity || interval > self.max_interval {
+                continue;
+            }
+
+            let dt = interval.to_seconds();
+
+            let (Some(state_1), Some(state_2)) = (
+                orbit.interpolate(corrected(*t_1)),
+                orbit.interpolate(corrected(*t_2)),
+            ) else {
+                continue;
+            };
+
+            let geometry_1 = Geometry::new(coordinates, &state_1);
+            let geometry_2 = Geometry::new(coordinates, &state_2);
+
+            let mut computed = (geometry_2.range_m - geometry_1.range_m) / dt;
+
+            if self.troposphere {
+                let delay = |epoch: Epoch, geometry: &Geometry| {
+                    observations.troposphere.slant_delay(
+                        &pass.station,
+                        epoch,
+                        latitude_deg,
+                        height_m,
+                        geometry.elevation_deg,
+                    )
+                };
+
+                computed += (delay(*t_2, &geometry_2) - delay(*t_1, &geometry_1)) / dt;
+            }
+
+            if self.relativity {
+                computed +=
+                    (Self::relativistic_range(&state_2) - Self::relativistic_range(&state_1)) / dt;
+            }
+
+            if self.clock {
+                if let (Some(offset_1), Some(offset_2)) =
+                    (clock_offsets.get(t_1), clock_offsets.get(t_2))
+                {
+                    computed += SPEED_OF_LIGHT_M_S * (*offset_2 - *offset_1).to_seconds() / dt;
+                }
+            }
+
+            if self.beacon_frequency {
+                computed += SPEED_OF_LIGHT_M_S * pass.station.relative_frequency_shift();
+            }
+
+            intervals.push(CountInterval {
+                sample: RangeRateSample {
+                    epoch: *t_2,
+                    interval,
+                    observed: (phase_2 - phase_1) / dt,
+                    computed,
+                    elevation_deg: geometry_2.elevation_deg,
+                },
+                state_1,
+                state_2,
+                geometry_1,
+                geometry_2,
+            });
+        }
+
+        intervals
+    }
+
+    /// Computes the [RangeRateResiduals] of this [Record], using this [Orbit].
+    /// Stations must be described by their coordinates, and observed
+    /// on both frequencies. Count intervals across a phase discontinuity are not used.
+    pub fn residuals(&self, record: &Record, orbit: &Orbit) -> RangeRateResiduals {
+        let observations = self.observations(record);
+
+        let passes = observations
+            .passes
+            .iter()
+            .filter_map(|pass| {
+                let coordinates = pass.station.coordinates?;
+
+                let samples = self
+                    .count_intervals(&observations, orbit, pass, &coordinates)
+                    .into_iter()
+                    .map(|interval| interval.sample)
+                    .collect::<Vec<_>>();
+
+                if samples.is_empty() {
+                    None
+                } else {
+                    Some(PassResiduals {
+                        pass: pass.clone(),
+                        samples,
+                    })
+                }
+            })
+            .collect();
+
+        RangeRateResiduals { passes }
+    }
+}
+
+impl DORIS {
+    /// Computes the [RangeRateResiduals] of this [DORIS] file, using the
+    /// [Orbit] of this satellite (see [SP3::header_orbit]) and this [RangeRateModel].
+    /// Returns None when this satellite is not described by the [SP3] file.
+    pub fn range_rate_residuals(
+        &self,
+        sp3: &SP3,
+        model: &RangeRateModel,
+    ) -> Option<RangeRateResiduals> {
+        let orbit = sp3.header_orbit(&self.header)?;
+        Some(model.residuals(&self.record, orbit))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tests::toolkit::{synthetic_record, synthetic_t0};
+
+    #[test]
+    fn range_rate_residuals() {
+        let t0 = synthetic_t0();
+
+        // circular equatorial orbit
+        let orbit_position = |t: f64| {
+            let (radius, rate) = (7.0E6, 1.0E-3);
+            (radius * (rate * t).cos(), radius * (rate * t).sin(), 0.0)
+        };
+
+        let orbit = Orbit {
+            satellite: "L27".to_string(),
+            states: (0..20)
+                .map(|i| {
+                    let t = i as f64 * 30.0;
+                    OrbitState {
+                        epoch: t0 + Duration::from_seconds(t),
+                        position_m: orbit_position(t),
+                        velocity_m_s: None,
+                    }
+                })
+                .collect(),
+        };
+
+        let coordinates = StationCoordinates {
+            epoch: t0,
+            position_m: (6378137.0, 0.0, 0.0),
+            velocity_m_yr: (0.0, 0.0, 0.0),
+        };
+
+        let station = GroundStation::default()
+            .with_site_label("TLSB")
+            .with_unique_id(13)
+            .with_coordinates(coordinates);
+
+        // phase = true geometric range + constant ambiguity
+        // and a constant range rate bias of 0.1 m/s
+        let l1 = Observable::UnambiguousPhaseRange(Frequency::DORIS1);
+        let l2 = Observable::UnambiguousPhaseRange(Frequency::DORIS2);
+
+        let samples = (0..30)
+            .map(|i| {
+                let t = 60.0 + i as f64 * 10.0;
+                let epoch = t0 + Duration::from_seconds(t);
+                let state = orbit.interpolate(epoch).unwrap();
+                let range = Geometry::new(&coordinates, &state).range_m + 0.1 * t;
+                (t, vec![(l1, range + 100.0), (l2, range + 100.0)])
+            })
+            .collect::<Vec<_>>();
+
+        let mut record = synthetic_record(&station, &samples);
+
+        let model = RangeRateModel::default()
+            .with_troposphere(false)
+            .with_relativity(false);
+
+        let residuals = model.residuals(&record, &orbit);
+        assert_eq!(residuals.passes.len(), 1);
+        assert_eq!(residuals.passes[0].samples.len(), 29);
+
+        let stats = residuals.passes[0].stats();
+        assert!((stats.mean - 0.1).abs() < 1.0E-3);
+        assert!(stats.std_dev < 1.0E-3);
+
+        let summary = residuals.station_summary();
+        assert_eq!(summary[&station].samples, 29);
+
+        // analytical reference, over [290s; 300s]: (ρ(300) - ρ(290)) / 10s
+        // with ρ(t)² = r² + R² - 2rR.cos(ωt + ωe.ρ(t)/c) (Earth rotation during propagation)
+        let sample = residuals.passes[0]
+            .samples
+            .iter()
+            .find(|sample| sample.epoch == t0 + Duration::from_seconds(300.0))
+            .unwrap();
+
+        assert!((sample.computed - 6300.9032).abs() < 1.0E-3);
+        assert!((sample.residual() - 0.1).abs() < 1.0E-3);
+
+        // phase discontinuity: one count interval is lost
+        for (key, measurements) in record.measurements.iter_mut() {
+            if key.epoch == t0 + Duration::from_seconds(160.0) {
+                for observation in measurements.observations.values_mut() {
+                    *observation = observation.with_phase_flag(PhaseFlag::Discontinuity);
+                }
+            }
+        }
+
+        let residuals = model.residuals(&record, &orbit);
+        assert_eq!(residuals.passes[0].samples.len(), 28);
+
+        // tropospheric delay is modeled: standard atmosphere
+        let troposphere = TroposphereModel::default();
+        let (latitude_deg, _, height_m) = coordinates.geodetic();
+
+        let samples = samples
+            .into_iter()
+            .map(|(t, observations)| {
+                let epoch = t0 + Duration::from_seconds(t);
+                let state = orbit.interpolate(epoch).unwrap();
+                let elevation_deg = Geometry::new(&coordinates, &state).elevation_deg;
+
+                let delay =
+                    troposphere.slant_delay(&station, epoch, latitude_deg, height_m, elevation_deg);
+
+                let observations = observations
+                    .into_iter()
+                    .map(|(observable, value)| (observable, value + delay))
+                    .collect();
+
+                (t, observations)
+            })
+            .collect::<Vec<_>>();
+
+        let record = synthetic_record(&station, &samples);
+
+        let residuals = model.residuals(&record, &orbit);
+        assert!((residuals.passes[0].stats().mean - 0.1).abs() > 1.0E-3);
+
+        let residuals = model.with_troposphere(true).residuals(&record, &orbit);
+        let stats = residuals.passes[0].stats();
+        assert!((stats.mean - 0.1).abs() < 1.0E-6);
+        assert!(stats.std_dev < 1.0E-6);
+    }
+}
@@ -0,0 +1,373 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use crate::{
+    fmt_comment, fmt_doris,
+    prelude::{ClockOffset, Epoch, FormattingError, Header, TimeScale, Version, DORIS},
+};
+
+/// Formats this value in Fortran E19.12 style (two digit exponent)
+fn fmt_scientific(value: f64) -> String {
+    let formatted = format!("{:.12E}", value);
+
+    let (mantissa, exponent) = formatted.split_once('E').unwrap_or((&formatted, "0"));
+
+    let exponent = exponent.parse::<i32>().unwrap_or_default();
+    let sign = if exponent < 0 { '-' } else { '+' };
+
+    format!(
+        "{:>19}",
+        format!("{}E{}{:02}", mantissa, sign, exponent.abs())
+    )
+}
+
+/// Formats this [Epoch] as RINEX Clock epoch, in [TimeScale::TAI]
+fn fmt_epoch(epoch: Epoch) -> String {
+    let (year, month, day, hours, mins, secs, nanos) = epoch.to_gregorian(TimeScale::TAI);
+
+    format!(
+        "{:4}{:3}{:3}{:3}{:3}{:10.6}",
+        year,
+        month,
+        day,
+        hours,
+        mins,
+        secs as f64 + nanos as f64 * 1.0E-9
+    )
+}
+
+impl DORIS {
+    /// Returns true if given RINEX Clock [Version] may be produced
+    fn supported_rinex_clock_version(version: Version) -> bool {
+        version.major == 3
+    }
+
+    /// Returns the width of the satellite name field, for given [Version]
+    /// (9 characters since 3.04, 4 characters before)
+    fn rinex_clock_name_width(version: Version) -> usize {
+        if version.minor >= 4 {
+            9
+        } else {
+            4
+        }
+    }
+
+    /// Returns the satellite name, as used in RINEX Clock files, for given [Version]
+    fn rinex_clock_satellite(&self, version: Version) -> String {
+        self.header
+            .satellite
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .map(|c| c.to_ascii_uppercase())
+            .take(Self::rinex_clock_name_width(version))
+            .collect()
+    }
+
+    /// Returns the "ANALYSIS CENTER" content: 3 character designator and full name,
+    /// both deduced from the production agency
+    fn rinex_clock_analysis_center(&self) -> String {
+        let agency = self.header.agency.as_deref().unwrap_or_default().trim();
+
+        let designator = agency
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .map(|c| c.to_ascii_uppercase())
+            .take(3)
+            .collect::<String>();
+
+        format!("{:<3}  {}", designator, agency)
+    }
+
+    /// Returns the satellite clock offsets to be exported
+    fn rinex_clock_offsets(&self, extrapolated: bool) -> Vec<(Epoch, ClockOffset)> {
+        self.satellite_clock_offset_iter()
+            .filter(|(_, clock_offset)| extrapolated || !clock_offset.extrapolated)
+            .collect()
+    }
+
+    /// Generates a standard RINEX Clock (short) file name, for this [DORIS] file,
+    /// from the satellite name and the day of the first clock offset.
+    pub fn rinex_clock_filename(&self) -> String {
+        let mut name = self.rinex_clock_satellite(Version::new(3, 0));
+        name.make_ascii_lowercase();
+
+        let (year, doy) = match self.satellite_clock_offset_iter().next() {
+            Some((epoch, _)) => (epoch.year(), epoch.day_of_year().floor() as u32),
+            None => (2000, 1),
+        };
+
+        format!("{:x<4}{:03}0.{:02}c", name, doy, year % 100)
+    }
+
+    /// Formats the satellite (=onboard receiver) [ClockOffset]s of this [DORIS] file,
+    /// as a RINEX Clock file (AS records), in desired [Version] (3.x) into [Write]able interface.
+    /// Epochs and offsets are expressed in [TimeScale::TAI]. The analysis center
+    /// is the production agency of the [Header].
+    /// RINEX Clock does not allow flagging a clock record: when `extrapolated` is set,
+    /// extrapolated offsets are exported and the extrapolated periods are described
+    /// in the header comments. Otherwise, only actual measurements are exported.
+    pub fn format_rinex_clock<W: Write>(
+        &self,
+        w: &mut BufWriter<W>,
+        version: Version,
+        extrapolated: bool,
+    ) -> Result<(), FormattingError> {
+        if !Self::supported_rinex_clock_version(version) {
+            return Err(FormattingError::RinexRevision(version));
+        }
+
+        let satellite = self.rinex_clock_satellite(version);
+        let offsets = self.rinex_clock_offsets(extrapolated);
+
+        writeln!(
+            w,
+            "{}",
+            fmt_doris(
+                &format!(
+                    "{:9.2}           C",
+                    version.major as f64 + version.minor as f64 / 100.0
+                ),
+                "RINEX VERSION / TYPE"
+            )
+        )?;
+
+        let program = format!(
+            "doris-rs v{}",
+            Header::format_pkg_version(env!("CARGO_PKG_VERSION"))
+        );
+
+        writeln!(
+            w,
+            "{}",
+            fmt_doris(
+                &format!(
+                    "{:<20}{:<20}{}",
+                    program,
+                    self.header.run_by.as_deref().unwrap_or_default(),
+                    self.header.date.as_deref().unwrap_or_default(),
+                ),
+                "PGM / RUN BY / DATE"
+            )
+        )?;
+
+        writeln!(
+            w,
+            "{}",
+            fmt_comment(&format!(
+                "DORIS onboard receiver clock offsets of {}",
+                self.header.satellite
+            ))
+        )?;
+
+        if let Some(cospar) = &self.header.cospar {
+            writeln!(w, "{}", fmt_comment(&format!("COSPAR ID {}", cospar)))?;
+        }
+
+        // describe the extrapolated periods
+        let mut periods = Vec::<(Epoch, Epoch)>::new();
+        let mut previous_extrapolated = false;
+
+        for (epoch, clock_offset) in offsets.iter() {
+            if clock_offset.extrapolated {
+                match periods.last_mut() {
+                    Some((_, end)) if previous_extrapolated => *end = *epoch,
+                    _ => periods.push((*epoch, *epoch)),
+                }
+            }
+
+            previous_extrapolated = clock_offset.extrapolated;
+        }
+
+        for (start, end) in periods.iter() {
+            writeln!(
+                w,
+                "{}",
+                fmt_comment(&format!("EXTRAPOLATED FROM  {}", fmt_epoch(*start)))
+            )?;
+
+            writeln!(
+                w,
+                "{}",
+                fmt_comment(&format!("EXTRAPOLATED UNTIL {}", fmt_epoch(*end)))
+            )?;
+        }
+
+        writeln!(w, "{}", fmt_doris("   TAI", "TIME SYSTEM ID"))?;
+        writeln!(w, "{}", fmt_doris("     1    AS", "# / TYPES OF DATA"))?;
+
+        writeln!(
+            w,
+            "{}",
+            fmt_doris(&self.rinex_clock_analysis_center(), "ANALYSIS CENTER")
+        )?;
+
+        writeln!(w, "{}", fmt_doris("     1", "# OF SOLN SATS"))?;
+        writeln!(w, "{}", fmt_doris(&satellite, "PRN LIST"))?;
+        writeln!(w, "{}", fmt_doris("", "END OF HEADER"))?;
+
+        let width = Self::rinex_clock_name_width(version);
+
+        for (epoch, clock_offset) in offsets.iter() {
+            writeln!(
+                w,
+                "AS {:<width$} {}  1   {}",
+                satellite,
+                fmt_epoch(*epoch),
+                fmt_scientific(clock_offset.offset.to_seconds()),
+                width = width,
+            )?;
+        }
+
+        w.flush()?;
+        Ok(())
+    }
+
+    /// Dumps the satellite [ClockOffset]s of this [DORIS] file into local RINEX Clock file.
+    /// See [Self::format_rinex_clock] for more information.
+    ///
+    /// ```
+    /// use doris_rs::prelude::*;
+    ///
+    /// let doris = DORIS::from_gzip_file("data/DOR/V3/cs2rx18164.gz")
+    ///     .unwrap();
+    ///
+    /// let path = std::env::temp_dir().join(doris.rinex_clock_filename());
+    ///
+    /// doris.to_rinex_clock_file(path, Version::new(3, 4), true)
+    ///     .unwrap();
+    /// ```
+    pub fn to_rinex_clock_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+        version: Version,
+        extrapolated: bool,
+    ) -> Result<(), FormattingError> {
+        let fd = File::create(path)?;
+        let mut writer = BufWriter::new(fd);
+        self.format_rinex_clock(&mut writer, version, extrapolated)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::fmt_scientific;
+    use crate::prelude::{
+        ClockOffset, Duration, EpochFlag, Header, Key, Measurements, Version, DORIS,
+    };
+    use crate::tests::toolkit::synthetic_t0;
+
+    use std::io::BufWriter;
+
+    fn format(doris: &DORIS, version: Version, extrapolated: bool) -> String {
+        let mut writer = BufWriter::new(Vec::new());
+
+        doris
+            .format_rinex_clock(&mut writer, version, extrapolated)
+            .unwrap();
+
+        String::from_utf8(writer.into_inner().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn scientific_formatting() {
+        assert_eq!(fmt_scientific(-4.326631626), "-4.326631626000E+00");
+        assert_eq!(fmt_scientific(1.5E-12), " 1.500000000000E-12");
+        assert_eq!(fmt_scientific(0.0), " 0.000000000000E+00");
+    }
+
+    #[test]
+    fn rinex_clock_formatting() {
+        let t0 = synthetic_t0();
+
+        let mut header = Header::default().with_run_by("CNES");
+        header.satellite = "CRYOSAT-2".to_string();
+        header.agency = Some("CNES".to_string());
+
+        let mut doris = DORIS {
+            header,
+            ..Default::default()
+        };
+
+        for (i, extrapolated) in [false, false, true, true].iter().enumerate() {
+            let offset = Duration::from_seconds(-4.326631626 + i as f64 * 1.0E-9);
+
+            let clock_offset = if *extrapolated {
+                ClockOffset::from_extrapolated_offset(offset)
+            } else {
+                ClockOffset::from_measured_offset(offset)
+            };
+
+            doris.record.measurements.insert(
+                Key {
+                    epoch: t0 + Duration::from_seconds(10.0 * i as f64),
+                    flag: EpochFlag::OK,
+                },
+                Measurements::default().with_satellite_clock_offset(clock_offset),
+            );
+        }
+
+        assert_eq!(doris.rinex_clock_filename(), "cryo1640.18c");
+
+        let v304 = format(&doris, Version::new(3, 4), true);
+        let lines = v304.lines().collect::<Vec<_>>();
+
+        assert_eq!(
+            lines[0],
+            "     3.04           C                                       RINEX VERSION / TYPE"
+        );
+
+        assert!(lines[2].contains("CRYOSAT-2"));
+
+        assert_eq!(
+            lines[3],
+            "EXTRAPOLATED FROM  2018  6 13  0  0 20.000000               COMMENT"
+        );
+
+        assert_eq!(
+            lines[4],
+            "EXTRAPOLATED UNTIL 2018  6 13  0  0 30.000000               COMMENT"
+        );
+
+        assert!(lines[5].starts_with("   TAI") && lines[5].ends_with("TIME SYSTEM ID"));
+
+        assert_eq!(
+            lines[7],
+            "CNE  CNES                                                   ANALYSIS CENTER"
+        );
+
+        assert!(lines[9].starts_with("CRYOSAT2 ") && lines[9].ends_with("PRN LIST"));
+        assert!(lines[10].ends_with("END OF HEADER"));
+
+        assert_eq!(lines.len(), 15);
+
+        assert_eq!(
+            lines[11],
+            "AS CRYOSAT2  2018  6 13  0  0  0.000000  1   -4.326631626000E+00"
+        );
+
+        assert_eq!(
+            lines[14],
+            "AS CRYOSAT2  2018  6 13  0  0 30.000000  1   -4.326631623000E+00"
+        );
+
+        let v300 = format(&doris, Version::new(3, 0), false);
+        let lines = v300.lines().collect::<Vec<_>>();
+
+        assert!(lines.iter().all(|line| !line.starts_with("EXTRAPOLATED")));
+        assert_eq!(lines.len(), 11);
+
+        assert_eq!(
+            lines[9],
+            "AS CRYO 2018  6 13  0  0  0.000000  1   -4.326631626000E+00"
+        );
+
+        let mut writer = BufWriter::new(Vec::new());
+
+        assert!(doris
+            .format_rinex_clock(&mut writer, Version::new(2, 0), true)
+            .is_err());
+    }
+}
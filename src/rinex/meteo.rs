@@ -0,0 +1,360 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    fmt_comment, fmt_doris,
+    meteo::MeteoSeries,
+    prelude::{Duration, FormattingError, Header, TimeScale, Version, DORIS},
+};
+
+/// RINEX meteo observables, in order of appearance
+const OBSERVABLES: [&str; 3] = ["PR", "TD", "HR"];
+
+impl MeteoSeries {
+    /// Returns true if given RINEX [Version] may be produced
+    fn supported_rinex_version(version: Version) -> bool {
+        matches!(version.major, 3 | 4) || (version.major == 2 && version.minor == 11)
+    }
+
+    /// Generates a standard RINEX Meteo file name for this [MeteoSeries], from the station
+    /// label and the day of the first sample, following the naming convention of
+    /// the RINEX [Version]: short name (`ssssdddf.yym`) for RINEX 2, long name
+    /// (`SSSSMRCCC_R_YYYYDDDHHMM_01D_MM.rnx`) otherwise. The country code is not known
+    /// from the DORIS file, and is set to `XXX` in long names.
+    pub fn rinex_filename(&self, version: Version) -> String {
+        let (year, doy) = match self.samples.first() {
+            Some(sample) => {
+                let epoch = sample.epoch.to_time_scale(TimeScale::GPST);
+                (epoch.year(), epoch.day_of_year().floor() as u32)
+            },
+            None => (2000, 1),
+        };
+
+        if version.major < 3 {
+            let mut label = self.station.label.to_lowercase();
+            label.truncate(4);
+
+            format!("{:x<4}{:03}0.{:02}m", label, doy, year % 100)
+        } else {
+            let mut label = self.station.label.to_uppercase();
+            label.truncate(4);
+
+            format!("{:X<4}00XXX_R_{:04}{:03}0000_01D_MM.rnx", label, year, doy)
+        }
+    }
+
+    /// Formats this [MeteoSeries] as a RINEX Meteo file, in desired [Version]
+    /// (2.11, 3.x or 4.x), into [Write]able interface.
+    /// The DORIS [Header] is used to describe the production context.
+    /// Epochs are expressed in [TimeScale::GPST], as per RINEX specifications, rounded
+    /// to the nearest second. The pressure sensor position is that of the station, when known.
+    pub fn format_rinex<W: Write>(
+        &self,
+        w: &mut BufWriter<W>,
+        version: Version,
+        header: &Header,
+    ) -> Result<(), FormattingError> {
+        if !Self::supported_rinex_version(version) {
+            return Err(FormattingError::RinexRevision(version));
+        }
+
+        writeln!(
+            w,
+            "{}",
+            fmt_doris(
+                &format!(
+                    "{:6}.{:02}           METEOROLOGICAL DATA",
+                    version.major, version.minor
+                ),
+                "RINEX VERSION / TYPE"
+            )
+        )?;
+
+        let program = format!(
+            "doris-rs v{}",
+            Header::format_pkg_version(env!("CARGO_PKG_VERSION"))
+        );
+
+        writeln!(
+            w,
+            "{}",
+            fmt_doris(
+                &format!(
+                    "{:<20}{:<20}{}",
+                    program,
+                    header.run_by.as_deref().unwrap_or_default(),
+                    header.date.as_deref().unwrap_or_default(),
+                ),
+                "PGM / RUN BY / DATE"
+            )
+        )?;
+
+        writeln!(
+            w,
+            "{}",
+            fmt_comment(&format!(
+                "DORIS beacon {} meteo, observed by {}",
+                self.station.label, header.satellite
+            ))
+        )?;
+
+        writeln!(w, "{}", fmt_doris(&self.station.site, "MARKER NAME"))?;
+
+        writeln!(
+            w,
+            "{}",
+            fmt_doris(&self.station.domes.to_string(), "MARKER NUMBER")
+        )?;
+
+        let mut types = format!("{:6}", OBSERVABLES.len());
+        for observable in OBSERVABLES.iter() {
+            types.push_str(&format!("    {}", observable));
+        }
+
+        writeln!(w, "{}", fmt_doris(&types, "# / TYPES OF OBSERV"))?;
+
+        let sensor_type = format!("DORIS BEACON REV{}", self.station.beacon_revision);
+
+        for observable in OBSERVABLES.iter() {
+            writeln!(
+                w,
+                "{}",
+                fmt_doris(
+                    &format!(
+                        "{:<20}{:<20}      {:7.1}    {}",
+                        "", sensor_type, 0.0, observable
+                    ),
+                    "SENSOR MOD/TYPE/ACC"
+                )
+            )?;
+        }
+
+        if let Some(coordinates) = &self.station.coordinates {
+            let (x, y, z) = coordinates.position_m;
+            let (_, _, height) = coordinates.geodetic();
+
+            writeln!(
+                w,
+                "{}",
+                fmt_doris(
+                    &format!(
+                        "{:14.4}{:14.4}{:14.4}{:14.4} {}",
+                        x, y, z, height, OBSERVABLES[0]
+                    ),
+                    "SENSOR POS XYZ/H"
+                )
+            )?;
+        }
+
+        writeln!(w, "{}", fmt_doris("", "END OF HEADER"))?;
+
+        for sample in self.samples.iter() {
+            // epochs are described to the nearest second
+            let (year, month, day, hours, mins, secs, _) = sample
+                .epoch
+                .round(Duration::from_seconds(1.0))
+                .to_gregorian(TimeScale::GPST);
+
+            if version.major < 3 {
+                write!(
+                    w,
+                    " {:02} {:02} {:02} {:02} {:02} {:02}",
+                    year % 100,
+                    month,
+                    day,
+                    hours,
+                    mins,
+                    secs
+                )?;
+            } else {
+                write!(
+                    w,
+                    " {:04} {:02} {:02} {:02} {:02} {:02}",
+                    year, month, day, hours, mins, secs
+                )?;
+            }
+
+            writeln!(
+                w,
+                "{:7.1}{:7.1}{:7.1}",
+                sample.pressure, sample.temperature, sample.humidity
+            )?;
+        }
+
+        w.flush()?;
+        Ok(())
+    }
+
+    /// Dumps this [MeteoSeries] into local RINEX Meteo file, in desired [Version].
+    /// See [Self::format_rinex] for more information.
+    pub fn to_rinex_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+        version: Version,
+        header: &Header,
+    ) -> Result<(), FormattingError> {
+        let fd = File::create(path)?;
+        let mut writer = BufWriter::new(fd);
+        self.format_rinex(&mut writer, version, header)
+    }
+}
+
+impl DORIS {
+    /// Dumps the meteo observations of this [DORIS] file, as one RINEX Meteo file
+    /// per ground station, in desired [Version] (2.11, 3.x or 4.x), within given directory.
+    /// Files are named according to [MeteoSeries::rinex_filename], which depends on [Version].
+    /// Returns the list of files that were produced.
+    ///
+    /// ```
+    /// use doris_rs::prelude::*;
+    ///
+    /// let doris = DORIS::from_gzip_file("data/DOR/V3/cs2rx18164.gz")
+    ///     .unwrap();
+    ///
+    /// let files = doris.to_rinex_meteo_files(std::env::temp_dir(), Version::new(3, 5))
+    ///     .unwrap();
+    ///
+    /// assert!(!files.is_empty());
+    /// ```
+    pub fn to_rinex_meteo_files<P: AsRef<Path>>(
+        &self,
+        directory: P,
+        version: Version,
+    ) -> Result<Vec<PathBuf>, FormattingError> {
+        let mut files = Vec::new();
+
+        for series in self.record.meteo_series() {
+            let path = directory.as_ref().join(series.rinex_filename(version));
+            series.to_rinex_file(&path, version, &self.header)?;
+            files.push(path);
+        }
+
+        Ok(files)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::meteo::{MeteoSample, MeteoSeries};
+    use crate::prelude::{Duration, GroundStation, Header, StationCoordinates, Version};
+    use crate::tests::toolkit::synthetic_t0;
+
+    use std::io::BufWriter;
+
+    fn format(series: &MeteoSeries, version: Version) -> String {
+        let header = Header::default().with_run_by("CNES");
+
+        let mut writer = BufWriter::new(Vec::new());
+
+        series.format_rinex(&mut writer, version, &header).unwrap();
+
+        String::from_utf8(writer.into_inner().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn rinex_meteo_formatting() {
+        let station = GroundStation::default()
+            .with_site_label("TLSB")
+            .with_site_name("TOULOUSE")
+            .with_domes_str("10003S005")
+            .unwrap();
+
+        let series = MeteoSeries {
+            station,
+            samples: vec![MeteoSample {
+                epoch: synthetic_t0() + Duration::from_seconds(37.0),
+                pressure: 1013.2,
+                temperature: 21.5,
+                humidity: 55.0,
+            }],
+        };
+
+        assert_eq!(series.rinex_filename(Version::new(2, 11)), "tlsb1640.18m");
+
+        assert_eq!(
+            series.rinex_filename(Version::new(3, 5)),
+            "TLSB00XXX_R_20181640000_01D_MM.rnx"
+        );
+
+        let v2 = format(&series, Version::new(2, 11));
+        let lines = v2.lines().collect::<Vec<_>>();
+
+        assert_eq!(
+            lines[0],
+            "     2.11           METEOROLOGICAL DATA                     RINEX VERSION / TYPE"
+        );
+
+        assert!(lines[3].starts_with("TOULOUSE"));
+        assert!(lines[3].ends_with("MARKER NAME"));
+
+        assert!(lines[4].starts_with("10003S005"));
+        assert!(lines[4].ends_with("MARKER NUMBER"));
+
+        assert_eq!(
+            lines[5],
+            "     3    PR    TD    HR                                    # / TYPES OF OBSERV"
+        );
+
+        assert_eq!(
+            lines[6],
+            "                    DORIS BEACON REV3             0.0    PR SENSOR MOD/TYPE/ACC"
+        );
+
+        // TAI = GPST + 19s
+        assert_eq!(lines[10], " 18 06 13 00 00 18 1013.2   21.5   55.0");
+
+        let v4 = format(&series, Version::new(4, 0));
+        let lines = v4.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines[10], " 2018 06 13 00 00 18 1013.2   21.5   55.0");
+
+        let mut writer = BufWriter::new(Vec::new());
+
+        assert!(series
+            .format_rinex(&mut writer, Version::new(2, 10), &Header::default())
+            .is_err());
+    }
+
+    #[test]
+    fn rinex_meteo_sensor_position() {
+        let t0 = synthetic_t0();
+
+        // on the equator, 100 m above the ellipsoid
+        let station = GroundStation::default()
+            .with_site_label("TLSB")
+            .with_site_name("TOULOUSE")
+            .with_domes_str("10003S005")
+            .unwrap()
+            .with_coordinates(StationCoordinates {
+                epoch: t0,
+                position_m: (6378237.0, 0.0, 0.0),
+                velocity_m_yr: (0.0, 0.0, 0.0),
+            });
+
+        let series = MeteoSeries {
+            station,
+            samples: vec![MeteoSample {
+                epoch: t0 + Duration::from_seconds(37.6),
+                pressure: 1013.2,
+                temperature: 21.5,
+                humidity: 55.0,
+            }],
+        };
+
+        let v3 = format(&series, Version::new(3, 5));
+        let lines = v3.lines().collect::<Vec<_>>();
+
+        assert_eq!(
+            lines[9],
+            "  6378237.0000        0.0000        0.0000      100.0000 PR SENSOR POS XYZ/H"
+        );
+
+        assert!(lines[10].ends_with("END OF HEADER"));
+
+        // TAI = GPST + 19s, rounded to the nearest second
+        assert_eq!(lines[11], " 2018 06 13 00 00 19 1013.2   21.5   55.0");
+    }
+}
@@ -0,0 +1,3 @@
+//! Standard RINEX products, derived from DORIS data
+mod clock;
+mod meteo;
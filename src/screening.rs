@@ -0,0 +1,607 @@
+//! Outlier screening, using robust statistics
+use std::collections::HashSet;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    prelude::{Duration, Epoch, GroundStation, Matcher, Observable, Record, StationHandle},
+    statistics::{mean_std_dev, median_mad},
+};
+
+#[cfg(doc)]
+use crate::prelude::Pass;
+
+/// [RobustEstimator] used to determine the central value and dispersion
+/// of a serie, prior outlier rejection.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RobustEstimator {
+    /// Median value and (scaled) median absolute deviation
+    #[default]
+    MedianAbsoluteDeviation,
+
+    /// Iterative sigma clipping, around the mean value
+    SigmaClipping,
+}
+
+/// [ScreeningTest] describes which test rejected an observation.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ScreeningTest {
+    /// Test applied to the raw observation values
+    Raw,
+
+    /// Test applied to the time differenced values (rate of change)
+    TimeDifferenced,
+
+    /// Test applied to the code minus phase combination.
+    /// Only the [Observable::PseudoRange] observation is rejected.
+    CodeMinusPhase,
+}
+
+/// [Rejection] describes one observation rejected by the [OutlierScreening].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Rejection {
+    /// [GroundStation] being observed
+    pub station: GroundStation,
+
+    /// [Epoch] of observation
+    pub epoch: Epoch,
+
+    /// Rejected [Observable]
+    pub observable: Observable,
+
+    /// Rejected value
+    pub value: f64,
+
+    /// [ScreeningTest] that rejected this observation
+    pub test: ScreeningTest,
+
+    /// Normalized deviation (in robust sigma units) that led to this rejection
+    pub score: f64,
+}
+
+/// [ScreeningReport] lists all observations rejected by the [OutlierScreening],
+/// in chronological order.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ScreeningReport {
+    /// Rejected observations
+    pub rejections: Vec<Rejection>,
+}
+
+impl ScreeningReport {
+    /// Total number of rejected observations
+    pub fn len(&self) -> usize {
+        self.rejections.len()
+    }
+
+    /// Returns true if no observations were rejected
+    pub fn is_empty(&self) -> bool {
+        self.rejections.is_empty()
+    }
+
+    /// Returns [Rejection]s [Iterator] for matching [GroundStation]
+    pub fn station_rejections_iter<'a>(
+        &'a self,
+        matcher: &'a Matcher<'a>,
+    ) -> Box<dyn Iterator<Item = &'a Rejection> + 'a> {
+        Box::new(
+            self.rejections
+                .iter()
+                .filter(move |rejection| rejection.station.matches(matcher)),
+        )
+    }
+}
+
+/// Rejected observations, each rejected only once
+#[derive(Default)]
+struct Rejections {
+    rejected: HashSet<(StationHandle, Epoch, Observable)>,
+    rejections: Vec<Rejection>,
+}
+
+impl Rejections {
+    fn contains(&self, station: &StationHandle, epoch: Epoch, observable: Observable) -> bool {
+        self.rejected
+            .contains(&(station.clone(), epoch, observable))
+    }
+
+    fn reject(
+        &mut self,
+        station: &StationHandle,
+        observable: Observable,
+        (epoch, value): (Epoch, f64),
+        test: ScreeningTest,
+        score: f64,
+    ) {
+        if self.rejected.insert((station.clone(), epoch, observable)) {
+            self.rejections.push(Rejection {
+                station: station.station().clone(),
+                epoch,
+                observable,
+                value,
+                test,
+                score,
+            });
+        }
+    }
+}
+
+/// [OutlierScreening] rejects outliers per [GroundStation], [Observable] and [Pass],
+/// using robust statistics. Each test is enabled by defining its threshold,
+/// expressed in robust sigma units. The time differenced test is sensitive to
+/// isolated bad samples, including the first and last sample of each [Pass].
+///
+/// ```
+/// use doris_rs::prelude::*;
+///
+/// let mut doris = DORIS::from_gzip_file("data/DOR/V3/cs2rx18164.gz")
+///     .unwrap();
+///
+/// let screening = OutlierScreening::default()
+///     .with_estimator(RobustEstimator::SigmaClipping)
+///     .with_raw_threshold(Some(6.0));
+///
+/// // remove outliers, and report on what was removed
+/// let report = screening.screen_mut(&mut doris.record);
+///
+/// for rejection in report.rejections.iter() {
+///     println!("{} {} {} rejected ({:?})",
+///         rejection.epoch, rejection.station, rejection.observable, rejection.test);
+/// }
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OutlierScreening {
+    /// [RobustEstimator] to be used
+    pub estimator: RobustEstimator,
+
+    /// Maximal number of iterations, when using [RobustEstimator::SigmaClipping]
+    pub max_iterations: usize,
+
+    /// Maximal data gap: a new [Pass] starts after this [Duration] without observations
+    pub max_gap: Duration,
+
+    /// Minimal number of samples per [Pass], for the statistics to be meaningful.
+    /// Smaller [Pass]es are not screened.
+    pub min_samples: usize,
+
+    /// Raw value test threshold
+    pub raw_threshold: Option<f64>,
+
+    /// Time differenced value test threshold
+    pub time_differenced_threshold: Option<f64>,
+
+    /// Code minus phase test threshold
+    pub code_minus_phase_threshold: Option<f64>,
+}
+
+impl Default for OutlierScreening {
+    /// Builds a default [OutlierScreening] using the median absolute deviation,
+    /// 10' maximal data gap, and a 5 sigma threshold for the time differenced
+    /// and code minus phase tests. The raw value test is disabled.
+    fn default() -> Self {
+        Self {
+            estimator: RobustEstimator::default(),
+            max_iterations: 10,
+            max_gap: Duration::from_seconds(600.0),
+            min_samples: 5,
+            raw_threshold: None,
+            time_differenced_threshold: Some(5.0),
+            code_minus_phase_threshold: Some(5.0),
+        }
+    }
+}
+
+impl OutlierScreening {
+    /// Copies and returns [OutlierScreening] with updated [RobustEstimator]
+    pub fn with_estimator(&self, estimator: RobustEstimator) -> Self {
+        let mut s = *self;
+        s.estimator = estimator;
+        s
+    }
+
+    /// Copies and returns [OutlierScreening] with updated maximal data gap
+    pub fn with_max_gap(&self, max_gap: Duration) -> Self {
+        let mut s = *self;
+        s.max_gap = max_gap;
+        s
+    }
+
+    /// Copies and returns [OutlierScreening] with updated minimal number of samples per pass
+    pub fn with_min_samples(&self, min_samples: usize) -> Self {
+        let mut s = *self;
+        s.min_samples = min_samples;
+        s
+    }
+
+    /// Copies and returns [OutlierScreening] with updated raw value test threshold.
+    /// Use None to disable this test.
+    pub fn with_raw_threshold(&self, threshold: Option<f64>) -> Self {
+        let mut s = *self;
+        s.raw_threshold = threshold;
+        s
+    }
+
+    /// Copies and returns [OutlierScreening] with updated time differenced test threshold.
+    /// Use None to disable this test.
+    pub fn with_time_differenced_threshold(&self, threshold: Option<f64>) -> Self {
+        let mut s = *self;
+        s.time_differenced_threshold = threshold;
+        s
+    }
+
+    /// Copies and returns [OutlierScreening] with updated code minus phase test threshold.
+    /// Use None to disable this test.
+    pub fn with_code_minus_phase_threshold(&self, threshold: Option<f64>) -> Self {
+        let mut s = *self;
+        s.code_minus_phase_threshold = threshold;
+        s
+    }
+
+    /// Returns (index, score) of each outlier in this serie
+    fn outliers(&self, values: &[f64], threshold: f64) -> Vec<(usize, f64)> {
+        let (center, scale) = match self.estimator {
+            RobustEstimator::MedianAbsoluteDeviation => match median_mad(values) {
+                Some(stats) => stats,
+                None => return Vec::new(),
+            },
+            RobustEstimator::SigmaClipping => {
+                let mut kept = vec![true; values.len()];
+                let mut stats = (0.0, 0.0);
+
+                for _ in 0..self.max_iterations {
+                    let retained = values
+                        .iter()
+                        .zip(kept.iter())
+                        .filter_map(|(value, kept)| if *kept { Some(*value) } else { None })
+                        .collect::<Vec<_>>();
+
+                    stats = match mean_std_dev(&retained) {
+                        Some(stats) => stats,
+                        None => return Vec::new(),
+                    };
+
+                    let mut updated = false;
+
+                    for (value, kept) in values.iter().zip(kept.iter_mut()) {
+                        let inlier = (value - stats.0).abs() <= threshold * stats.1;
+
+                        if *kept != inlier {
+                            *kept = inlier;
+                            updated = true;
+                        }
+                    }
+
+                    if !updated {
+                        break;
+                    }
+                }
+
+                stats
+            },
+        };
+
+        if scale <= 0.0 {
+            return Vec::new();
+        }
+
+        values
+            .iter()
+            .enumerate()
+            .filter_map(|(index, value)| {
+                let score = (value - center).abs() / scale;
+                if score > threshold {
+                    Some((index, score))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Splits this chronological serie into [Pass]es
+    fn passes<'a>(&self, series: &'a [(Epoch, f64)]) -> Vec<&'a [(Epoch, f64)]> {
+        let mut passes = Vec::new();
+        let mut start = 0;
+
+        for index in 1..series.len() {
+            if series[index].0 - series[index - 1].0 > self.max_gap {
+                passes.push(&series[start..index]);
+                start = index;
+            }
+        }
+
+        if start < series.len() {
+            passes.push(&series[start..]);
+        }
+
+        passes
+    }
+
+    /// Time differenced test, applied to one [Pass].
+    /// A sample is rejected when both surrounding rates are outliers.
+    /// The first (last) sample of the [Pass] is rejected when its single rate
+    /// is an outlier while the following (preceding) rate is not: otherwise,
+    /// the outlier rate is explained by a spike on the second (penultimate) sample.
+    fn time_differenced_outliers(
+        &self,
+        pass: &[(Epoch, f64)],
+        threshold: f64,
+    ) -> Vec<(usize, f64)> {
+        let rates = pass
+            .iter()
+            .zip(pass.iter().skip(1))
+            .map(|((t_1, v_1), (t_2, v_2))| (v_2 - v_1) / (*t_2 - *t_1).to_seconds())
+            .collect::<Vec<_>>();
+
+        let mut scores = vec![None; rates.len()];
+
+        for (index, score) in self.outliers(&rates, threshold) {
+            scores[index] = Some(score);
+        }
+
+        let last = pass.len() - 1;
+
+        let is_outlier = |rate_index: Option<usize>| {
+            rate_index
+                .and_then(|rate_index| scores.get(rate_index).copied().flatten())
+                .is_some()
+        };
+
+        (0..pass.len())
+            .filter_map(|index| {
+                let left = if index > 0 { scores[index - 1] } else { None };
+                let right = if index < last { scores[index] } else { None };
+
+                match (left, right) {
+                    (Some(left), Some(right)) => Some((index, left.min(right))),
+                    (None, Some(right)) if index == 0 && !is_outlier(Some(1)) => {
+                        Some((index, right))
+                    },
+                    (Some(left), None) if index == last && !is_outlier(last.checked_sub(2)) => {
+                        Some((index, left))
+                    },
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    /// Screens this [Record] and returns the [ScreeningReport],
+    /// without modifying the [Record].
+    pub fn screen(&self, record: &Record) -> ScreeningReport {
+        let series = record.station_observable_series();
+
+        let mut rejections = Rejections::default();
+
+        for ((station, observable), series) in series.iter() {
+            for pass in self.passes(series) {
+                if pass.len() < self.min_samples {
+                    continue;
+                }
+
+                if let Some(threshold) = self.raw_threshold {
+                    let values = pass.iter().map(|(_, value)| *value).collect::<Vec<_>>();
+
+                    for (index, score) in self.outliers(&values, threshold) {
+                        rejections.reject(
+                            station,
+                            *observable,
+                            pass[index],
+                            ScreeningTest::Raw,
+                            score,
+                        );
+                    }
+                }
+
+                if let Some(threshold) = self.time_differenced_threshold {
+                    for (index, score) in self.time_differenced_outliers(pass, threshold) {
+                        rejections.reject(
+                            station,
+                            *observable,
+                            pass[index],
+                            ScreeningTest::TimeDifferenced,
+                            score,
+                        );
+                    }
+                }
+            }
+        }
+
+        // code minus phase test is performed last,
+        // so phase outliers are not attributed to the code observation.
+        if let Some(threshold) = self.code_minus_phase_threshold {
+            for ((station, observable), code) in series.iter() {
+                let phase_observable = match observable {
+                    Observable::PseudoRange(frequency) => {
+                        Observable::UnambiguousPhaseRange(*frequency)
+                    },
+                    _ => continue,
+                };
+
+                let phase = match series.get(&(station.clone(), phase_observable)) {
+                    Some(phase) => phase,
+                    None => continue,
+                };
+
+                for pass in self.passes(code) {
+                    let (samples, cmc): (Vec<_>, Vec<_>) = pass
+                        .iter()
+                        .filter_map(|(epoch, code)| {
+                            if rejections.contains(station, *epoch, phase_observable) {
+                                return None;
+                            }
+
+                            let index = phase.binary_search_by(|(t, _)| t.cmp(epoch)).ok()?;
+                            Some(((*epoch, *code), code - phase[index].1))
+                        })
+                        .unzip();
+
+                    if samples.len() < self.min_samples {
+                        continue;
+                    }
+
+                    for (index, score) in self.outliers(&cmc, threshold) {
+                        rejections.reject(
+                            station,
+                            *observable,
+                            samples[index],
+                            ScreeningTest::CodeMinusPhase,
+                            score,
+                        );
+                    }
+                }
+            }
+        }
+
+        let mut rejections = rejections.rejections;
+
+        rejections.sort_by(|a, b| {
+            (a.epoch, &a.station, a.observable).cmp(&(b.epoch, &b.station, b.observable))
+        });
+
+        ScreeningReport { rejections }
+    }
+
+    /// Screens this [Record] and removes all rejected observations (in place).
+    /// Returns the [ScreeningReport].
+    pub fn screen_mut(&self, record: &mut Record) -> ScreeningReport {
+        let report = self.screen(record);
+
+        let rejected = report
+            .rejections
+            .iter()
+            .map(|rejection| {
+                (
+                    StationHandle::from(&rejection.station),
+                    rejection.epoch,
+                    rejection.observable,
+                )
+            })
+            .collect::<HashSet<_>>();
+
+        record.measurements.retain(|key, measurements| {
+            measurements.observations.retain(|obs_key, _| {
+                !rejected.contains(&(obs_key.station.clone(), key.epoch, obs_key.observable))
+            });
+
+            !measurements.observations.is_empty()
+        });
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::Frequency;
+    use crate::tests::toolkit::{synthetic_record, synthetic_t0};
+
+    #[test]
+    fn outlier_screening() {
+        let station = GroundStation::default()
+            .with_site_label("TLSB")
+            .with_unique_id(13);
+
+        let l1 = Observable::UnambiguousPhaseRange(Frequency::DORIS1);
+        let c1 = Observable::PseudoRange(Frequency::DORIS1);
+
+        // smooth pass, with small noise, bad first phase sample,
+        // one phase spike and one code spike
+        let samples = (0..30)
+            .map(|i| {
+                let t = i as f64 * 10.0;
+                let noise = 0.01 * (i as f64 * 1.7).sin();
+                let range = 1.0E6 + 5.0E3 * t;
+
+                let phase = match i {
+                    0 => range + 500.0,
+                    15 => range + 300.0,
+                    _ => range + noise,
+                };
+
+                let code = if i == 20 {
+                    range + 100.0
+                } else {
+                    range - noise
+                };
+
+                (t, vec![(l1, phase), (c1, code)])
+            })
+            .collect::<Vec<_>>();
+
+        let mut record = synthetic_record(&station, &samples);
+
+        let screening = OutlierScreening::default();
+
+        let report = screening.screen(&record);
+
+        let rejected = report
+            .rejections
+            .iter()
+            .map(|rejection| {
+                (
+                    (rejection.epoch - synthetic_t0()).to_seconds(),
+                    rejection.observable,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(rejected, vec![(0.0, l1), (150.0, l1), (200.0, c1)]);
+
+        let matcher = Matcher::Label("TLSB");
+        assert_eq!(report.station_rejections_iter(&matcher).count(), 3);
+
+        let report = screening.screen_mut(&mut record);
+        assert_eq!(report.len(), 3);
+
+        assert!(screening.screen(&record).is_empty());
+
+        let sigma_clipping = screening.with_estimator(RobustEstimator::SigmaClipping);
+        assert!(sigma_clipping.screen(&record).is_empty());
+    }
+
+    #[test]
+    fn outlier_screening_edge_spikes() {
+        let station = GroundStation::default()
+            .with_site_label("TLSB")
+            .with_unique_id(13);
+
+        let l1 = Observable::UnambiguousPhaseRange(Frequency::DORIS1);
+
+        // spikes on the second and penultimate samples:
+        // first and last samples are valid and must be preserved
+        let samples = (0..30)
+            .map(|i| {
+                let t = i as f64 * 10.0;
+                let noise = 0.01 * (i as f64 * 1.7).sin();
+                let range = 1.0E6 + 5.0E3 * t;
+
+                let phase = match i {
+                    1 => range + 400.0,
+                    28 => range - 400.0,
+                    _ => range + noise,
+                };
+
+                (t, vec![(l1, phase)])
+            })
+            .collect::<Vec<_>>();
+
+        let record = synthetic_record(&station, &samples);
+
+        let report = OutlierScreening::default().screen(&record);
+
+        let rejected = report
+            .rejections
+            .iter()
+            .map(|rejection| (rejection.epoch - synthetic_t0()).to_seconds())
+            .collect::<Vec<_>>();
+
+        assert_eq!(rejected, vec![10.0, 280.0]);
+    }
+}
@@ -0,0 +1,464 @@
+//! SINEX station coordinates (for example DPOD solutions)
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufRead, BufReader, Read},
+    path::Path,
+};
+
+#[cfg(feature = "flate2")]
+use flate2::read::GzDecoder;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::{
+    Duration, Epoch, GroundStation, Header, ParsingError, Record, StationCoordinates,
+    StationHandle, TimeScale, DOMES, DORIS,
+};
+
+/// [SinexSolution] is one station position and velocity solution,
+/// valid over a given time frame (solutions are split at station discontinuities).
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SinexSolution {
+    /// 4 letter site code
+    pub code: String,
+
+    /// [DOMES] site identifier
+    pub domes: DOMES,
+
+    /// Solution number
+    pub solution: String,
+
+    /// Start of validity, None when unbounded
+    pub start: Option<Epoch>,
+
+    /// End of validity, None when unbounded
+    pub end: Option<Epoch>,
+
+    /// [StationCoordinates] at the reference [Epoch] of this solution
+    pub coordinates: StationCoordinates,
+}
+
+impl SinexSolution {
+    /// Returns true if this [SinexSolution] applies at this [Epoch]
+    pub fn is_valid(&self, epoch: Epoch) -> bool {
+        let after_start = self.start.map(|start| epoch >= start).unwrap_or(true);
+        let before_end = self.end.map(|end| epoch <= end).unwrap_or(true);
+        after_start && before_end
+    }
+}
+
+/// [Sinex] gathers the station solutions of a SINEX file.
+/// Only the SITE/ID, SOLUTION/EPOCHS and SOLUTION/ESTIMATE blocks
+/// are interpreted.
+///
+/// ```no_run
+/// use doris_rs::prelude::*;
+///
+/// let sinex = Sinex::from_gzip_file("data/SNX/dpod2020_031.snx.gz")
+///     .unwrap();
+///
+/// // coordinates propagated to the beginning of the day
+/// let t0 = Epoch::from_gregorian_utc_at_midnight(2018, 6, 13);
+///
+/// let doris = DORIS::from_gzip_file("data/DOR/V3/cs2rx18164.gz")
+///     .unwrap()
+///     .with_station_coordinates(&sinex, t0);
+///
+/// for station in doris.header.ground_stations.iter() {
+///     if let Some(coordinates) = station.coordinates {
+///         let (lat_deg, long_deg, height_m) = coordinates.geodetic();
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Sinex {
+    /// [SinexSolution]s
+    pub solutions: Vec<SinexSolution>,
+}
+
+/// Solution being gathered while parsing
+#[derive(Default)]
+struct PendingSolution {
+    reference: Option<Epoch>,
+    position_m: [Option<f64>; 3],
+    velocity_m_yr: [f64; 3],
+    start: Option<Epoch>,
+    end: Option<Epoch>,
+}
+
+/// Parses a SINEX "YY:DDD:SSSSS" (or "YYYY:DDD:SSSSS") epoch.
+/// Returns None for the null (00:000:00000) epoch.
+fn parse_sinex_epoch(content: &str) -> Result<Option<Epoch>, ParsingError> {
+    let mut items = content.trim().split(':');
+
+    let (Some(year), Some(doy), Some(secs), None) =
+        (items.next(), items.next(), items.next(), items.next())
+    else {
+        return Err(ParsingError::SinexFormat);
+    };
+
+    let two_digit_year = year.len() == 2;
+
+    let year = year.parse::<i32>().map_err(|_| ParsingError::SinexFormat)?;
+    let doy = doy.parse::<u16>().map_err(|_| ParsingError::SinexFormat)?;
+    let secs = secs.parse::<f64>().map_err(|_| ParsingError::SinexFormat)?;
+
+    if year == 0 && doy == 0 && secs == 0.0 {
+        return Ok(None);
+    }
+
+    let year = if !two_digit_year {
+        year
+    } else if year <= 50 {
+        year + 2000
+    } else {
+        year + 1900
+    };
+
+    let epoch = Epoch::from_gregorian_at_midnight(year, 1, 1, TimeScale::UTC)
+        + Duration::from_days(doy.saturating_sub(1) as f64)
+        + Duration::from_seconds(secs);
+
+    Ok(Some(epoch))
+}
+
+impl Sinex {
+    /// Parses [Sinex] content by consuming [BufReader] (efficient buffered reader).
+    pub fn parse<R: Read>(reader: &mut BufReader<R>) -> Result<Self, ParsingError> {
+        let mut block = String::new();
+
+        let mut domes = HashMap::<(String, String), DOMES>::new();
+        let mut pending = HashMap::<(String, String, String), PendingSolution>::new();
+
+        for line in reader.lines() {
+            let line = line?;
+
+            if let Some(name) = line.strip_prefix('+') {
+                block = name.trim().to_string();
+                continue;
+            }
+
+            if line.starts_with('-') {
+                block.clear();
+                continue;
+            }
+
+            if line.starts_with('*') || line.trim().is_empty() {
+                continue;
+            }
+
+            match block.as_str() {
+                "SITE/ID" => {
+                    if line.len() < 18 {
+                        return Err(ParsingError::SinexFormat);
+                    }
+
+                    let code = line[1..5].trim().to_string();
+                    let point = line[6..8].trim().to_string();
+                    let site_domes = line[9..18].trim().parse::<DOMES>()?;
+
+                    domes.insert((code, point), site_domes);
+                },
+                "SOLUTION/EPOCHS" => {
+                    let items = line.split_whitespace().collect::<Vec<_>>();
+
+                    if items.len() < 6 {
+                        return Err(ParsingError::SinexFormat);
+                    }
+
+                    let key = (
+                        items[0].to_string(),
+                        items[1].to_string(),
+                        items[2].to_string(),
+                    );
+
+                    let solution = pending.entry(key).or_default();
+                    solution.start = parse_sinex_epoch(items[4])?;
+                    solution.end = parse_sinex_epoch(items[5])?;
+                },
+                "SOLUTION/ESTIMATE" => {
+                    let items = line.split_whitespace().collect::<Vec<_>>();
+
+                    if items.len() < 9 {
+                        return Err(ParsingError::SinexFormat);
+                    }
+
+                    let (index, is_velocity) = match items[1] {
+                        "STAX" => (0, false),
+                        "STAY" => (1, false),
+                        "STAZ" => (2, false),
+                        "VELX" => (0, true),
+                        "VELY" => (1, true),
+                        "VELZ" => (2, true),
+                        _ => continue,
+                    };
+
+                    let value = items[8]
+                        .parse::<f64>()
+                        .map_err(|_| ParsingError::SinexFormat)?;
+
+                    let key = (
+                        items[2].to_string(),
+                        items[3].to_string(),
+                        items[4].to_string(),
+                    );
+
+                    let solution = pending.entry(key).or_default();
+
+                    if is_velocity {
+                        solution.velocity_m_yr[index] = value;
+                    } else {
+                        solution.position_m[index] = Some(value);
+                        solution.reference = parse_sinex_epoch(items[5])?;
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        let mut solutions = pending
+            .into_iter()
+            .filter_map(|((code, point, solution), pending)| {
+                let domes = domes.get(&(code.clone(), point))?;
+                let [Some(x), Some(y), Some(z)] = pending.position_m else {
+                    return None;
+                };
+
+                let [vx, vy, vz] = pending.velocity_m_yr;
+
+                Some(SinexSolution {
+                    code,
+                    domes: *domes,
+                    solution,
+                    start: pending.start,
+                    end: pending.end,
+                    coordinates: StationCoordinates {
+                        epoch: pending.reference?,
+                        position_m: (x, y, z),
+                        velocity_m_yr: (vx, vy, vz),
+                    },
+                })
+            })
+            .collect::<Vec<_>>();
+
+        solutions.sort_by(|a, b| (&a.code, &a.solution).cmp(&(&b.code, &b.solution)));
+
+        Ok(Self { solutions })
+    }
+
+    /// Parses [Sinex] from local readable file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ParsingError> {
+        let fd = File::open(path)?;
+        let mut reader = BufReader::new(fd);
+        Self::parse(&mut reader)
+    }
+
+    /// Parses [Sinex] from local gzip compressed file.
+    #[cfg(feature = "flate2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "flate2")))]
+    pub fn from_gzip_file<P: AsRef<Path>>(path: P) -> Result<Self, ParsingError> {
+        let fd = File::open(path)?;
+        let reader = GzDecoder::new(fd);
+        let mut reader = BufReader::new(reader);
+        Self::parse(&mut reader)
+    }
+
+    /// Returns the [StationCoordinates] of this [DOMES] site, propagated to desired [Epoch].
+    /// The solution that is valid at this [Epoch] is preferred, otherwise
+    /// the closest solution is used.
+    pub fn station_coordinates(&self, domes: &DOMES, epoch: Epoch) -> Option<StationCoordinates> {
+        let mut solutions = self
+            .solutions
+            .iter()
+            .filter(|solution| solution.domes == *domes)
+            .peekable();
+
+        solutions.peek()?;
+
+        let solution = solutions.min_by_key(|solution| {
+            if solution.is_valid(epoch) {
+                Duration::ZERO
+            } else {
+                [solution.start, solution.end]
+                    .into_iter()
+                    .flatten()
+                    .map(|bound| (bound - epoch).abs())
+                    .min()
+                    .unwrap_or(Duration::MAX)
+            }
+        })?;
+
+        Some(solution.coordinates.propagate(epoch))
+    }
+
+    /// Returns [GroundStation] with coordinates at desired [Epoch], when it is described
+    /// by this [Sinex]. Returns a copy of the [GroundStation] otherwise.
+    fn enrich(&self, station: &GroundStation, epoch: Epoch) -> GroundStation {
+        match self.station_coordinates(&station.domes, epoch) {
+            Some(coordinates) => station.with_coordinates(coordinates),
+            None => station.clone(),
+        }
+    }
+}
+
+impl Header {
+    /// Copies and returns [Header] where [Header::ground_stations] are described
+    /// by this [Sinex], with coordinates propagated to desired [Epoch].
+    pub fn with_station_coordinates(&self, sinex: &Sinex, epoch: Epoch) -> Self {
+        let mut s = self.clone();
+        s.station_coordinates_mut(sinex, epoch);
+        s
+    }
+
+    /// Describes [Header::ground_stations] with [Sinex] coordinates (in place),
+    /// propagated to desired [Epoch].
+    pub fn station_coordinates_mut(&mut self, sinex: &Sinex, epoch: Epoch) {
+        for station in self.ground_stations.iter_mut() {
+            *station = sinex.enrich(station, epoch);
+        }
+    }
+}
+
+impl Record {
+    /// Describes all [GroundStation]s of this [Record] with [Sinex] coordinates (in place),
+    /// propagated to desired [Epoch].
+    pub fn station_coordinates_mut(&mut self, sinex: &Sinex, epoch: Epoch) {
+        let mut enriched = HashMap::<StationHandle, StationHandle>::new();
+
+        for measurements in self.measurements.values_mut() {
+            measurements.observations = std::mem::take(&mut measurements.observations)
+                .into_iter()
+                .map(|(mut key, observation)| {
+                    key.station = enriched
+                        .entry(key.station.clone())
+                        .or_insert_with(|| sinex.enrich(&key.station, epoch).into())
+                        .clone();
+
+                    (key, observation)
+                })
+                .collect();
+        }
+    }
+}
+
+impl DORIS {
+    /// Copies and returns [DORIS] where all [GroundStation]s are described
+    /// by this [Sinex], with coordinates propagated to desired [Epoch].
+    /// See [Self::station_coordinates_mut].
+    pub fn with_station_coordinates(&self, sinex: &Sinex, epoch: Epoch) -> Self {
+        let mut s = self.clone();
+        s.station_coordinates_mut(sinex, epoch);
+        s
+    }
+
+    /// Describes all [GroundStation]s, in both [Header] and [Record],
+    /// with [Sinex] coordinates (in place), propagated to desired [Epoch].
+    /// The first [Epoch] of the file is usually a good choice, since
+    /// station velocities are a few cm per year.
+    pub fn station_coordinates_mut(&mut self, sinex: &Sinex, epoch: Epoch) {
+        self.header.station_coordinates_mut(sinex, epoch);
+        self.record.station_coordinates_mut(sinex, epoch);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::{Frequency, Observable};
+    use crate::tests::toolkit::{synthetic_record, synthetic_t0};
+    use std::str::FromStr;
+
+    const SINEX: &str = "%=SNX 2.02 IGN 20:031:00000 IGN 93:003:00000 20:001:00000 D 00001 2 S
++SITE/ID
+*CODE PT __DOMES__ T _STATION DESCRIPTION__ APPROX_LON_ APPROX_LAT_ _APP_H_
+ TLSB  A 10003S005 D Toulouse, France         1 28 52.6  43 33 38.7   207.8
+-SITE/ID
++SOLUTION/EPOCHS
+*CODE PT SOLN T _DATA_START_ __DATA_END__ _MEAN_EPOCH_
+ TLSB  A    1 D 10:001:00000 17:365:00000 14:001:00000
+ TLSB  A    2 D 18:001:00000 00:000:00000 19:001:00000
+-SOLUTION/EPOCHS
++SOLUTION/ESTIMATE
+*INDEX TYPE__ CODE PT SOLN _REF_EPOCH__ UNIT S __ESTIMATED VALUE____ _STD_DEV___
+     1 STAX   TLSB  A    1 15:001:00000 m    2  4.62785190000000e+06 1.0e-03
+     2 STAY   TLSB  A    1 15:001:00000 m    2  1.19640100000000e+05 1.0e-03
+     3 STAZ   TLSB  A    1 15:001:00000 m    2  4.37299360000000e+06 1.0e-03
+     4 STAX   TLSB  A    2 15:001:00000 m    2  4.62785000000000e+06 1.0e-03
+     5 STAY   TLSB  A    2 15:001:00000 m    2  1.19640000000000e+05 1.0e-03
+     6 STAZ   TLSB  A    2 15:001:00000 m    2  4.37299000000000e+06 1.0e-03
+     7 VELX   TLSB  A    2 15:001:00000 m/y  2 -1.00000000000000e-02 1.0e-04
+     8 VELY   TLSB  A    2 15:001:00000 m/y  2  2.00000000000000e-02 1.0e-04
+     9 VELZ   TLSB  A    2 15:001:00000 m/y  2  1.00000000000000e-02 1.0e-04
+-SOLUTION/ESTIMATE
+%ENDSNX
+";
+
+    #[test]
+    fn sinex_parsing() {
+        let mut reader = BufReader::new(SINEX.as_bytes());
+        let sinex = Sinex::parse(&mut reader).unwrap();
+
+        assert_eq!(sinex.solutions.len(), 2);
+        assert_eq!(sinex.solutions[0].code, "TLSB");
+        assert_eq!(sinex.solutions[1].end, None);
+
+        let domes = DOMES::from_str("10003S005").unwrap();
+        let unknown = DOMES::from_str("10002S019").unwrap();
+
+        let reference = parse_sinex_epoch("15:001:00000").unwrap().unwrap();
+        let t0 = synthetic_t0();
+
+        // 2 and 4 digit years
+        let epoch = parse_sinex_epoch("2015:001:00000").unwrap().unwrap();
+        assert_eq!(epoch, reference);
+
+        let epoch = parse_sinex_epoch("93:001:00000").unwrap().unwrap();
+        assert_eq!(epoch.year(), 1993);
+
+        assert_eq!(parse_sinex_epoch("00:000:00000").unwrap(), None);
+
+        assert!(sinex.station_coordinates(&unknown, t0).is_none());
+
+        // second solution applies, propagated over 3.5 years
+        let coordinates = sinex.station_coordinates(&domes, t0).unwrap();
+        let dt_yr = (t0 - reference).to_seconds() / 86400.0 / 365.25;
+
+        assert_eq!(coordinates.epoch, t0);
+        assert!((coordinates.position_m.0 - (4627850.0 - 0.01 * dt_yr)).abs() < 1.0E-6);
+        assert!((coordinates.position_m.1 - (119640.0 + 0.02 * dt_yr)).abs() < 1.0E-6);
+
+        // first solution applies, without velocity
+        let coordinates = sinex.station_coordinates(&domes, reference).unwrap();
+        assert_eq!(coordinates.position_m, (4627851.9, 119640.1, 4372993.6));
+
+        let (latitude, longitude, height) = coordinates.geodetic();
+        assert!((latitude - 43.5607).abs() < 1.0E-3);
+        assert!((longitude - 1.4808).abs() < 1.0E-3);
+        assert!(height > 150.0 && height < 300.0);
+
+        let station = GroundStation::default()
+            .with_site_label("TLSB")
+            .with_unique_id(13);
+
+        let mut record = synthetic_record(
+            &station,
+            &[(
+                0.0,
+                vec![(Observable::PseudoRange(Frequency::DORIS1), 1.0E6)],
+            )],
+        );
+
+        record.station_coordinates_mut(&sinex, t0);
+
+        for measurements in record.measurements.values() {
+            for key in measurements.observations.keys() {
+                assert_eq!(*key.station, station);
+                assert!(key.station.coordinates.is_some());
+            }
+        }
+    }
+}
@@ -0,0 +1,825 @@
+//! SQLite archive support (requires the `sqlite` feature).
+//!
+//! [SqliteArchive] stores [DORIS] files into normalized tables, so years of data
+//! may be queried with SQL. Files are identified by their [ProductionAttributes].
+//!
+//! | Table           | Content                                                            |
+//! |-----------------|--------------------------------------------------------------------|
+//! | `files`         | One row per file: `satellite`, `year`, `doy` ([ProductionAttributes]), `cospar`, and the RINEX `header` section |
+//! | `stations`      | [GroundStation]s of each file: `file_id`, `code`, `label`, `site`, `domes`, `beacon_revision`, `k_frequency_shift` |
+//! | `epochs`        | One row per epoch: `file_id`, `epoch` (TAI nanoseconds since J1900), `flag` (RINEX value) |
+//! | `clock_offsets` | Satellite [ClockOffset] of each epoch: `epoch_id`, `offset` (s), `extrapolated` |
+//! | `observations`  | One row per observation: `epoch_id`, `station_id`, `observable` (RINEX code), `value`, `snr` and `phase_flag` (RINEX values) |
+//!
+//! The `observation_view` view joins all tables, with one row per observation,
+//! and is the easiest way to select data, see [SqliteArchive::load_query].
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    io::BufReader,
+    path::Path,
+    str::FromStr,
+};
+
+use rusqlite::{params, Connection, OptionalExtension, Params, Transaction};
+
+use crate::{
+    prelude::{
+        ClockOffset, FormattingError, GroundStation, Header, Key, Observable, Observation,
+        ParsingError, ProductionAttributes, Record, StationHandle, TimeScale, DORIS, SNR,
+    },
+    record::columns::{
+        duration_from_seconds, epoch_flag, epoch_flag_value, epoch_from_tai_nanoseconds,
+        phase_flag, phase_flag_value, snr_value, tai_nanoseconds,
+    },
+};
+
+/// Archive schema
+const SCHEMA: &str = "
+PRAGMA foreign_keys = ON;
+
+CREATE TABLE IF NOT EXISTS files (
+    id INTEGER PRIMARY KEY,
+    satellite TEXT NOT NULL,
+    year INTEGER NOT NULL,
+    doy INTEGER NOT NULL,
+    cospar TEXT,
+    header TEXT NOT NULL,
+    UNIQUE (satellite, year, doy)
+);
+
+CREATE TABLE IF NOT EXISTS stations (
+    id INTEGER PRIMARY KEY,
+    file_id INTEGER NOT NULL REFERENCES files (id) ON DELETE CASCADE,
+    code INTEGER NOT NULL,
+    label TEXT NOT NULL,
+    site TEXT NOT NULL,
+    domes TEXT NOT NULL,
+    beacon_revision INTEGER NOT NULL,
+    k_frequency_shift INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS epochs (
+    id INTEGER PRIMARY KEY,
+    file_id INTEGER NOT NULL REFERENCES files (id) ON DELETE CASCADE,
+    epoch INTEGER NOT NULL,
+    flag INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS clock_offsets (
+    epoch_id INTEGER PRIMARY KEY REFERENCES epochs (id) ON DELETE CASCADE,
+    offset REAL NOT NULL,
+    extrapolated INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS observations (
+    epoch_id INTEGER NOT NULL REFERENCES epochs (id) ON DELETE CASCADE,
+    station_id INTEGER NOT NULL REFERENCES stations (id) ON DELETE CASCADE,
+    observable TEXT NOT NULL,
+    value REAL NOT NULL,
+    snr INTEGER,
+    phase_flag INTEGER
+);
+
+CREATE INDEX IF NOT EXISTS epochs_file ON epochs (file_id, epoch);
+CREATE INDEX IF NOT EXISTS stations_file ON stations (file_id);
+CREATE INDEX IF NOT EXISTS observations_epoch ON observations (epoch_id);
+CREATE INDEX IF NOT EXISTS observations_station ON observations (station_id);
+
+CREATE VIEW IF NOT EXISTS observation_view AS
+SELECT
+    files.id AS file_id,
+    files.satellite AS satellite,
+    files.year AS year,
+    files.doy AS doy,
+    epochs.id AS epoch_id,
+    epochs.epoch AS epoch,
+    epochs.flag AS flag,
+    clock_offsets.offset AS clock_offset,
+    clock_offsets.extrapolated AS clock_extrapolated,
+    stations.id AS station_id,
+    stations.code AS station_code,
+    stations.label AS station_label,
+    stations.domes AS station_domes,
+    observations.observable AS observable,
+    observations.value AS value,
+    observations.snr AS snr,
+    observations.phase_flag AS phase_flag
+FROM epochs
+JOIN files ON files.id = epochs.file_id
+LEFT JOIN clock_offsets ON clock_offsets.epoch_id = epochs.id
+LEFT JOIN observations ON observations.epoch_id = epochs.id
+LEFT JOIN stations ON stations.id = observations.station_id;
+";
+
+/// Columns of the `observation_view` selection, in order of appearance
+const VIEW_COLUMNS: &str = "file_id, epoch, flag, clock_offset, clock_extrapolated, \
+    station_id, observable, value, snr, phase_flag, satellite";
+
+/// [SqliteArchive] stores [DORIS] files into a SQLite database,
+/// see the [module](crate::sqlite) documentation for the table description.
+///
+/// ```
+/// use doris_rs::prelude::*;
+///
+/// let doris = DORIS::from_gzip_file("data/DOR/V3/cs2rx18164.gz")
+///     .unwrap();
+///
+/// let mut archive = SqliteArchive::open(std::env::temp_dir().join("doris.sqlite"))
+///     .unwrap();
+///
+/// // files are identified by their production attributes:
+/// // appending the same file again replaces its content.
+/// archive.append(&doris)
+///     .unwrap();
+///
+/// let production = doris.production.as_ref()
+///     .unwrap();
+///
+/// let loaded = archive.load(production)
+///     .unwrap()
+///     .unwrap();
+///
+/// assert_eq!(loaded.record, doris.record);
+///
+/// // Toulouse temperatures
+/// let toulouse = archive.load_query(
+///     "station_label = ?1 AND observable = ?2",
+///     ["TLSB", "T"],
+/// )
+/// .unwrap();
+/// ```
+#[derive(Debug)]
+pub struct SqliteArchive {
+    /// SQLite [Connection]
+    connection: Connection,
+}
+
+impl SqliteArchive {
+    /// Opens (or creates) the [SqliteArchive] stored in this local file
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ParsingError> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    /// Creates a new [SqliteArchive] in memory
+    pub fn open_in_memory() -> Result<Self, ParsingError> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    /// Builds [SqliteArchive] from SQLite [Connection], creating the tables if needed
+    pub fn from_connection(connection: Connection) -> Result<Self, ParsingError> {
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self { connection })
+    }
+
+    /// Returns the SQLite [Connection], to run custom queries
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    /// Returns the file identifier of these [ProductionAttributes], if archived
+    fn file_id(
+        connection: &Connection,
+        production: &ProductionAttributes,
+    ) -> Result<Option<i64>, rusqlite::Error> {
+        connection
+            .query_row(
+                "SELECT id FROM files WHERE satellite = ?1 AND year = ?2 AND doy = ?3",
+                params![production.satellite, production.year, production.doy],
+                |row| row.get(0),
+            )
+            .optional()
+    }
+
+    /// Returns true if the file described by these [ProductionAttributes] is archived
+    pub fn contains(&self, production: &ProductionAttributes) -> Result<bool, ParsingError> {
+        Ok(Self::file_id(&self.connection, production)?.is_some())
+    }
+
+    /// Returns the [ProductionAttributes] of all archived files,
+    /// in chronological order.
+    pub fn files(&self) -> Result<Vec<ProductionAttributes>, ParsingError> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT satellite, year, doy FROM files ORDER BY year, doy, satellite")?;
+
+        let files = statement
+            .query_map([], |row| {
+                Ok(ProductionAttributes {
+                    satellite: row.get(0)?,
+                    year: row.get(1)?,
+                    doy: row.get(2)?,
+                    ..Default::default()
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(files)
+    }
+
+    /// Appends this [DORIS] file to the [SqliteArchive], in a single transaction.
+    /// The file is identified by its [ProductionAttributes], which must be defined:
+    /// a file that was previously archived is replaced. Returns the file identifier.
+    pub fn append(&mut self, doris: &DORIS) -> Result<i64, FormattingError> {
+        let production = doris
+            .production
+            .as_ref()
+            .ok_or(FormattingError::MissingProductionAttributes)?;
+
+        let transaction = self.connection.transaction()?;
+
+        if let Some(file_id) = Self::file_id(&transaction, production)? {
+            transaction.execute("DELETE FROM files WHERE id = ?1", [file_id])?;
+        }
+
+        transaction.execute(
+            "INSERT INTO files (satellite, year, doy, cospar, header) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                production.satellite,
+                production.year,
+                production.doy,
+                doris
+                    .header
+                    .cospar
+                    .as_ref()
+                    .map(|cospar| cospar.to_string()),
+                doris.header.format_string()?,
+            ],
+        )?;
+
+        let file_id = transaction.last_insert_rowid();
+
+        Self::append_record(&transaction, file_id, doris)?;
+
+        transaction.commit()?;
+        Ok(file_id)
+    }
+
+    /// Appends the stations, epochs, clock offsets and observations of this [DORIS] file
+    fn append_record(
+        transaction: &Transaction,
+        file_id: i64,
+        doris: &DORIS,
+    ) -> Result<(), FormattingError> {
+        let mut stations = HashMap::<StationHandle, i64>::new();
+
+        let mut insert_station = transaction.prepare(
+            "INSERT INTO stations (file_id, code, label, site, domes, beacon_revision, k_frequency_shift)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        )?;
+
+        let mut insert_epoch =
+            transaction.prepare("INSERT INTO epochs (file_id, epoch, flag) VALUES (?1, ?2, ?3)")?;
+
+        let mut insert_clock = transaction.prepare(
+            "INSERT INTO clock_offsets (epoch_id, offset, extrapolated) VALUES (?1, ?2, ?3)",
+        )?;
+
+        let mut insert_observation = transaction.prepare(
+            "INSERT INTO observations (epoch_id, station_id, observable, value, snr, phase_flag)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+
+        let header_stations = doris.header.ground_stations.iter().map(StationHandle::from);
+
+        let record_stations = doris
+            .record
+            .measurements
+            .values()
+            .flat_map(|measurements| measurements.observations.keys())
+            .map(|obs_key| obs_key.station.clone());
+
+        for station in header_stations.chain(record_stations) {
+            if stations.contains_key(&station) {
+                continue;
+            }
+
+            let station_id = insert_station.insert(params![
+                file_id,
+                station.code,
+                station.label,
+                station.site,
+                station.domes.to_string(),
+                station.beacon_revision,
+                station.k_frequency_shift,
+            ])?;
+
+            stations.insert(station, station_id);
+        }
+
+        for (key, measurements) in doris.record.measurements.iter() {
+            let epoch_id = insert_epoch.insert(params![
+                file_id,
+                tai_nanoseconds(key.epoch),
+                epoch_flag_value(key.flag),
+            ])?;
+
+            if let Some(clock) = measurements.satellite_clock_offset {
+                insert_clock.execute(params![
+                    epoch_id,
+                    clock.offset.to_seconds(),
+                    clock.extrapolated
+                ])?;
+            }
+
+            for (obs_key, observation) in measurements.observations.iter() {
+                insert_observation.execute(params![
+                    epoch_id,
+                    stations[&obs_key.station],
+                    format!("{:x}", obs_key.observable),
+                    observation.value,
+                    observation.snr.map(snr_value),
+                    observation.phase_flag.map(phase_flag_value),
+                ])?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Removes the file described by these [ProductionAttributes] from the [SqliteArchive].
+    /// Returns true if the file was archived.
+    pub fn remove(&mut self, production: &ProductionAttributes) -> Result<bool, FormattingError> {
+        let removed = self.connection.execute(
+            "DELETE FROM files WHERE satellite = ?1 AND year = ?2 AND doy = ?3",
+            params![production.satellite, production.year, production.doy],
+        )?;
+
+        Ok(removed > 0)
+    }
+
+    /// Loads the [DORIS] file described by these [ProductionAttributes], if archived.
+    pub fn load(&self, production: &ProductionAttributes) -> Result<Option<DORIS>, ParsingError> {
+        let Some(file_id) = Self::file_id(&self.connection, production)? else {
+            return Ok(None);
+        };
+
+        let mut doris = self.load_query("file_id = ?1", [file_id])?;
+        doris.production = Some(production.clone());
+
+        Ok(Some(doris))
+    }
+
+    /// Loads [DORIS] from the rows of the `observation_view` view that match this SQL
+    /// condition (WHERE clause), with these parameters.
+    /// The [Record] gathers all matching observations, with epochs expressed in [TimeScale::TAI].
+    ///
+    /// All matching rows must describe the same satellite: this returns
+    /// [ParsingError::SqliteSatellites] otherwise.
+    ///
+    /// The [Header] is that of the first matching file (in chronological order). When the
+    /// matching rows span several files, the [GroundStation]s and [Observable]s of the other
+    /// files are appended, and the time of first and last observation describe the [Record]
+    /// (see [Header::describe_record]).
+    ///
+    /// Stations are described by the [GroundStation]s of their file [Header] when possible.
+    /// Station codes are file dependent: a station already described by a previous file
+    /// (same label and DOMES) is reused, and a station whose code is already taken by
+    /// another station is given a new code.
+    ///
+    /// ⚠️ `condition` is inserted as is in the SQL statement: it must be written by the
+    /// application and never built from user input. Values must be passed as `params`
+    /// (`?1`, `?2`..), which are bound by SQLite and are safe.
+    ///
+    /// ```
+    /// use doris_rs::prelude::*;
+    ///
+    /// let archive = SqliteArchive::open_in_memory()
+    ///     .unwrap();
+    ///
+    /// let toulouse = archive
+    ///     .load_query("year = ?1 AND station_label = ?2", (2018, "TLSB"))
+    ///     .unwrap();
+    ///
+    /// assert!(toulouse.record.measurements.is_empty());
+    /// ```
+    pub fn load_query<P: Params>(&self, condition: &str, params: P) -> Result<DORIS, ParsingError> {
+        let mut statement = self.connection.prepare(&format!(
+            "SELECT {} FROM observation_view WHERE {} ORDER BY year, doy, satellite, epoch",
+            VIEW_COLUMNS, condition
+        ))?;
+
+        let mut rows = statement.query(params)?;
+
+        let mut headers = HashMap::<i64, Header>::new();
+        let mut first_file = Option::<i64>::None;
+        let mut satellite = Option::<String>::None;
+        let mut record = Record::default();
+
+        // (file, station) identifiers
+        let mut stations = HashMap::<(i64, i64), StationHandle>::new();
+
+        // stations of all files, with unique codes
+        let mut known = Vec::<StationHandle>::new();
+
+        while let Some(row) = rows.next()? {
+            let file_id = row.get::<_, i64>(0)?;
+
+            if let Entry::Vacant(entry) = headers.entry(file_id) {
+                let file_satellite = row.get::<_, String>(10)?;
+
+                if *satellite.get_or_insert_with(|| file_satellite.clone()) != file_satellite {
+                    return Err(ParsingError::SqliteSatellites);
+                }
+
+                let header = entry.insert(self.load_header(file_id)?);
+
+                stations.extend(
+                    self.load_stations(file_id, header, &mut known)?
+                        .into_iter()
+                        .map(|(id, station)| ((file_id, id), station)),
+                );
+
+                first_file.get_or_insert(file_id);
+            }
+
+            let key = Key {
+                epoch: epoch_from_tai_nanoseconds(row.get(1)?, TimeScale::TAI),
+                flag: epoch_flag(row.get(2)?)?,
+            };
+
+            let measurements = record.measurements.entry(key).or_default();
+
+            if let Some(offset) = row.get::<_, Option<f64>>(3)? {
+                measurements.satellite_clock_offset = Some(ClockOffset {
+                    offset: duration_from_seconds(offset),
+                    extrapolated: row.get::<_, Option<bool>>(4)?.unwrap_or_default(),
+                });
+            }
+
+            let Some(station_id) = row.get::<_, Option<i64>>(5)? else {
+                continue;
+            };
+
+            let station = stations
+                .get(&(file_id, station_id))
+                .ok_or(ParsingError::SqliteSchema)?
+                .clone();
+
+            let observable = Observable::from_str(&row.get::<_, String>(6)?)?;
+
+            let mut observation = Observation::default().with_value(row.get(7)?);
+
+            if let Some(snr) = row.get::<_, Option<u8>>(8)? {
+                observation = observation.with_snr(SNR::from(snr));
+            }
+
+            if let Some(flag) = row.get::<_, Option<u8>>(9)? {
+                observation = observation.with_phase_flag(phase_flag(flag)?);
+            }
+
+            measurements.add_observation(station, observable, observation);
+        }
+
+        let multiple_files = headers.len() > 1;
+
+        let mut header = first_file
+            .and_then(|file_id| headers.remove(&file_id))
+            .unwrap_or_default();
+
+        if multiple_files {
+            header.describe_record(&record);
+        }
+
+        Ok(DORIS::new(header, record))
+    }
+
+    /// Loads the [Header] of this file
+    fn load_header(&self, file_id: i64) -> Result<Header, ParsingError> {
+        let header = self.connection.query_row(
+            "SELECT header FROM files WHERE id = ?1",
+            [file_id],
+            |row| row.get::<_, String>(0),
+        )?;
+
+        Header::parse(&mut BufReader::new(header.as_bytes()))
+    }
+
+    /// Loads the [GroundStation]s of this file, indexed by identifier.
+    /// Stations described in the file [Header] are preferred.
+    /// Stations are identified against (and appended to) the `known` stations of
+    /// previous files, so that each code describes a single station.
+    fn load_stations(
+        &self,
+        file_id: i64,
+        header: &Header,
+        known: &mut Vec<StationHandle>,
+    ) -> Result<Vec<(i64, StationHandle)>, ParsingError> {
+        let mut statement = self.connection.prepare(
+            "SELECT id, code, label, site, domes, beacon_revision, k_frequency_shift
+            FROM stations WHERE file_id = ?1",
+        )?;
+
+        let mut rows = statement.query([file_id])?;
+        let mut stations = Vec::new();
+
+        while let Some(row) = rows.next()? {
+            let station = GroundStation::default()
+                .with_unique_id(row.get(1)?)
+                .with_site_label(&row.get::<_, String>(2)?)
+                .with_site_name(&row.get::<_, String>(3)?)
+                .with_domes_str(&row.get::<_, String>(4)?)?
+                .with_beacon_revision(row.get(5)?)
+                .with_frequency_shift(row.get(6)?);
+
+            let station = header
+                .ground_stations
+                .iter()
+                .find(|described| {
+                    described.code == station.code && described.label == station.label
+                })
+                .unwrap_or(&station);
+
+            let same_station = known
+                .iter()
+                .find(|other| other.label == station.label && other.domes == station.domes);
+
+            let handle = match same_station {
+                Some(handle) => handle.clone(),
+                None => {
+                    let handle = if known.iter().any(|other| other.code == station.code) {
+                        let code = known
+                            .iter()
+                            .map(|other| other.code)
+                            .max()
+                            .unwrap_or_default();
+                        StationHandle::from(station.with_unique_id(code + 1))
+                    } else {
+                        StationHandle::from(station)
+                    };
+
+                    known.push(handle.clone());
+                    handle
+                },
+            };
+
+            stations.push((row.get(0)?, handle));
+        }
+
+        Ok(stations)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prelude::{
+        ClockOffset, Duration, EpochFlag, Frequency, GroundStation, Key, Observable, ParsingError,
+        PhaseFlag, ProductionAttributes, SqliteArchive, DORIS, SNR,
+    };
+    use crate::tests::toolkit::{add_synthetic_samples, synthetic_record, synthetic_t0};
+
+    #[test]
+    fn sqlite_archive() {
+        let l1 = Observable::UnambiguousPhaseRange(Frequency::DORIS1);
+        let l2 = Observable::UnambiguousPhaseRange(Frequency::DORIS2);
+
+        let toulouse = GroundStation::default()
+            .with_site_label("TLSB")
+            .with_site_name("TOULOUSE")
+            .with_domes_str("10003S005")
+            .unwrap()
+            .with_unique_id(1);
+
+        // not described in the header
+        let grasse = GroundStation::default()
+            .with_site_label("GR4B")
+            .with_domes_str("10002S019")
+            .unwrap()
+            .with_unique_id(2);
+
+        let mut record = synthetic_record(
+            &toulouse,
+            &[
+                (0.0, vec![(l1, 1.0), (l2, 2.0)]),
+                (10.0, vec![(l1, 3.0), (Observable::Temperature, 20.5)]),
+            ],
+        );
+
+        add_synthetic_samples(&mut record, &grasse, &[(10.0, vec![(l2, 4.125)])]);
+
+        let t0 = synthetic_t0();
+
+        let measurements = record.measurements.values_mut().next().unwrap();
+
+        measurements.satellite_clock_offset = Some(ClockOffset::from_extrapolated_offset(
+            Duration::from_seconds(-4.25E-3),
+        ));
+
+        for observation in measurements.observations.values_mut() {
+            *observation = observation
+                .with_snr(SNR::DbHz42_47)
+                .with_phase_flag(PhaseFlag::Discontinuity);
+        }
+
+        record.measurements.insert(
+            Key {
+                epoch: t0 + Duration::from_seconds(5.0),
+                flag: EpochFlag::ExternalEvent,
+            },
+            Default::default(),
+        );
+
+        let mut doris = DORIS::default();
+        doris.header.satellite = "CRYOSAT-2".to_string();
+        doris.header.observables = vec![l1, l2, Observable::Temperature];
+        doris.header.ground_stations = vec![toulouse.clone()];
+        doris.record = record;
+
+        let mut archive = SqliteArchive::open_in_memory().unwrap();
+
+        // production attributes are required
+        assert!(archive.append(&doris).is_err());
+
+        let production = ProductionAttributes {
+            satellite: "CS2RX".to_string(),
+            year: 2018,
+            doy: 164,
+            ..Default::default()
+        };
+
+        doris.production = Some(production.clone());
+
+        archive.append(&doris).unwrap();
+
+        // incremental append: same file is replaced
+        archive.append(&doris).unwrap();
+
+        let next_day = ProductionAttributes {
+            doy: 165,
+            ..production.clone()
+        };
+
+        let mut next = doris.clone();
+        next.production = Some(next_day.clone());
+        archive.append(&next).unwrap();
+
+        assert!(archive.contains(&production).unwrap());
+        assert_eq!(
+            archive.files().unwrap(),
+            vec![production.clone(), next_day.clone()]
+        );
+
+        let count = archive
+            .connection()
+            .query_row("SELECT COUNT(*) FROM observations", [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .unwrap();
+
+        assert_eq!(count, 2 * 5);
+
+        let loaded = archive.load(&production).unwrap().unwrap();
+
+        assert_eq!(loaded.record, doris.record);
+        assert_eq!(loaded.header.satellite, "CRYOSAT-2");
+        assert_eq!(loaded.header.ground_stations, vec![toulouse]);
+        assert_eq!(loaded.production, Some(production.clone()));
+
+        // query
+        let loaded = archive
+            .load_query(
+                "doy = ?1 AND station_label = ?2 AND observable = ?3",
+                (164, "TLSB", "L1"),
+            )
+            .unwrap();
+
+        let values = loaded
+            .record
+            .measurements
+            .values()
+            .flat_map(|measurements| measurements.observations.values())
+            .map(|observation| observation.value)
+            .collect::<Vec<_>>();
+
+        assert_eq!(values, vec![1.0, 3.0]);
+
+        assert!(archive.remove(&next_day).unwrap());
+        assert!(!archive.remove(&next_day).unwrap());
+        assert!(archive.load(&next_day).unwrap().is_none());
+    }
+
+    #[test]
+    fn sqlite_multiple_files() {
+        let l1 = Observable::UnambiguousPhaseRange(Frequency::DORIS1);
+
+        let toulouse = GroundStation::default()
+            .with_site_label("TLSB")
+            .with_domes_str("10003S005")
+            .unwrap()
+            .with_unique_id(1);
+
+        let kourou = GroundStation::default()
+            .with_site_label("KRVB")
+            .with_domes_str("97301S006")
+            .unwrap()
+            .with_unique_id(2);
+
+        let production = ProductionAttributes {
+            satellite: "CS2RX".to_string(),
+            year: 2018,
+            doy: 164,
+            ..Default::default()
+        };
+
+        let mut first = DORIS::default();
+        first.header.observables = vec![l1];
+        first.header.ground_stations = vec![toulouse.clone()];
+        first.record = synthetic_record(&toulouse, &[(0.0, vec![(l1, 1.0)])]);
+        first.production = Some(production.clone());
+
+        // codes are file dependent: TLSB is #2 and KRVB is #1 on the next day,
+        // KRVB is then given the first free code
+        let next_toulouse = toulouse.with_unique_id(2);
+        let next_kourou = kourou.with_unique_id(1);
+
+        let mut next = DORIS::default();
+        next.header.observables = vec![l1, Observable::Temperature];
+        next.header.ground_stations = vec![next_kourou.clone(), next_toulouse.clone()];
+
+        next.record = synthetic_record(
+            &next_toulouse,
+            &[(86400.0, vec![(l1, 2.0), (Observable::Temperature, 20.5)])],
+        );
+
+        add_synthetic_samples(
+            &mut next.record,
+            &next_kourou,
+            &[(86400.0, vec![(l1, 3.0)])],
+        );
+
+        next.production = Some(ProductionAttributes {
+            doy: 165,
+            ..production
+        });
+
+        let mut archive = SqliteArchive::open_in_memory().unwrap();
+        archive.append(&first).unwrap();
+        archive.append(&next).unwrap();
+
+        let loaded = archive.load_query("satellite = ?1", ["CS2RX"]).unwrap();
+
+        let kourou = kourou.with_unique_id(2);
+
+        assert_eq!(
+            loaded.header.ground_stations,
+            vec![toulouse.clone(), kourou.clone()]
+        );
+
+        assert_eq!(loaded.header.observables, vec![l1, Observable::Temperature]);
+
+        assert_eq!(
+            loaded.header.time_of_last_observation,
+            Some(synthetic_t0() + Duration::from_seconds(86400.0))
+        );
+
+        let observations = loaded
+            .record
+            .measurements
+            .values()
+            .flat_map(|measurements| measurements.observations.iter())
+            .filter(|(obs_key, _)| obs_key.observable == l1)
+            .map(|(obs_key, observation)| (obs_key.station.code, observation.value))
+            .collect::<Vec<_>>();
+
+        assert_eq!(observations, vec![(1, 1.0), (1, 2.0), (2, 3.0)]);
+    }
+
+    #[test]
+    fn sqlite_multiple_satellites() {
+        let l1 = Observable::UnambiguousPhaseRange(Frequency::DORIS1);
+
+        let toulouse = GroundStation::default()
+            .with_site_label("TLSB")
+            .with_domes_str("10003S005")
+            .unwrap()
+            .with_unique_id(1);
+
+        let mut archive = SqliteArchive::open_in_memory().unwrap();
+
+        for satellite in ["CS2RX", "JA3RX"] {
+            let mut doris = DORIS::default();
+            doris.header.observables = vec![l1];
+            doris.header.ground_stations = vec![toulouse.clone()];
+            doris.record = synthetic_record(&toulouse, &[(0.0, vec![(l1, 1.0)])]);
+
+            doris.production = Some(ProductionAttributes {
+                satellite: satellite.to_string(),
+                year: 2018,
+                doy: 164,
+                ..Default::default()
+            });
+
+            archive.append(&doris).unwrap();
+        }
+
+        assert!(matches!(
+            archive.load_query("station_label = ?1", ["TLSB"]),
+            Err(ParsingError::SqliteSatellites)
+        ));
+
+        let loaded = archive
+            .load_query("station_label = ?1 AND satellite = ?2", ("TLSB", "JA3RX"))
+            .unwrap();
+
+        assert_eq!(loaded.record.measurements.len(), 1);
+    }
+}
@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use std::{str::FromStr, sync::Arc};
 
 #[cfg(doc)]
 use crate::prelude::DORIS;
@@ -8,11 +8,78 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     constants::USO_FREQ_HZ,
-    prelude::{Matcher, ParsingError, DOMES},
+    prelude::{Epoch, Matcher, ParsingError, DOMES},
 };
 
+/// WGS84 semi major axis, in meters
+const WGS84_SEMI_MAJOR_AXIS_M: f64 = 6378137.0;
+
+/// WGS84 flattening
+const WGS84_FLATTENING: f64 = 1.0 / 298.257223563;
+
+/// Days per julian year, used in velocity propagation
+const DAYS_PER_YEAR: f64 = 365.25;
+
+/// [StationCoordinates] describe the position of a [GroundStation]
+/// in the terrestrial (ECEF) frame, at a given [Epoch].
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StationCoordinates {
+    /// [Epoch] of the position
+    pub epoch: Epoch,
+
+    /// ECEF (x, y, z) position, in meters
+    pub position_m: (f64, f64, f64),
+
+    /// ECEF (x, y, z) velocity, in meters per year
+    pub velocity_m_yr: (f64, f64, f64),
+}
+
+impl StationCoordinates {
+    /// Propagates these [StationCoordinates] to desired [Epoch],
+    /// using the linear velocity model.
+    pub fn propagate(&self, epoch: Epoch) -> Self {
+        let dt_yr = (epoch - self.epoch).to_seconds() / 86400.0 / DAYS_PER_YEAR;
+
+        let (x, y, z) = self.position_m;
+        let (vx, vy, vz) = self.velocity_m_yr;
+
+        Self {
+            epoch,
+            position_m: (x + vx * dt_yr, y + vy * dt_yr, z + vz * dt_yr),
+            velocity_m_yr: self.velocity_m_yr,
+        }
+    }
+
+    /// Converts the ECEF position to geodetic (latitude, longitude, height) on the WGS84
+    /// ellipsoid, with angles in degrees and ellipsoidal height in meters.
+    pub fn geodetic(&self) -> (f64, f64, f64) {
+        let (x, y, z) = self.position_m;
+
+        let a = WGS84_SEMI_MAJOR_AXIS_M;
+        let e2 = WGS84_FLATTENING * (2.0 - WGS84_FLATTENING);
+
+        let p = (x * x + y * y).sqrt();
+        let longitude = y.atan2(x);
+
+        let mut latitude = z.atan2(p * (1.0 - e2));
+        let mut height = 0.0;
+
+        for _ in 0..5 {
+            let sin_lat = latitude.sin();
+            let n = a / (1.0 - e2 * sin_lat * sin_lat).sqrt();
+            height = p / latitude.cos() - n;
+            latitude = z.atan2(p * (1.0 - e2 * n / (n + height)));
+        }
+
+        (latitude.to_degrees(), longitude.to_degrees(), height)
+    }
+}
+
 /// [GroundStation] definition, observed from DORIS satellites.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Possible [StationCoordinates] do not contribute to the identity
+/// of the station (equality, ordering and hashing).
+#[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct GroundStation {
     /// 4 letter station mnemonic label (antenna point)
@@ -32,6 +99,48 @@ pub struct GroundStation {
 
     /// ID# used in file indexing
     pub(crate) code: u16,
+
+    /// Possible [StationCoordinates], not described in DORIS files.
+    pub coordinates: Option<StationCoordinates>,
+}
+
+impl GroundStation {
+    fn identity(&self) -> (&str, &str, &DOMES, u8, i8, u16) {
+        (
+            &self.label,
+            &self.site,
+            &self.domes,
+            self.beacon_revision,
+            self.k_frequency_shift,
+            self.code,
+        )
+    }
+}
+
+impl PartialEq for GroundStation {
+    fn eq(&self, rhs: &Self) -> bool {
+        self.identity() == rhs.identity()
+    }
+}
+
+impl Eq for GroundStation {}
+
+impl PartialOrd for GroundStation {
+    fn partial_cmp(&self, rhs: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(rhs))
+    }
+}
+
+impl Ord for GroundStation {
+    fn cmp(&self, rhs: &Self) -> std::cmp::Ordering {
+        self.identity().cmp(&rhs.identity())
+    }
+}
+
+impl std::hash::Hash for GroundStation {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.identity().hash(state)
+    }
 }
 
 impl Default for GroundStation {
@@ -45,6 +154,7 @@ impl Default for GroundStation {
             beacon_revision: 3,
             k_frequency_shift: 0,
             code: 0,
+            coordinates: None,
         }
     }
 }
@@ -100,6 +210,13 @@ impl GroundStation {
         s
     }
 
+    /// Defines a [GroundStation] with desired [StationCoordinates]
+    pub fn with_coordinates(&self, coordinates: StationCoordinates) -> Self {
+        let mut s = self.clone();
+        s.coordinates = Some(coordinates);
+        s
+    }
+
     /// Returns true if this [GroundStation] is matched by given [Matcher] specs
     pub fn matches<'a>(&self, matcher: &'a Matcher) -> bool {
         match matcher {
@@ -110,18 +227,27 @@ impl GroundStation {
         }
     }
 
-    /// Returns S1 frequency shift for this [GroundStation] in Hertz
+    /// Returns S1 frequency of this [GroundStation] beacon, in Hertz,
+    /// including its frequency shift.
     pub fn s1_frequency_shift(&self) -> f64 {
-        543.0
-            * USO_FREQ_HZ
-            * (3.0 / 4.0 + 87.0 * self.k_frequency_shift as f64 / 5.0 * 2.0_f64.powi(26))
+        543.0 * USO_FREQ_HZ * (3.0 / 4.0 + self.k_shift_factor())
     }
 
-    /// Returns U2 frequency shift for this [GroundStation] in Hertz
+    /// Returns U2 frequency of this [GroundStation] beacon, in Hertz,
+    /// including its frequency shift.
     pub fn u2_frequency_shift(&self) -> f64 {
-        107.0
-            * USO_FREQ_HZ
-            * (3.0 / 4.0 + 87.0 * self.k_frequency_shift as f64 / 5.0 * 2.0_f64.powi(26))
+        107.0 * USO_FREQ_HZ * (3.0 / 4.0 + self.k_shift_factor())
+    }
+
+    /// Returns the nominal relative frequency offset of this [GroundStation] beacon,
+    /// due to its frequency shift. It is identical on both frequencies.
+    pub fn relative_frequency_shift(&self) -> f64 {
+        self.k_shift_factor() / (3.0 / 4.0)
+    }
+
+    /// Frequency shift factor, 87 k / (5 · 2^26)
+    fn k_shift_factor(&self) -> f64 {
+        87.0 * self.k_frequency_shift as f64 / (5.0 * 2.0_f64.powi(26))
     }
 }
 
@@ -156,6 +282,7 @@ impl std::str::FromStr for GroundStation {
                 .trim()
                 .parse::<u16>()
                 .map_err(|_| ParsingError::GroundStation)?,
+            coordinates: None,
         })
     }
 }
@@ -187,9 +314,126 @@ impl std::fmt::LowerHex for GroundStation {
     }
 }
 
+/// [StationHandle] is a compact and shared reference to a [GroundStation].
+/// It is used to index observations in the [DORIS] record, so the [GroundStation]
+/// definition is not duplicated in every single observation. Cloning a [StationHandle]
+/// does not allocate. It dereferences to [GroundStation], so all [GroundStation]
+/// fields and methods remain accessible.
+///
+/// Two [StationHandle]s are equal if they refer to the same (identical) [GroundStation].
+/// [StationHandle]s are sorted by [GroundStation] unique code first, which is
+/// the order of appearance in the file header, and are hashed by unique code.
+#[derive(Clone, Default)]
+pub struct StationHandle(Arc<GroundStation>);
+
+impl StationHandle {
+    /// Returns the [GroundStation] this [StationHandle] refers to
+    pub fn station(&self) -> &GroundStation {
+        &self.0
+    }
+
+    /// Returns true if both [StationHandle]s share the same [GroundStation] allocation,
+    /// which is the case for all observations of the same station, obtained
+    /// from the same parsing session.
+    pub fn ptr_eq(&self, rhs: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &rhs.0)
+    }
+}
+
+impl std::ops::Deref for StationHandle {
+    type Target = GroundStation;
+
+    fn deref(&self) -> &GroundStation {
+        &self.0
+    }
+}
+
+impl AsRef<GroundStation> for StationHandle {
+    fn as_ref(&self) -> &GroundStation {
+        &self.0
+    }
+}
+
+impl From<GroundStation> for StationHandle {
+    fn from(station: GroundStation) -> Self {
+        Self(Arc::new(station))
+    }
+}
+
+impl From<&GroundStation> for StationHandle {
+    fn from(station: &GroundStation) -> Self {
+        Self(Arc::new(station.clone()))
+    }
+}
+
+impl From<&StationHandle> for StationHandle {
+    fn from(handle: &StationHandle) -> Self {
+        handle.clone()
+    }
+}
+
+impl std::fmt::Debug for StationHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::fmt::Display for StationHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl PartialEq for StationHandle {
+    fn eq(&self, rhs: &Self) -> bool {
+        self.ptr_eq(rhs) || self.0 == rhs.0
+    }
+}
+
+impl Eq for StationHandle {}
+
+impl PartialOrd for StationHandle {
+    fn partial_cmp(&self, rhs: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(rhs))
+    }
+}
+
+impl Ord for StationHandle {
+    fn cmp(&self, rhs: &Self) -> std::cmp::Ordering {
+        if self.ptr_eq(rhs) {
+            std::cmp::Ordering::Equal
+        } else {
+            self.0
+                .code
+                .cmp(&rhs.0.code)
+                .then_with(|| self.0.cmp(&rhs.0))
+        }
+    }
+}
+
+impl std::hash::Hash for StationHandle {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.code.hash(state)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for StationHandle {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for StationHandle {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self::from(GroundStation::deserialize(deserializer)?))
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::GroundStation;
+    use super::{GroundStation, StationHandle};
     use crate::prelude::{DOMESTrackingPoint, DOMES};
     use std::str::FromStr;
 
@@ -198,6 +442,19 @@ mod test {
         let _ = GroundStation::default();
     }
 
+    #[test]
+    fn beacon_frequencies() {
+        let station = GroundStation::default();
+        assert_eq!(station.s1_frequency_shift(), 2036.25E6);
+        assert_eq!(station.u2_frequency_shift(), 401.25E6);
+        assert_eq!(station.relative_frequency_shift(), 0.0);
+
+        let station = station.with_frequency_shift(-15);
+        assert!((station.s1_frequency_shift() - 2036.25E6 + 10559.19).abs() < 1.0E-2);
+        assert!((station.u2_frequency_shift() - 401.25E6 + 2080.72).abs() < 1.0E-2);
+        assert!((station.relative_frequency_shift() + 5.1856E-6).abs() < 1.0E-9);
+    }
+
     #[test]
     fn station_parsing() {
         for (desc, expected) in [
@@ -215,6 +472,7 @@ mod test {
                     beacon_revision: 3,
                     k_frequency_shift: 0,
                     code: 1,
+                    coordinates: None,
                 },
             ),
             (
@@ -231,6 +489,7 @@ mod test {
                     beacon_revision: 3,
                     k_frequency_shift: 0,
                     code: 17,
+                    coordinates: None,
                 },
             ),
             (
@@ -247,6 +506,7 @@ mod test {
                     beacon_revision: 3,
                     k_frequency_shift: -15,
                     code: 12,
+                    coordinates: None,
                 },
             ),
         ] {
@@ -257,4 +517,37 @@ mod test {
             assert_eq!(formatted, desc, "station reciprocal error");
         }
     }
+
+    #[test]
+    fn station_handle() {
+        let toulouse = GroundStation::default()
+            .with_site_label("TLSB")
+            .with_unique_id(2);
+
+        let grasse = GroundStation::default()
+            .with_site_label("GR4B")
+            .with_unique_id(12);
+
+        let handle = StationHandle::from(&toulouse);
+        let shared = handle.clone();
+
+        assert!(handle.ptr_eq(&shared));
+        assert_eq!(handle.label, "TLSB");
+        assert_eq!(handle.station(), &toulouse);
+
+        // identical definitions, distinct allocations
+        let other = StationHandle::from(toulouse.clone());
+        assert!(!handle.ptr_eq(&other));
+        assert_eq!(handle, other);
+
+        // same code, different definition
+        let renamed = StationHandle::from(toulouse.with_site_label("TLHA"));
+        assert_ne!(handle, renamed);
+
+        // sorted by unique code first
+        let grasse = StationHandle::from(grasse);
+        assert!(grasse > handle);
+        assert!(*grasse < *handle);
+        assert!(renamed < handle);
+    }
 }
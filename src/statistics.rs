@@ -0,0 +1,88 @@
+//! Statistical helpers, shared by our processing modules
+
+/// Returns the median value of this (unsorted) serie
+pub(crate) fn median(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+
+    let mid = sorted.len() / 2;
+
+    if sorted.len() % 2 == 1 {
+        Some(sorted[mid])
+    } else {
+        Some((sorted[mid - 1] + sorted[mid]) / 2.0)
+    }
+}
+
+/// Returns (median, scaled median absolute deviation) of this serie.
+/// The MAD is scaled to be a consistent estimator of the standard deviation
+/// of normally distributed data.
+pub(crate) fn median_mad(values: &[f64]) -> Option<(f64, f64)> {
+    let center = median(values)?;
+
+    let deviations = values
+        .iter()
+        .map(|value| (value - center).abs())
+        .collect::<Vec<_>>();
+
+    let mad = median(&deviations)?;
+
+    Some((center, 1.4826 * mad))
+}
+
+/// Returns the mean value of this serie
+pub(crate) fn mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.iter().sum::<f64>() / values.len() as f64)
+    }
+}
+
+/// Returns (mean, standard deviation) of this serie
+pub(crate) fn mean_std_dev(values: &[f64]) -> Option<(f64, f64)> {
+    let mean = mean(values)?;
+
+    let variance = values
+        .iter()
+        .map(|value| (value - mean).powi(2))
+        .sum::<f64>()
+        / values.len() as f64;
+
+    Some((mean, variance.sqrt()))
+}
+
+/// Returns the root mean square of this serie
+pub(crate) fn rms(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        None
+    } else {
+        Some((values.iter().map(|value| value.powi(2)).sum::<f64>() / values.len() as f64).sqrt())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn robust_statistics() {
+        assert_eq!(median(&[]), None);
+        assert_eq!(median(&[3.0, 1.0, 2.0]), Some(2.0));
+        assert_eq!(median(&[4.0, 1.0, 2.0, 3.0]), Some(2.5));
+
+        let (median, mad) = median_mad(&[1.0, 2.0, 3.0, 4.0, 100.0]).unwrap();
+        assert_eq!(median, 3.0);
+        assert!((mad - 1.4826).abs() < 1.0E-9);
+
+        let (mean, std_dev) = mean_std_dev(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]).unwrap();
+        assert_eq!(mean, 5.0);
+        assert_eq!(std_dev, 2.0);
+
+        assert_eq!(rms(&[3.0, -3.0]), Some(3.0));
+    }
+}
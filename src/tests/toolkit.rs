@@ -1,4 +1,7 @@
-use crate::prelude::{ClockOffset, Epoch, EpochFlag, Key, Observable, ObservationKey, DORIS};
+use crate::prelude::{
+    ClockOffset, Duration, Epoch, EpochFlag, GroundStation, Key, Observable, Observation,
+    ObservationKey, Record, StationHandle, TimeScale, DORIS,
+};
 
 #[derive(Debug)]
 pub struct StationObservationData {
@@ -28,6 +31,49 @@ impl TestPoint {
     }
 }
 
+/// Reference [Epoch] of all synthetic [Record]s
+pub fn synthetic_t0() -> Epoch {
+    Epoch::from_gregorian_at_midnight(2018, 6, 13, TimeScale::TAI)
+}
+
+/// Builds a synthetic [Record] for a single [GroundStation], from a list of
+/// (elapsed seconds since [synthetic_t0], observations) samples.
+pub fn synthetic_record(
+    station: &GroundStation,
+    samples: &[(f64, Vec<(Observable, f64)>)],
+) -> Record {
+    let mut record = Record::default();
+    add_synthetic_samples(&mut record, station, samples);
+    record
+}
+
+/// Stacks synthetic samples of a [GroundStation] to an existing [Record].
+pub fn add_synthetic_samples(
+    record: &mut Record,
+    station: &GroundStation,
+    samples: &[(f64, Vec<(Observable, f64)>)],
+) {
+    let t0 = synthetic_t0();
+    let station = StationHandle::from(station);
+
+    for (dt, observations) in samples.iter() {
+        let key = Key {
+            epoch: t0 + Duration::from_seconds(*dt),
+            flag: EpochFlag::OK,
+        };
+
+        let measurements = record.measurements.entry(key).or_default();
+
+        for (observable, value) in observations.iter() {
+            measurements.add_observation(
+                station.clone(),
+                *observable,
+                Observation::default().with_value(*value),
+            );
+        }
+    }
+}
+
 pub fn is_null_doris(dut: &DORIS) {
     for (k, measurement) in dut.record.measurements.iter() {
         for (obs_k, observation) in measurement.observations.iter() {
@@ -85,7 +131,7 @@ pub fn testbench(dut: &DORIS, testpoints: Vec<TestPoint>) {
                 // locate
                 let obs_key = ObservationKey {
                     observable: station_data.observable,
-                    station: station,
+                    station: station.into(),
                 };
 
                 let observation = measurements.observations.get(&obs_key).unwrap_or_else(|| {
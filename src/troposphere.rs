@@ -0,0 +1,319 @@
+//! Tropospheric delay model, using the ground station meteo observations
+use std::collections::HashMap;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::{Epoch, GroundStation, MeteoSample, MeteoSeries, ObservationKey, Record};
+
+/// Standard atmosphere: sea level pressure, in hPa
+const STANDARD_PRESSURE_HPA: f64 = 1013.25;
+
+/// Standard atmosphere: sea level temperature, in °C
+const STANDARD_TEMPERATURE_CELSIUS: f64 = 15.0;
+
+/// Standard atmosphere: temperature lapse rate, in °C/m
+const STANDARD_LAPSE_RATE: f64 = -6.5E-3;
+
+/// Standard atmosphere: relative humidity, in %
+const STANDARD_HUMIDITY: f64 = 50.0;
+
+impl MeteoSample {
+    /// Builds a [MeteoSample] from the standard atmosphere model,
+    /// at given [Epoch] and height above sea level (in meters).
+    /// This is used when the ground station did not report
+    /// (valid) meteo observations.
+    pub fn standard_atmosphere(epoch: Epoch, height_m: f64) -> Self {
+        Self {
+            epoch,
+            pressure: STANDARD_PRESSURE_HPA * (1.0 - 2.2557E-5 * height_m).powf(5.2568),
+            temperature: STANDARD_TEMPERATURE_CELSIUS + STANDARD_LAPSE_RATE * height_m,
+            humidity: STANDARD_HUMIDITY,
+        }
+    }
+}
+
+/// [ZenithDelay] is the tropospheric delay in the zenith direction
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ZenithDelay {
+    /// Zenith hydrostatic delay, in meters
+    pub hydrostatic: f64,
+
+    /// Zenith wet delay, in meters
+    pub wet: f64,
+}
+
+impl ZenithDelay {
+    /// Computes the [ZenithDelay] using the Saastamoinen model, from given [MeteoSample],
+    /// station latitude (in degrees) and height above sea level (in meters).
+    pub fn saastamoinen(sample: &MeteoSample, latitude_deg: f64, height_m: f64) -> Self {
+        let gravity_factor =
+            1.0 - 0.00266 * (2.0 * latitude_deg.to_radians()).cos() - 0.00028E-3 * height_m;
+
+        let temperature_k = sample.temperature + 273.15;
+
+        Self {
+            hydrostatic: 0.0022768 * sample.pressure / gravity_factor,
+            wet: 0.002277 * (1255.0 / temperature_k + 0.05) * sample.water_vapour_pressure(),
+        }
+    }
+
+    /// Returns the total zenith delay, in meters
+    pub fn total(&self) -> f64 {
+        self.hydrostatic + self.wet
+    }
+
+    /// Returns the slant delay, in meters, at given elevation angle (in degrees),
+    /// using desired [MappingFunction].
+    pub fn slant_delay(&self, elevation_deg: f64, mapping: MappingFunction) -> f64 {
+        let (m_h, m_w) = mapping.factors(elevation_deg);
+        self.hydrostatic * m_h + self.wet * m_w
+    }
+}
+
+/// [MappingFunction] projects the [ZenithDelay] to the line of sight
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MappingFunction {
+    /// Black & Eisner (1984), shared by both components
+    #[default]
+    BlackEisner,
+
+    /// Chao (1972), with distinct hydrostatic and wet components
+    Chao,
+}
+
+impl MappingFunction {
+    /// Returns the (hydrostatic, wet) mapping factors at given elevation angle (in degrees)
+    pub fn factors(&self, elevation_deg: f64) -> (f64, f64) {
+        let elevation = elevation_deg.to_radians();
+        let (sin_e, tan_e) = (elevation.sin(), elevation.tan());
+
+        match self {
+            Self::BlackEisner => {
+                let m = 1.001 / (0.002001 + sin_e.powi(2)).sqrt();
+                (m, m)
+            },
+            Self::Chao => (
+                1.0 / (sin_e + 0.00143 / (tan_e + 0.0445)),
+                1.0 / (sin_e + 0.00035 / (tan_e + 0.017)),
+            ),
+        }
+    }
+}
+
+/// [TroposphereModel] evaluates the tropospheric delay at each [GroundStation],
+/// from the meteo observations contained in a [Record]. When a station did not report
+/// (physical) meteo observations at the desired [Epoch], the standard atmosphere is used.
+///
+/// ```
+/// use doris_rs::prelude::*;
+///
+/// let doris = DORIS::from_gzip_file("data/DOR/V3/cs2rx18164.gz")
+///     .unwrap();
+///
+/// let model = TroposphereModel::new(&doris.record)
+///     .with_mapping_function(MappingFunction::Chao);
+///
+/// for (key, measurements) in doris.record.measurements.iter() {
+///     for (obs_key, observation) in measurements.observations.iter() {
+///         // station coordinates and elevation angle are
+///         // to be determined by other means
+///         let (latitude_deg, height_m, elevation_deg) = (43.56, 207.0, 30.0);
+///
+///         if let Some(corrected) = model.corrected_value(
+///             key.epoch,
+///             obs_key,
+///             observation.value,
+///             latitude_deg,
+///             height_m,
+///             elevation_deg,
+///         ) {
+///             // range observation, corrected for the troposphere
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TroposphereModel {
+    /// [MappingFunction] being used
+    pub mapping: MappingFunction,
+
+    /// [MeteoSeries] of each [GroundStation]
+    meteo: HashMap<GroundStation, MeteoSeries>,
+}
+
+impl TroposphereModel {
+    /// Builds a new [TroposphereModel] from the meteo observations of this [Record]
+    pub fn new(record: &Record) -> Self {
+        Self {
+            mapping: Default::default(),
+            meteo: record
+                .meteo_series()
+                .into_iter()
+                .map(|mut series| {
+                    series.retain_physical_mut();
+                    (series.station.clone(), series)
+                })
+                .collect(),
+        }
+    }
+
+    /// Copies and returns [TroposphereModel] with desired [MappingFunction]
+    pub fn with_mapping_function(&self, mapping: MappingFunction) -> Self {
+        let mut s = self.clone();
+        s.mapping = mapping;
+        s
+    }
+
+    /// Returns the [MeteoSample] used for this [GroundStation] at this [Epoch]:
+    /// either interpolated from the station observations, or the standard atmosphere.
+    pub fn meteo_sample(
+        &self,
+        station: &GroundStation,
+        epoch: Epoch,
+        height_m: f64,
+    ) -> MeteoSample {
+        self.meteo
+            .get(station)
+            .and_then(|series| series.interpolate(epoch))
+            .unwrap_or_else(|| MeteoSample::standard_atmosphere(epoch, height_m))
+    }
+
+    /// Returns the [ZenithDelay] at this [GroundStation] and [Epoch],
+    /// for given station latitude (in degrees) and height above sea level (in meters).
+    pub fn zenith_delay(
+        &self,
+        station: &GroundStation,
+        epoch: Epoch,
+        latitude_deg: f64,
+        height_m: f64,
+    ) -> ZenithDelay {
+        let sample = self.meteo_sample(station, epoch, height_m);
+        ZenithDelay::saastamoinen(&sample, latitude_deg, height_m)
+    }
+
+    /// Returns the slant tropospheric delay, in meters, at this [GroundStation] and [Epoch],
+    /// for given station latitude (in degrees), height above sea level (in meters)
+    /// and elevation angle of the satellite (in degrees).
+    pub fn slant_delay(
+        &self,
+        station: &GroundStation,
+        epoch: Epoch,
+        latitude_deg: f64,
+        height_m: f64,
+        elevation_deg: f64,
+    ) -> f64 {
+        self.zenith_delay(station, epoch, latitude_deg, height_m)
+            .slant_delay(elevation_deg, self.mapping)
+    }
+
+    /// Corrects this observation (in meters) for the tropospheric delay.
+    /// Only applies to pseudo range and phase range observations, returns None otherwise.
+    /// The troposphere is not dispersive: both are delayed by the same amount.
+    pub fn corrected_value(
+        &self,
+        epoch: Epoch,
+        key: &ObservationKey,
+        value: f64,
+        latitude_deg: f64,
+        height_m: f64,
+        elevation_deg: f64,
+    ) -> Option<f64> {
+        if !key.observable.is_pseudo_range_observable()
+            && !key.observable.is_phase_range_observable()
+        {
+            return None;
+        }
+
+        let delay = self.slant_delay(&key.station, epoch, latitude_deg, height_m, elevation_deg);
+
+        Some(value - delay)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::{Duration, Frequency, Observable};
+    use crate::tests::toolkit::{synthetic_record, synthetic_t0};
+
+    #[test]
+    fn saastamoinen_model() {
+        let t0 = synthetic_t0();
+
+        let standard = MeteoSample::standard_atmosphere(t0, 0.0);
+        assert_eq!(standard.pressure, 1013.25);
+        assert_eq!(standard.temperature, 15.0);
+
+        // standard atmosphere reference: ZHD = 2.3070m at 45°, sea level
+        let zenith = ZenithDelay::saastamoinen(&standard, 45.0, 0.0);
+        assert!((zenith.hydrostatic - 2.307).abs() < 1.0E-3);
+        assert!(zenith.wet > 0.05 && zenith.wet < 0.2);
+
+        for mapping in [MappingFunction::BlackEisner, MappingFunction::Chao] {
+            let (m_h, m_w) = mapping.factors(90.0);
+            assert!((m_h - 1.0).abs() < 2.0E-3);
+            assert!((m_w - 1.0).abs() < 2.0E-3);
+
+            let (m_h, _) = mapping.factors(10.0);
+            assert!(m_h > 5.0 && m_h < 6.0);
+        }
+
+        // references at 10° elevation
+        let (m_h, m_w) = MappingFunction::BlackEisner.factors(10.0);
+        assert!((m_h - 5.5823).abs() < 1.0E-4);
+        assert_eq!(m_h, m_w);
+
+        let (m_h, m_w) = MappingFunction::Chao.factors(10.0);
+        assert!((m_h - 5.5517).abs() < 1.0E-4);
+        assert!((m_w - 5.6994).abs() < 1.0E-4);
+
+        let station = GroundStation::default()
+            .with_site_label("TLSB")
+            .with_unique_id(13);
+
+        let record = synthetic_record(
+            &station,
+            &[(
+                0.0,
+                vec![
+                    (Observable::PseudoRange(Frequency::DORIS1), 1.0E6),
+                    (Observable::Pressure, 900.0),
+                    (Observable::Temperature, 20.0),
+                    (Observable::HumidityRate, 50.0),
+                ],
+            )],
+        );
+
+        let model = TroposphereModel::new(&record);
+
+        let observed = model.zenith_delay(&station, t0, 45.0, 0.0);
+        assert!((observed.hydrostatic - 0.0022768 * 900.0).abs() < 1.0E-3);
+
+        // not sampled: standard atmosphere
+        let fallback = model.zenith_delay(&station, t0 + Duration::from_seconds(10.0), 45.0, 0.0);
+        assert_eq!(fallback, zenith);
+
+        let code = ObservationKey {
+            station: station.clone().into(),
+            observable: Observable::PseudoRange(Frequency::DORIS1),
+        };
+
+        let corrected = model
+            .corrected_value(t0, &code, 1.0E6, 45.0, 0.0, 90.0)
+            .unwrap();
+
+        assert!((1.0E6 - corrected - observed.total()).abs() < 1.0E-2);
+
+        let pressure = ObservationKey {
+            station: station.into(),
+            observable: Observable::Pressure,
+        };
+
+        assert!(model
+            .corrected_value(t0, &pressure, 900.0, 45.0, 0.0, 90.0)
+            .is_none());
+    }
+}